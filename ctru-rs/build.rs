@@ -30,4 +30,44 @@ fn main() {
     }
 
     println!("cargo:rerun-if-changed={manifest_dir}");
+
+    // Compute the stack size of the main thread, used by the `big-stack` feature.
+    let stack_size = match std::env::var("CTRU_STACK_SIZE") {
+        Ok(value) => {
+            if std::env::var_os("CARGO_FEATURE_BIG_STACK").is_none() {
+                println!(
+                    "cargo:warning=CTRU_STACK_SIZE is ignored unless the `big-stack` feature is enabled"
+                );
+            }
+
+            parse_stack_size(&value).unwrap_or_else(|| {
+                panic!("Invalid CTRU_STACK_SIZE {value:?}: expected a non-zero size in bytes")
+            })
+        }
+        Err(_) => DEFAULT_STACK_SIZE,
+    };
+
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    std::fs::write(format!("{out_dir}/stack_size.rs"), stack_size.to_string())
+        .expect("Could not write the stack size to OUT_DIR");
+
+    println!("cargo:rerun-if-env-changed=CTRU_STACK_SIZE");
+}
+
+/// Stack size of the main thread when `CTRU_STACK_SIZE` is not set.
+const DEFAULT_STACK_SIZE: usize = 2 * 1024 * 1024; // 2MB
+
+fn parse_stack_size(value: &str) -> Option<usize> {
+    let value = value.trim().replace('_', "");
+
+    let size = match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => usize::from_str_radix(hex, 16).ok()?,
+        None => value.parse().ok()?,
+    };
+
+    // The target is 32-bit, so the size must also fit in its `usize`.
+    (size > 0 && u32::try_from(size).is_ok()).then_some(size)
 }
@@ -1,11 +1,9 @@
 //! Camera image example.
 //!
-//! This example demonstrates how to use the built-in cameras to take a picture and display it to the screen.
+//! This example demonstrates how to use the built-in cameras to stream a live preview to the screen.
 
 use ctru::prelude::*;
-use ctru::services::cam::{
-    Cam, Camera, OutputFormat, ShutterSound, Trimming, ViewSize, WhiteBalance,
-};
+use ctru::services::cam::{Cam, Camera, OutputFormat, Trimming, ViewSize, WhiteBalance};
 use ctru::services::gfx::{Flush, Screen, Swap, TopScreen3D};
 use ctru::services::gspgpu::FramebufferFormat;
 
@@ -59,7 +57,16 @@ fn main() {
     let len = camera.final_byte_length();
     let mut buf = vec![0u8; len];
 
-    println!("\nPress R to take a new picture");
+    let (width, height) = camera.final_view_size();
+
+    // The cameras keep capturing until the stream is dropped, so each frame is received without restarting them.
+    let mut stream = camera
+        .stream(WAIT_TIMEOUT)
+        .expect("Failed to start capturing");
+
+    let mut paused = false;
+
+    println!("\nPress R to pause/resume the preview");
     println!("Press Start to exit");
 
     while apt.main_loop() {
@@ -72,20 +79,14 @@ fn main() {
 
         // If the user presses the R button.
         if keys_down.contains(KeyPad::R) {
-            println!("Capturing new image");
-
-            let camera = &mut cam.both_outer_cams;
-
-            // Take a picture and write it to the buffer.
-            camera
-                .take_picture(&mut buf, WAIT_TIMEOUT)
-                .expect("Failed to take picture");
-
-            let (width, height) = camera.final_view_size();
+            paused = !paused;
+        }
 
-            // Play the normal shutter sound.
-            cam.play_shutter_sound(ShutterSound::Normal)
-                .expect("Failed to play shutter sound");
+        if !paused {
+            // Wait for the next frame and write it to the buffer.
+            stream
+                .next_frame(&mut buf)
+                .expect("Failed to capture frame");
 
             {
                 let (mut left_side, mut right_side) = top_screen_3d.split_mut();
@@ -110,9 +111,9 @@ fn main() {
             // We will only flush and swap the "camera" screen, since the other screen is handled by the `Console`.
             top_screen_3d.flush_buffers();
             top_screen_3d.swap_buffers();
-
-            gfx.wait_for_vblank();
         }
+
+        gfx.wait_for_vblank();
     }
 }
 
@@ -6,11 +6,9 @@ use ctru::prelude::*;
 use std::io::BufWriter;
 
 fn main() {
-    // Setup Graphics, Controller Inputs, Application runtime.
+    // Setup Application runtime, Graphics and Controller Inputs.
     // These is standard setup any app would need.
-    let gfx = Gfx::new().expect("Couldn't obtain GFX controller");
-    let mut hid = Hid::new().expect("Couldn't obtain HID controller");
-    let apt = Apt::new().expect("Couldn't obtain APT controller");
+    let (apt, gfx, mut hid) = ctru::init().expect("Couldn't initialize the basic services");
 
     // Create a Console to print our "Hello, World!" to.
     let _console = Console::new(gfx.top_screen.borrow_mut());
@@ -3,6 +3,10 @@
 //! This applet displays error text as a pop-up message on the lower screen.
 
 use crate::services::{apt::Apt, gfx::Gfx};
+use crate::utf16::Utf16Writer;
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
 
 use ctru_sys::errorConf;
 
@@ -59,14 +63,7 @@ impl PopUp {
     /// 1900 UTF-16 code units in length after conversion.
     #[doc(alias = "errorText")]
     pub fn set_text(&mut self, text: &str) {
-        for (idx, code_unit) in text
-            .encode_utf16()
-            .take(self.state.Text.len() - 1)
-            .chain(std::iter::once(0))
-            .enumerate()
-        {
-            self.state.Text[idx] = code_unit;
-        }
+        let _ = Utf16Writer::new(&mut self.state.Text).write_str(text);
     }
 
     /// Launches the error applet.
@@ -75,6 +72,48 @@ impl PopUp {
         unsafe { self.launch_unchecked() }
     }
 
+    /// Displays `text` over as many pop-ups as needed to show it whole, launching the error applet once per page.
+    ///
+    /// The error applet cuts off any text that doesn't fit on its window, which often happens with long messages (such as panic messages).
+    /// This function word-wraps `text` and splits it into pages, each one ending with a "page N/M" indicator when there is more than one.
+    /// The user moves to the next page by closing the current pop-up.
+    ///
+    /// # Notes
+    ///
+    /// The error applet uses a proportional font, so the amount of text fitting on a page is estimated.
+    /// Lines are wrapped at 40 characters, with 12 lines per page.
+    ///
+    /// # Errors
+    ///
+    /// If launching one of the pages fails (e.g. because the user pressed the Home button), the remaining pages are not displayed
+    /// and the error is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::applets::error::{PopUp, WordWrap};
+    /// use ctru::services::{apt::Apt, gfx::Gfx};
+    ///
+    /// let apt = Apt::new()?;
+    /// let gfx = Gfx::new()?;
+    ///
+    /// let report = "A very long report. ".repeat(100);
+    ///
+    /// let mut popup = PopUp::new(WordWrap::Enabled);
+    /// popup.launch_paged(&apt, &gfx, &report)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "errorDisp")]
+    pub fn launch_paged(&mut self, _apt: &Apt, _gfx: &Gfx, text: &str) -> Result<(), Error> {
+        unsafe { self.launch_paged_unchecked(text) }
+    }
+
     /// Launches the error applet without requiring an [`Apt`] or [`Gfx`] handle.
     ///
     /// # Safety
@@ -92,6 +131,80 @@ impl PopUp {
             _ => Err(Error::Unknown),
         }
     }
+
+    /// Launches the error applet for each page of `text` without requiring an [`Apt`] or [`Gfx`] handle.
+    ///
+    /// # Safety
+    ///
+    /// See [`PopUp::launch_unchecked()`].
+    unsafe fn launch_paged_unchecked(&mut self, text: &str) -> Result<(), Error> {
+        for page in paginate(text, LINE_WIDTH, LINES_PER_PAGE) {
+            self.set_text(&page);
+
+            unsafe { self.launch_unchecked()? };
+        }
+
+        Ok(())
+    }
+}
+
+/// Estimated number of characters fitting on a line of the error applet window.
+const LINE_WIDTH: usize = 40;
+/// Estimated number of lines fitting on the error applet window.
+const LINES_PER_PAGE: usize = 12;
+
+/// Word-wrap `text` to lines of (at most) `width` characters, and group them into pages of (at most) `height` lines.
+///
+/// When the text spans multiple pages, the last two lines of every page are used by a page indicator.
+fn paginate(text: &str, width: usize, height: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        let mut line_len = 0;
+
+        for word in paragraph.split_whitespace() {
+            let mut word: Vec<char> = word.chars().collect();
+
+            // Split words which don't fit on a single line.
+            while word.len() > width {
+                if line_len > 0 {
+                    lines.push(std::mem::take(&mut line));
+                    line_len = 0;
+                }
+
+                lines.push(word.drain(..width).collect());
+            }
+
+            if line_len > 0 && line_len + 1 + word.len() > width {
+                lines.push(std::mem::take(&mut line));
+                line_len = 0;
+            }
+
+            if line_len > 0 {
+                line.push(' ');
+                line_len += 1;
+            }
+
+            line.extend(&word);
+            line_len += word.len();
+        }
+
+        lines.push(line);
+    }
+
+    if lines.len() <= height {
+        return vec![lines.join("\n")];
+    }
+
+    let chunks: Vec<_> = lines.chunks(height.saturating_sub(2).max(1)).collect();
+    let count = chunks.len();
+
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, chunk)| format!("{}\n\n(page {}/{count})", chunk.join("\n"), i + 1))
+        .collect()
 }
 
 /// Sets a custom [panic hook](https://doc.rust-lang.org/std/panic/fn.set_hook.html) that uses the error applet to display panic messages.
@@ -105,13 +218,56 @@ impl PopUp {
 /// # Notes
 ///
 /// * If the [`Gfx`] service is not initialized during a panic, the error applet will not be displayed and the old panic hook will be called.
+/// * CPU exceptions (e.g. data aborts) don't panic by default. Use [`panic_on_exception()`](crate::os::exception::panic_on_exception)
+///   to have them displayed by the error applet too.
 pub fn set_panic_hook(call_old_hook: bool) {
+    set_panic_hook_inner(call_old_hook, None);
+}
+
+/// Sets a custom [panic hook](https://doc.rust-lang.org/std/panic/fn.set_hook.html) that uses the error applet to display panic messages,
+/// and additionally appends a crash log to the file at `path`.
+///
+/// Each log entry contains the panicking thread's name and id, the panic message and a backtrace.
+/// The crash log is written before the error applet is displayed, so it will be available even if the user
+/// powers off the console while the popup is open.
+///
+/// See [`set_panic_hook`] for more information about the error applet popup and the `call_old_hook` parameter.
+///
+/// # Notes
+///
+/// * Writing the log is best-effort: if the file cannot be opened or written to (e.g. because the SD card is full or missing),
+///   the failure is silently ignored rather than causing a second panic.
+/// * Paths such as `sdmc:/my_app/crash.log` can be used to write to the SD card. Parent directories are not created automatically.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// use std::path::Path;
+///
+/// ctru::applets::error::set_panic_hook_with_log(true, Path::new("sdmc:/crash.log"));
+/// ```
+pub fn set_panic_hook_with_log(call_old_hook: bool, path: &Path) {
+    set_panic_hook_inner(call_old_hook, Some(path.to_path_buf()));
+}
+
+fn set_panic_hook_inner(call_old_hook: bool, log_path: Option<PathBuf>) {
     use crate::services::gfx::GFX_ACTIVE;
     use std::sync::TryLockError;
 
     let old_hook = std::panic::take_hook();
 
     std::panic::set_hook(Box::new(move |panic_info| {
+        let thread = std::thread::current();
+
+        let name = thread.name().unwrap_or("<unnamed>");
+
+        let message = format!("thread '{name}' {panic_info}");
+
+        if let Some(path) = &log_path {
+            write_crash_log(path, &thread, &message);
+        }
+
         // If we get a `WouldBlock` error, we know that the `Gfx` service has been initialized.
         // Otherwise fallback to using the old panic hook.
         if let (Err(TryLockError::WouldBlock), Ok(_apt)) = (GFX_ACTIVE.try_lock(), Apt::new()) {
@@ -119,18 +275,10 @@ pub fn set_panic_hook(call_old_hook: bool) {
                 old_hook(panic_info);
             }
 
-            let thread = std::thread::current();
-
-            let name = thread.name().unwrap_or("<unnamed>");
-
-            let message = format!("thread '{name}' {panic_info}");
-
             let mut popup = PopUp::new(WordWrap::Enabled);
 
-            popup.set_text(&message);
-
             unsafe {
-                let _ = popup.launch_unchecked();
+                let _ = popup.launch_paged_unchecked(&message);
             }
         } else {
             old_hook(panic_info);
@@ -138,6 +286,26 @@ pub fn set_panic_hook(call_old_hook: bool) {
     }));
 }
 
+/// Append a crash log entry to the file at `path`, ignoring any I/O errors.
+fn write_crash_log(path: &Path, thread: &std::thread::Thread, message: &str) {
+    use std::backtrace::Backtrace;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) else {
+        return;
+    };
+
+    let backtrace = Backtrace::force_capture();
+
+    let _ = writeln!(
+        file,
+        "--- panic in {:?} ---\n{message}\nbacktrace:\n{backtrace}\n",
+        thread.id()
+    );
+    let _ = file.flush();
+}
+
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -151,3 +319,24 @@ impl std::fmt::Display for Error {
 }
 
 impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paginate_wraps_and_splits() {
+        assert_eq!(paginate("short text", 10, 4), ["short text"]);
+
+        // Words are moved to the next line, overlong words are split.
+        assert_eq!(
+            paginate("one two three\nabcdefghijkl", 10, 4),
+            ["one two\nthree\nabcdefghij\nkl"]
+        );
+
+        let pages = paginate("a b c d e f", 1, 4);
+        assert_eq!(pages.len(), 3);
+        assert_eq!(pages[0], "a\nb\n\n(page 1/3)");
+        assert_eq!(pages[2], "e\nf\n\n(page 3/3)");
+    }
+}
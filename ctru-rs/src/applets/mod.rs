@@ -9,5 +9,7 @@
 //! Applets block execution of the thread that launches them as long as the user doesn't close the applet.
 
 pub mod error;
+#[cfg(feature = "mii")]
 pub mod mii_selector;
+#[cfg(feature = "swkbd")]
 pub mod swkbd;
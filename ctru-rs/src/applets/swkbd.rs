@@ -4,6 +4,7 @@
 #![doc(alias = "keyboard")]
 
 use crate::services::{apt::Apt, gfx::Gfx};
+use crate::utf16::Utf16Writer;
 use ctru_sys::{
     aptLaunchLibraryApplet, aptSetMessageCallback, envGetAptAppId, svcCloseHandle,
     svcCreateMemoryBlock, APT_SendParameter, SwkbdButton, SwkbdDictWord, SwkbdLearningData,
@@ -13,9 +14,9 @@ use ctru_sys::{
 use bitflags::bitflags;
 
 use std::borrow::Cow;
-use std::fmt::Display;
+use std::fmt::{Display, Write};
 use std::iter::once;
-use std::str;
+use std::str::{self, FromStr};
 
 type CallbackFunction = dyn Fn(&str) -> (CallbackResult, Option<Cow<'static, str>>);
 
@@ -25,6 +26,8 @@ pub struct SoftwareKeyboard {
     state: Box<SwkbdState>,
     filter_callback: Option<Box<CallbackFunction>>,
     initial_text: Option<Cow<'static, str>>,
+    // Pointed to by `state.extra.dict`, so it must live as long as the configuration.
+    dict_words: Vec<SwkbdDictWord>,
 }
 
 /// Configuration structure to setup the Parental Lock applet.
@@ -243,6 +246,7 @@ impl SoftwareKeyboard {
                 state,
                 filter_callback: None,
                 initial_text: None,
+                dict_words: Vec::new(),
             }
         }
     }
@@ -270,17 +274,107 @@ impl SoftwareKeyboard {
     /// ```
     #[doc(alias = "swkbdInputText")]
     pub fn launch(&mut self, apt: &Apt, gfx: &Gfx) -> Result<(String, Button), Error> {
-        let mut output = String::new();
+        let (output, result) = self.launch_detailed(apt, gfx);
 
-        match self.swkbd_input_text(&mut output, apt, gfx) {
-            ctru_sys::SWKBD_BUTTON_NONE => Err(self.state.result.into()),
-            ctru_sys::SWKBD_BUTTON_LEFT => Ok((output, Button::Left)),
-            ctru_sys::SWKBD_BUTTON_MIDDLE => Ok((output, Button::Middle)),
-            ctru_sys::SWKBD_BUTTON_RIGHT => Ok((output, Button::Right)),
-            _ => unreachable!(),
+        match result {
+            ctru_sys::SWKBD_D1_CLICK0 | ctru_sys::SWKBD_D2_CLICK0 => Ok((output, Button::Left)),
+            ctru_sys::SWKBD_D2_CLICK1 => Ok((output, Button::Middle)),
+            ctru_sys::SWKBD_D0_CLICK | ctru_sys::SWKBD_D1_CLICK1 | ctru_sys::SWKBD_D2_CLICK2 => {
+                Ok((output, Button::Right))
+            }
+            _ => Err(result.into()),
         }
     }
 
+    /// Launches the applet based on the given configuration and parses the text input as a `T`,
+    /// along with the button that was pressed to exit the prompt.
+    ///
+    /// This is especially useful with a [`Kind::Numpad`] keyboard, e.g. to ask for a PIN or a quantity.
+    /// The configured [validation](SoftwareKeyboard::set_validation) still applies before the text is parsed.
+    ///
+    /// The parsed value is `None` if the text can't be parsed as a `T`. The text is parsed whichever button was pressed,
+    /// so check the button to tell apart a cancelled prompt (usually [`Button::Left`]).
+    ///
+    /// # Errors
+    ///
+    /// Errors are the same as [`SoftwareKeyboard::launch()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// # use ctru::services::{apt::Apt, gfx::Gfx};
+    /// #
+    /// # let gfx = Gfx::new().unwrap();
+    /// # let apt = Apt::new().unwrap();
+    /// #
+    /// use ctru::applets::swkbd::{Button, ButtonConfig, Filters, Kind, SoftwareKeyboard, ValidInput};
+    /// let mut keyboard = SoftwareKeyboard::new(Kind::Numpad, ButtonConfig::LeftRight);
+    /// keyboard.set_validation(ValidInput::NotEmptyNotBlank, Filters::empty());
+    ///
+    /// match keyboard.launch_parse::<u16>(&apt, &gfx)? {
+    ///     (_, Button::Left) => println!("Cancelled"),
+    ///     (Some(quantity), _) => println!("Buying {quantity} items"),
+    ///     (None, _) => println!("That's not a valid quantity"),
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "swkbdInputText")]
+    pub fn launch_parse<T: FromStr>(
+        &mut self,
+        apt: &Apt,
+        gfx: &Gfx,
+    ) -> Result<(Option<T>, Button), Error> {
+        let (text, button) = self.launch(apt, gfx)?;
+
+        Ok((text.trim().parse().ok(), button))
+    }
+
+    /// Launches the applet based on the given configuration and returns a string containing the text input,
+    /// along with the raw result code reported by the applet.
+    ///
+    /// Contrary to [`SoftwareKeyboard::launch()`], the result code tells apart which button was pressed for each [`ButtonConfig`]
+    /// (e.g. `SWKBD_D2_CLICK1` is the middle button of a three-button layout), and errors are not converted to [`Error`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// # use ctru::services::{apt::Apt, gfx::Gfx};
+    /// #
+    /// # let gfx = Gfx::new().unwrap();
+    /// # let apt = Apt::new().unwrap();
+    /// #
+    /// use ctru::applets::swkbd::{ButtonConfig, Kind, SoftwareKeyboard};
+    /// let mut keyboard = SoftwareKeyboard::new(Kind::Normal, ButtonConfig::LeftMiddleRight);
+    ///
+    /// let (text, result) = keyboard.launch_detailed(&apt, &gfx);
+    ///
+    /// match result {
+    ///     ctru_sys::SWKBD_D2_CLICK0 => println!("Cancelled"),
+    ///     ctru_sys::SWKBD_D2_CLICK1 => println!("Forgot: {text}"),
+    ///     ctru_sys::SWKBD_D2_CLICK2 => println!("Confirmed: {text}"),
+    ///     _ => println!("The keyboard was closed without pressing a button"),
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "swkbdInputText", alias = "SwkbdResult")]
+    pub fn launch_detailed(&mut self, apt: &Apt, gfx: &Gfx) -> (String, ctru_sys::SwkbdResult) {
+        let mut output = String::new();
+
+        self.swkbd_input_text(&mut output, apt, gfx);
+
+        (output, self.state.result)
+    }
+
     /// Set special features for this keyboard.
     ///
     /// # Example
@@ -420,7 +514,7 @@ impl SoftwareKeyboard {
     /// Passing [`None`] will clear the hint text.
     ///
     /// The hint text will be converted to UTF-16 when passed to the software keyboard, and the text will be truncated
-    /// if the length exceeds 64 code units after conversion.
+    /// if the length exceeds 64 code units after conversion. Returns `true` if the text was truncated.
     ///
     /// # Example
     ///
@@ -431,22 +525,143 @@ impl SoftwareKeyboard {
     /// use ctru::applets::swkbd::SoftwareKeyboard;
     /// let mut keyboard = SoftwareKeyboard::default();
     ///
-    /// keyboard.set_hint_text(Some("Write here what you like!"));
+    /// let truncated = keyboard.set_hint_text(Some("Write here what you like!"));
+    /// assert!(!truncated);
     /// #
     /// # }
     #[doc(alias = "swkbdSetHintText")]
-    pub fn set_hint_text(&mut self, text: Option<&str>) {
+    pub fn set_hint_text(&mut self, text: Option<&str>) -> bool {
+        let mut writer = Utf16Writer::new(&mut self.state.hint_text);
+
         if let Some(text) = text {
-            for (idx, code_unit) in text
-                .encode_utf16()
-                .take(self.state.hint_text.len() - 1)
-                .chain(once(0))
-                .enumerate()
-            {
-                self.state.hint_text[idx] = code_unit;
-            }
+            let _ = writer.write_str(text);
+        }
+
+        writer.is_truncated()
+    }
+
+    /// Returns the hint text currently set, as stored by [`SoftwareKeyboard::set_hint_text()`] (possibly truncated).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # fn main() {
+    /// #
+    /// use ctru::applets::swkbd::SoftwareKeyboard;
+    /// let mut keyboard = SoftwareKeyboard::default();
+    ///
+    /// keyboard.set_hint_text(Some("Name"));
+    /// assert_eq!(keyboard.hint_text(), "Name");
+    /// #
+    /// # }
+    pub fn hint_text(&self) -> String {
+        let hint_text = &self.state.hint_text;
+        let len = hint_text
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(hint_text.len());
+
+        String::from_utf16_lossy(&hint_text[..len])
+    }
+
+    /// Set the dictionary of words suggested by the keyboard's predictive input.
+    ///
+    /// Each entry is a `(reading, word)` pair: when the user writes the reading, the keyboard suggests the word as a completion.
+    /// Both are truncated to 40 UTF-16 code units. Passing an empty slice clears the dictionary.
+    ///
+    /// # Notes
+    ///
+    /// Suggestions are only shown if [`Features::PREDICTIVE_INPUT`] is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # fn main() {
+    /// #
+    /// use ctru::applets::swkbd::{Features, SoftwareKeyboard};
+    /// let mut keyboard = SoftwareKeyboard::default();
+    ///
+    /// keyboard.set_features(Features::PREDICTIVE_INPUT);
+    /// // Writing "ty" suggests "thank you".
+    /// keyboard.set_dict_words(&[("ty", "thank you"), ("brb", "be right back")]);
+    /// #
+    /// # }
+    #[doc(alias = "swkbdSetDictionary")]
+    #[doc(alias = "swkbdSetDictWord")]
+    pub fn set_dict_words(&mut self, words: &[(&str, &str)]) {
+        self.dict_words = words
+            .iter()
+            .map(|&(reading, word)| {
+                let mut entry = SwkbdDictWord {
+                    all_languages: true,
+                    ..Default::default()
+                };
+
+                let _ = Utf16Writer::new(&mut entry.reading).write_str(reading);
+                let _ = Utf16Writer::new(&mut entry.word).write_str(word);
+
+                entry
+            })
+            .collect();
+
+        let mut extra = unsafe { self.state.__bindgen_anon_1.extra };
+
+        if self.dict_words.is_empty() {
+            extra.dict = std::ptr::null();
+            self.state.dict_word_count = 0;
         } else {
-            self.state.hint_text[0] = 0;
+            extra.dict = self.dict_words.as_ptr();
+            self.state.dict_word_count = self.dict_words.len() as _;
+        }
+
+        self.state.__bindgen_anon_1.extra = extra;
+    }
+
+    /// Let the keyboard's predictive input learn from the user's previous inputs.
+    ///
+    /// On every launch, the keyboard loads its status (e.g. the last used keyboard page) and learning data from
+    /// `status` and `learning`, and writes them back once the user is done. Keep both structs in the application's
+    /// state (or save them to a file) between launches, so that suggestions improve over successive prompts.
+    /// Zeroed structs can be used the first time.
+    ///
+    /// # Safety
+    ///
+    /// The keyboard keeps pointers to `status` and `learning`: both must stay valid (and not be moved)
+    /// until the last call to [`SoftwareKeyboard::launch()`] on this configuration has returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # fn main() {
+    /// #
+    /// use ctru::applets::swkbd::{Features, SoftwareKeyboard};
+    /// use ctru_sys::{SwkbdLearningData, SwkbdStatusData};
+    ///
+    /// // These must outlive every launch of the keyboard.
+    /// let mut status = SwkbdStatusData::default();
+    /// let mut learning = SwkbdLearningData::default();
+    ///
+    /// let mut keyboard = SoftwareKeyboard::default();
+    /// keyboard.set_features(Features::PREDICTIVE_INPUT);
+    ///
+    /// unsafe { keyboard.enable_learning(&mut status, &mut learning) };
+    /// #
+    /// # }
+    /// ```
+    #[doc(alias = "swkbdSetStatusData")]
+    #[doc(alias = "swkbdSetLearningData")]
+    pub unsafe fn enable_learning(
+        &mut self,
+        status: &mut SwkbdStatusData,
+        learning: &mut SwkbdLearningData,
+    ) {
+        // Load the data on launch, and save it back once the keyboard is closed.
+        unsafe {
+            ctru_sys::swkbdSetStatusData(self.state.as_mut(), status, true, true);
+            ctru_sys::swkbdSetLearningData(self.state.as_mut(), learning, true, true);
         }
     }
 
@@ -541,16 +756,7 @@ impl SoftwareKeyboard {
     /// # }
     #[doc(alias = "swkbdSetButton")]
     pub fn configure_button(&mut self, button: Button, text: &str, submit: bool) {
-        let button_text = &mut self.state.button_text[button as usize];
-
-        for (idx, code_unit) in text
-            .encode_utf16()
-            .take(button_text.len() - 1)
-            .chain(once(0))
-            .enumerate()
-        {
-            button_text[idx] = code_unit;
-        }
+        let _ = Utf16Writer::new(&mut self.state.button_text[button as usize]).write_str(text);
 
         self.state.button_submits_text[button as usize] = submit;
     }
@@ -612,7 +818,7 @@ impl SoftwareKeyboard {
 
         let status_off = shared_mem_size;
 
-        shared_mem_size += if swkbd.initial_learning_offset >= 0 {
+        shared_mem_size += if swkbd.initial_status_offset >= 0 {
             std::mem::size_of::<SwkbdStatusData>()
         } else {
             0
@@ -834,14 +1040,7 @@ impl SoftwareKeyboard {
         swkbd.callback_result = result as _;
 
         if let Some(msg) = retmsg.as_deref() {
-            for (idx, code_unit) in msg
-                .encode_utf16()
-                .take(swkbd.callback_msg.len() - 1)
-                .chain(once(0))
-                .enumerate()
-            {
-                swkbd.callback_msg[idx] = code_unit;
-            }
+            let _ = Utf16Writer::new(&mut swkbd.callback_msg).write_str(msg);
         }
 
         let _ = unsafe {
@@ -993,3 +1192,63 @@ from_impl!(ValidInput, u32);
 from_impl!(ButtonConfig, i32);
 from_impl!(PasswordMode, u32);
 from_impl!(CallbackResult, u32);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hint_text_ascii() {
+        let mut keyboard = SoftwareKeyboard::default();
+
+        assert!(!keyboard.set_hint_text(Some("Write here")));
+        assert_eq!(keyboard.hint_text(), "Write here");
+
+        assert!(!keyboard.set_hint_text(None));
+        assert_eq!(keyboard.hint_text(), "");
+    }
+
+    #[test]
+    fn hint_text_at_limit() {
+        let mut keyboard = SoftwareKeyboard::default();
+        let text = "a".repeat(64);
+
+        assert!(!keyboard.set_hint_text(Some(&text)));
+        assert_eq!(keyboard.hint_text(), text);
+    }
+
+    #[test]
+    fn hint_text_truncated() {
+        let mut keyboard = SoftwareKeyboard::default();
+
+        // The emoji takes two code units, and doesn't fit after the first 63.
+        let text = format!("{}😀", "a".repeat(63));
+
+        assert!(keyboard.set_hint_text(Some(&text)));
+        assert_eq!(keyboard.hint_text(), "a".repeat(63));
+    }
+
+    #[test]
+    fn set_dict_words() {
+        let mut keyboard = SoftwareKeyboard::default();
+
+        keyboard.set_dict_words(&[("one", "1"), ("two", "2"), ("three", "3")]);
+        assert_eq!(keyboard.state.dict_word_count, 3);
+
+        let dict = unsafe { keyboard.state.__bindgen_anon_1.extra.dict };
+        assert_eq!(dict, keyboard.dict_words.as_ptr());
+        assert_eq!(
+            keyboard.dict_words[1].reading[..4],
+            ['t' as u16, 'w' as u16, 'o' as u16, 0]
+        );
+
+        // Words longer than the field are truncated.
+        let long = "a".repeat(100);
+        keyboard.set_dict_words(&[(&long, &long)]);
+        assert_eq!(keyboard.state.dict_word_count, 1);
+        assert_eq!(keyboard.dict_words[0].word.last(), Some(&0));
+
+        keyboard.set_dict_words(&[]);
+        assert_eq!(keyboard.state.dict_word_count, 0);
+    }
+}
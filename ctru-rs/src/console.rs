@@ -3,9 +3,12 @@
 //! The [`Console`] works as a virtual shell that renders on screen all output of `stdout`. As such, it is useful as a basic interface to show info to the user,
 //! such as in simple "Hello World" applications or more complex software that does not need much user interaction.
 //!
+//! For simple interactive interfaces, [`Menu`] can render a list of selectable items on a [`Console`].
+//!
 //! Have a look at [`Soc::redirect_to_3dslink()`](crate::services::soc::Soc::redirect_to_3dslink) for a better alternative when debugging applications.
 
 use std::cell::{RefMut, UnsafeCell};
+use std::io::Write;
 
 use ctru_sys::{consoleClear, consoleInit, consoleSelect, consoleSetWindow, PrintConsole};
 
@@ -64,6 +67,7 @@ impl<S: Screen + Swap + Flush> ConsoleScreen for S {}
 pub struct Console<'screen> {
     context: Box<UnsafeCell<PrintConsole>>,
     screen: RefMut<'screen, dyn ConsoleScreen>,
+    auto_flush: bool,
 }
 
 impl<'screen> Console<'screen> {
@@ -110,7 +114,11 @@ impl<'screen> Console<'screen> {
 
         unsafe { consoleInit(screen.as_raw(), context.get()) };
 
-        Console { context, screen }
+        Console {
+            context,
+            screen,
+            auto_flush: false,
+        }
     }
 
     /// Returns `true` if a valid [`Console`] to print on is currently selected.
@@ -193,10 +201,138 @@ impl<'screen> Console<'screen> {
         }
     }
 
-    /// Clear all text from the console.
+    /// Clear all text from the console, and move the cursor back to the top left corner of the window.
+    ///
+    /// # Notes
+    ///
+    /// Pending output of `stdout` and `stderr` is flushed first, so that it isn't printed after the console is cleared.
+    /// This console is cleared even if it isn't the [selected](Console::select) one.
     #[doc(alias = "consoleClear")]
     pub fn clear(&self) {
-        unsafe { consoleClear() }
+        flush_std_streams();
+
+        unsafe {
+            let previous = consoleSelect(self.context.get());
+            consoleClear();
+            consoleSelect(previous);
+        }
+    }
+
+    /// Returns the position of the cursor as (column, row), relative to the top left corner of the console's window.
+    ///
+    /// # Notes
+    ///
+    /// Pending output of `stdout` and `stderr` is flushed first, so that the position takes it into account.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// # use ctru::services::gfx::Gfx;
+    /// # let gfx = Gfx::new()?;
+    /// #
+    /// use std::io::Write;
+    /// use ctru::console::Console;
+    ///
+    /// let mut bottom_console = Console::new(gfx.bottom_screen.borrow_mut());
+    /// let top_console = Console::new(gfx.top_screen.borrow_mut());
+    /// top_console.select();
+    ///
+    /// // Writing to a console prints on it, even if it isn't the selected one.
+    /// bottom_console.set_cursor_position(0, 2)?;
+    /// write!(bottom_console, "Hello")?;
+    ///
+    /// assert_eq!(bottom_console.cursor_position(), (5, 2));
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn cursor_position(&self) -> (i32, i32) {
+        flush_std_streams();
+
+        let context = unsafe { &*self.context.get() };
+
+        (context.cursorX, context.cursorY)
+    }
+
+    /// Move the cursor to (column, row), relative to the top left corner of the console's window.
+    ///
+    /// The next text printed on this console will start at the new position.
+    /// Pending output of `stdout` and `stderr` is flushed first, so that it is printed at the previous position.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the position is outside of the console's window.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// # use ctru::services::gfx::Gfx;
+    /// # let gfx = Gfx::new()?;
+    /// #
+    /// use ctru::console::Console;
+    ///
+    /// let mut top_console = Console::new(gfx.top_screen.borrow_mut());
+    ///
+    /// // Print on the last row of the window.
+    /// top_console.set_cursor_position(0, 29)?;
+    /// println!("Press START to exit");
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_cursor_position(&mut self, x: i32, y: i32) -> Result<(), Error> {
+        flush_std_streams();
+
+        let context = self.context.get_mut();
+
+        if !(0..context.windowWidth).contains(&x) {
+            return Err(Error::CoordinateOutOfBounds(Axis::X));
+        }
+        if !(0..context.windowHeight).contains(&y) {
+            return Err(Error::CoordinateOutOfBounds(Axis::Y));
+        }
+
+        context.cursorX = x;
+        context.cursorY = y;
+
+        Ok(())
+    }
+
+    /// Choose what happens when text is printed past the last row of the console's window.
+    ///
+    /// If scrolling is enabled (the default), all rows are moved up to make space for the new one, discarding the first row.
+    /// Otherwise, the cursor wraps back to the first row, and the new text is printed over the previous one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// # use ctru::services::gfx::Gfx;
+    /// # let gfx = Gfx::new()?;
+    /// #
+    /// use ctru::console::Console;
+    ///
+    /// let mut top_console = Console::new(gfx.top_screen.borrow_mut());
+    ///
+    /// // Keep the text in place, e.g. for a status screen redrawn from the top on each frame.
+    /// top_console.set_scroll(false);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_scroll(&mut self, scroll: bool) {
+        self.context.get_mut().PrintChar = if scroll { None } else { Some(wrap_print_char) };
     }
 
     /// Resize the console to fit in a smaller portion of the screen.
@@ -291,6 +427,78 @@ impl<'screen> Console<'screen> {
         self.set_window(0, 0, width, 30).unwrap();
     }
 
+    /// Enable or disable automatic flushing when writing to this [`Console`] via its [`Write`] implementation.
+    ///
+    /// When enabled, every `write!` to the [`Console`] is followed by a call to [`Console::flush()`], so the text appears on screen immediately.
+    /// This is disabled by default.
+    ///
+    /// # Notes
+    ///
+    /// Flushing after every write is considerably slower than letting the output be buffered, especially when printing many small pieces of text.
+    /// It's best used for debugging, e.g. when a crash may occur right after printing something without a trailing newline.
+    ///
+    /// This setting doesn't affect `print!` and `println!`, which always go through the line-buffered standard output.
+    /// Use [`Console::flush()`] to force their output to be shown.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// # use ctru::services::gfx::Gfx;
+    /// # let gfx = Gfx::new()?;
+    /// #
+    /// use std::io::Write;
+    /// use ctru::console::Console;
+    ///
+    /// let mut top_console = Console::new(gfx.top_screen.borrow_mut());
+    /// top_console.set_flush(true);
+    ///
+    /// // This is visible right away, even without a newline.
+    /// write!(top_console, "Loading...")?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_flush(&mut self, auto: bool) {
+        self.auto_flush = auto;
+    }
+
+    /// Flush any pending output of `stdout` and `stderr` and make sure it's rendered on screen.
+    ///
+    /// # Notes
+    ///
+    /// Text printed via `print!` without a trailing newline stays in the standard output's buffer until a newline is printed
+    /// or the buffer is flushed, so it may not appear on screen right away.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// # use ctru::services::gfx::Gfx;
+    /// # let gfx = Gfx::new()?;
+    /// #
+    /// use ctru::console::Console;
+    ///
+    /// let mut top_console = Console::new(gfx.top_screen.borrow_mut());
+    ///
+    /// print!("Progress: 50%");
+    /// top_console.flush();
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn flush(&mut self) {
+        flush_std_streams();
+
+        self.flush_buffers();
+    }
+
     /// Returns this [`Console`]'s maximum character width depending on the screen used.
     ///
     /// # Example
@@ -327,6 +535,38 @@ impl<'screen> Console<'screen> {
     }
 }
 
+fn flush_std_streams() {
+    let _ = std::io::stdout().flush();
+    let _ = std::io::stderr().flush();
+}
+
+// Called by `libctru` before printing each character. Moving the cursor above the first row
+// when a new row would be started after the last one makes `libctru` wrap to the first row instead of scrolling.
+unsafe extern "C" fn wrap_print_char(console: *mut libc::c_void, c: libc::c_int) -> bool {
+    let console = unsafe { &mut *console.cast::<PrintConsole>() };
+
+    let line_full = console.cursorX >= console.windowWidth;
+    let new_line = c == libc::c_int::from(b'\n');
+
+    // A pending wrap moves the cursor to the next row before a new line does.
+    let row = console.cursorY + i32::from(line_full) + i32::from(new_line);
+
+    if row >= console.windowHeight {
+        if line_full && new_line {
+            // The cursor moves down two rows, so the new line is handled here.
+            console.cursorX = 0;
+            console.cursorY = row - console.windowHeight;
+
+            return true;
+        }
+
+        console.cursorY = -1;
+    }
+
+    // Let `libctru` print the character.
+    false
+}
+
 impl Swap for Console<'_> {
     /// Swaps the video buffers. Note: The console's cursor position is not reset, only the framebuffer is changed.
     ///
@@ -348,6 +588,41 @@ impl Swap for Console<'_> {
     }
 }
 
+impl Write for Console<'_> {
+    /// Print to this [`Console`], even if it isn't the [selected](Console::select) one.
+    ///
+    /// The text goes through the standard output, which is flushed while this console is selected.
+    /// The previously selected console is restored afterwards.
+    /// If automatic flushing is enabled (see [`Console::set_flush()`]) the output is flushed to the screen right away.
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // Pending output belongs to the currently selected console.
+        flush_std_streams();
+
+        let written = unsafe {
+            let previous = consoleSelect(self.context.get());
+
+            let mut stdout = std::io::stdout().lock();
+            let written = stdout.write(buf).and_then(|n| stdout.flush().map(|()| n));
+
+            consoleSelect(previous);
+
+            written
+        }?;
+
+        if self.auto_flush {
+            Console::flush(self);
+        }
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Console::flush(self);
+
+        Ok(())
+    }
+}
+
 impl Flush for Console<'_> {
     fn flush_buffers(&mut self) {
         self.screen.flush_buffers();
@@ -380,6 +655,156 @@ impl Drop for Console<'_> {
     }
 }
 
+/// Simple list of selectable items, rendered on a [`Console`] with the selected entry highlighted.
+///
+/// [`Menu`] only keeps track of its items and the selected index: navigation is done via [`Menu::up()`] and [`Menu::down()`],
+/// which makes it possible to bind them to any input scheme.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru::prelude::*;
+/// use ctru::console::Menu;
+///
+/// let apt = Apt::new()?;
+/// let mut hid = Hid::new()?;
+/// let gfx = Gfx::new()?;
+/// let mut console = Console::new(gfx.top_screen.borrow_mut());
+///
+/// let mut menu = Menu::new(["New game", "Load game", "Options"]);
+///
+/// while apt.main_loop() {
+///     hid.scan_input();
+///
+///     if hid.keys_down().contains(KeyPad::DPAD_UP) {
+///         menu.up();
+///     }
+///     if hid.keys_down().contains(KeyPad::DPAD_DOWN) {
+///         menu.down();
+///     }
+///     if hid.keys_down().contains(KeyPad::A) {
+///         break;
+///     }
+///
+///     menu.draw(&mut console)?;
+/// #   break;
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Menu {
+    items: Vec<String>,
+    selected: usize,
+    row: u8,
+    column: u8,
+}
+
+impl Menu {
+    /// Create a new menu with the given items, with the first one selected.
+    ///
+    /// The menu is drawn starting from the top-left corner of the [`Console`] window,
+    /// use [`Menu::set_position()`] to move it somewhere else.
+    pub fn new<I>(items: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        Self {
+            items: items.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Append an item at the end of the menu.
+    pub fn push(&mut self, item: impl Into<String>) {
+        self.items.push(item.into());
+    }
+
+    /// Returns the items of the menu.
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+
+    /// Set the position (in characters, relative to the [`Console`] window) of the first item of the menu.
+    pub fn set_position(&mut self, row: u8, column: u8) {
+        self.row = row;
+        self.column = column;
+    }
+
+    /// Move the selection to the previous item, wrapping around to the last one.
+    pub fn up(&mut self) {
+        if !self.items.is_empty() {
+            self.selected = self.selected.checked_sub(1).unwrap_or(self.items.len() - 1);
+        }
+    }
+
+    /// Move the selection to the next item, wrapping around to the first one.
+    pub fn down(&mut self) {
+        if !self.items.is_empty() {
+            self.selected = (self.selected + 1) % self.items.len();
+        }
+    }
+
+    /// Returns the index of the selected item, or `None` if the menu is empty.
+    pub fn selected(&self) -> Option<usize> {
+        (!self.items.is_empty()).then_some(self.selected)
+    }
+
+    /// Returns the selected item, or `None` if the menu is empty.
+    pub fn selected_item(&self) -> Option<&str> {
+        self.items.get(self.selected).map(String::as_str)
+    }
+
+    /// Select the item at `index`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `index` is out of bounds.
+    pub fn select(&mut self, index: usize) {
+        assert!(index < self.items.len(), "menu index out of bounds");
+
+        self.selected = index;
+    }
+
+    /// Render the menu on `console`, highlighting the selected item.
+    ///
+    /// # Notes
+    ///
+    /// This function only overwrites the rows taken by the menu, up to the width of its longest item
+    /// (so that shorter items don't leave characters of a previous draw behind).
+    /// Items are drawn using the console's ANSI escape codes for cursor positioning and reverse video.
+    pub fn draw(&self, console: &mut Console) -> std::io::Result<()> {
+        let width = self
+            .items
+            .iter()
+            .map(|item| item.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        for (i, item) in self.items.iter().enumerate() {
+            // The console's cursor positioning is 1-based.
+            let row = usize::from(self.row) + i + 1;
+            let column = usize::from(self.column) + 1;
+            let highlight = if i == self.selected { "\x1b[7m" } else { "" };
+
+            write!(
+                console,
+                "\x1b[{row};{column}H{highlight}{item:<width$}\x1b[0m"
+            )?;
+        }
+
+        Console::flush(console);
+
+        Ok(())
+    }
+}
+
 impl std::fmt::Display for Axis {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -412,3 +837,68 @@ impl std::fmt::Display for Error {
 }
 
 impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn menu_navigation() {
+        let mut menu = Menu::new(["a", "b", "c"]);
+        assert_eq!(menu.selected(), Some(0));
+
+        menu.up();
+        assert_eq!(menu.selected_item(), Some("c"));
+
+        menu.down();
+        menu.down();
+        assert_eq!(menu.selected(), Some(1));
+
+        let mut empty = Menu::default();
+        empty.up();
+        empty.down();
+        assert_eq!(empty.selected(), None);
+        assert_eq!(empty.selected_item(), None);
+    }
+
+    #[test]
+    fn wrap_to_first_row() {
+        let mut console: PrintConsole = unsafe { std::mem::zeroed() };
+        console.windowWidth = 40;
+        console.windowHeight = 30;
+
+        let print = |console: &mut PrintConsole, c: u8| unsafe {
+            wrap_print_char(std::ptr::from_mut(console).cast(), c.into())
+        };
+
+        // Nothing changes before the last row.
+        console.cursorY = 28;
+        assert!(!print(&mut console, b'\n'));
+        assert_eq!(console.cursorY, 28);
+
+        // A new line on the last row wraps (`libctru` then moves the cursor to the next row, which is the first one).
+        console.cursorY = 29;
+        print(&mut console, b'a');
+        assert_eq!(console.cursorY, 29);
+        print(&mut console, b'\n');
+        assert_eq!(console.cursorY, -1);
+
+        // Same when the last row is full.
+        console.cursorX = 40;
+        console.cursorY = 29;
+        print(&mut console, b'a');
+        assert_eq!(console.cursorY, -1);
+
+        // A new line after a pending wrap on the second to last row lands past the last row.
+        console.cursorX = 40;
+        console.cursorY = 28;
+        assert!(print(&mut console, b'\n'));
+        assert_eq!((console.cursorX, console.cursorY), (0, 0));
+
+        // The same on the last row lands on the second row.
+        console.cursorX = 40;
+        console.cursorY = 29;
+        assert!(print(&mut console, b'\n'));
+        assert_eq!((console.cursorX, console.cursorY), (0, 1));
+    }
+}
@@ -84,6 +84,14 @@ pub enum Error {
     Libc(String),
     /// Requested service is already active and cannot be activated again.
     ServiceAlreadyActive,
+    /// Requested service isn't available in the current environment.
+    ///
+    /// This usually happens when running on an emulator which doesn't implement the service,
+    /// or when some system files required by the service (e.g. the DSP firmware) are missing.
+    ServiceUnavailable {
+        /// Name of the unavailable service.
+        name: &'static str,
+    },
     /// `stdout` is already being redirected.
     OutputAlreadyRedirected,
     /// The buffer provided by the user to store some data is shorter than required.
@@ -149,6 +157,10 @@ impl fmt::Debug for Error {
                 .finish(),
             Self::Libc(err) => f.debug_tuple("Libc").field(err).finish(),
             Self::ServiceAlreadyActive => f.debug_tuple("ServiceAlreadyActive").finish(),
+            Self::ServiceUnavailable { name } => f
+                .debug_struct("ServiceUnavailable")
+                .field("name", name)
+                .finish(),
             Self::OutputAlreadyRedirected => f.debug_tuple("OutputAlreadyRedirected").finish(),
             Self::BufferTooShort { provided, wanted } => f
                 .debug_struct("BufferTooShort")
@@ -175,6 +187,10 @@ impl fmt::Display for Error {
             ),
             Self::Libc(err) => write!(f, "{err}"),
             Self::ServiceAlreadyActive => write!(f, "service already active"),
+            Self::ServiceUnavailable { name } => write!(
+                f,
+                "service `{name}` is unavailable (it may not be supported by the emulator, or some required system files may be missing)"
+            ),
             Self::OutputAlreadyRedirected => {
                 write!(f, "output streams are already redirected to 3dslink")
             }
@@ -186,6 +202,40 @@ impl fmt::Display for Error {
 
 impl error::Error for Error {}
 
+/// Extension trait for [`Result`]s returned by [`ctru-rs`](crate) functions.
+pub trait ResultExt<T> {
+    /// Returns the contained [`Ok`] value, or panics with a message containing `msg` and a description of the error.
+    ///
+    /// Unlike [`Result::expect()`], the panic message shows the level, module, summary and description
+    /// of [`Error::Os`] result codes (as formatted by the [`Display`](fmt::Display) implementation of [`Error`]),
+    /// instead of only their raw value.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the value is an [`Err`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// use ctru::error::ResultExt;
+    /// use ctru::services::hid::Hid;
+    ///
+    /// let hid = Hid::new().expect_ctru("failed to initialize the HID service");
+    /// ```
+    fn expect_ctru(self, msg: &str) -> T;
+}
+
+impl<T> ResultExt<T> for Result<T> {
+    #[track_caller]
+    fn expect_ctru(self, msg: &str) -> T {
+        match self {
+            Ok(value) => value,
+            Err(err) => panic!("{msg}: {err}"),
+        }
+    }
+}
+
 fn result_code_level_str(result: ctru_sys::Result) -> Cow<'static, str> {
     use ctru_sys::{
         RL_FATAL, RL_INFO, RL_PERMANENT, RL_REINITIALIZE, RL_RESET, RL_STATUS, RL_SUCCESS,
@@ -0,0 +1,111 @@
+//! Per-frame input handling.
+//!
+//! Most applications run a main loop which checks whether the application should keep running via [`Apt::main_loop()`]
+//! and then scans the user's input with [`Hid::scan_input()`]. [`frame()`] wraps this sequence in a single call,
+//! returning an [`InputFrame`] to query the input state of the current frame.
+//!
+//! The [`Apt`] and [`Hid`] handles stay available, so their other methods can still be used alongside this module.
+
+use crate::services::apt::Apt;
+use crate::services::hid::{Hid, KeyPad};
+
+/// Input state of a single frame of the main loop.
+///
+/// Obtained with [`frame()`]. The input state is scanned once when the frame is created,
+/// so all methods return values consistent with each other for the whole frame.
+pub struct InputFrame<'hid> {
+    hid: &'hid Hid,
+}
+
+/// Start a new frame of the main loop, returning its input state.
+///
+/// This function calls [`Apt::main_loop()`] and, if the application should keep running, [`Hid::scan_input()`].
+/// Returns `None` when the application should quit (e.g. when the user closes it from the Home Menu).
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru::prelude::*;
+///
+/// let apt = Apt::new()?;
+/// let mut hid = Hid::new()?;
+///
+/// while let Some(frame) = ctru::frame(&apt, &mut hid) {
+///     if frame.should_exit() {
+///         break;
+///     }
+///
+///     if frame.keys_down().contains(KeyPad::A) {
+///         println!("You pressed A!");
+///     }
+/// #   break;
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[doc(alias = "aptMainLoop")]
+#[doc(alias = "hidScanInput")]
+pub fn frame<'hid>(apt: &Apt, hid: &'hid mut Hid) -> Option<InputFrame<'hid>> {
+    // `aptMainLoop` may block while the application is suspended, so the input must be scanned afterwards.
+    if !apt.main_loop() {
+        return None;
+    }
+
+    hid.scan_input();
+
+    Some(InputFrame { hid })
+}
+
+impl InputFrame<'_> {
+    /// Returns a bitflag struct representing which buttons have just been pressed in this frame.
+    ///
+    /// See [`Hid::keys_down()`].
+    pub fn keys_down(&self) -> KeyPad {
+        self.hid.keys_down()
+    }
+
+    /// Returns a bitflag struct representing which buttons are being held down in this frame.
+    ///
+    /// See [`Hid::keys_held()`].
+    pub fn keys_held(&self) -> KeyPad {
+        self.hid.keys_held()
+    }
+
+    /// Returns a bitflag struct representing which buttons have just been released in this frame.
+    ///
+    /// See [`Hid::keys_up()`].
+    pub fn keys_up(&self) -> KeyPad {
+        self.hid.keys_up()
+    }
+
+    /// Returns the position of the current touch in bottom screen pixels, or `None` if the touch screen isn't being touched.
+    ///
+    /// See [`Hid::touch_screen_pixel()`].
+    pub fn touch(&self) -> Option<(u16, u16)> {
+        self.hid.touch_screen_pixel()
+    }
+
+    /// Returns the current circle pad position in relative (x, y).
+    ///
+    /// See [`Hid::circlepad_position()`].
+    pub fn circlepad_position(&self) -> (i16, i16) {
+        self.hid.circlepad_position()
+    }
+
+    /// Returns `true` if the user pressed START in this frame.
+    ///
+    /// This follows the convention used by most homebrew applications (and all of the examples in this crate) to quit the main loop.
+    pub fn should_exit(&self) -> bool {
+        self.keys_down().contains(KeyPad::START)
+    }
+
+    /// Returns the [`Hid`] handle used to scan the input of this frame.
+    pub fn hid(&self) -> &Hid {
+        self.hid
+    }
+}
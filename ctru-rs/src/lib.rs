@@ -23,6 +23,7 @@
 #![feature(try_trait_v2)]
 #![feature(allocator_api)]
 #![feature(new_uninit)]
+#![cfg_attr(feature = "std-threads", feature(horizon_thread_ext))]
 #![test_runner(test_runner::run_gdb)] // TODO: does this make sense to have configurable?
 #![doc(
     html_favicon_url = "https://user-images.githubusercontent.com/11131775/225929072-2fa1741c-93ae-4b47-9bdf-af70f3d59910.png"
@@ -40,14 +41,15 @@ extern crate shim_3ds;
 ///
 /// It takes effect only if the `big-stack` feature is active. Otherwise, the default stack size should be ~32kB.
 ///
-/// This value was chosen to support crate dependencies which expected more stack than provided. It's suggested to use less stack if possible.
+/// The default value of 2MB was chosen to support crate dependencies which expected more stack than provided. It's suggested to use less stack if possible:
+/// the size (in bytes, decimal or `0x`-prefixed hexadecimal) can be set at build time with the `CTRU_STACK_SIZE` environment variable.
 #[no_mangle]
 // When building lib tests, we don't want to redefine the same symbol twice,
 // since ctru-rs is both the crate under test and a dev-dependency (non-test).
 // We might also be able to use #[linkage] for similar effect, but this way
 // works without depending on another unstable feature.
 #[cfg(all(feature = "big-stack", not(test)))]
-static __stacksize__: usize = 2 * 1024 * 1024; // 2MB
+static __stacksize__: usize = include!(concat!(env!("OUT_DIR"), "/stack_size.rs"));
 
 macro_rules! from_impl {
     ($from_type:ty, $into_type:ty) => {
@@ -62,11 +64,69 @@ macro_rules! from_impl {
 pub mod applets;
 pub mod console;
 pub mod error;
+pub mod input;
 pub mod linear;
+#[cfg(feature = "log")]
+pub mod logger;
+#[cfg(feature = "mii")]
 pub mod mii;
 pub mod os;
 pub mod prelude;
 mod sealed;
 pub mod services;
+#[cfg(feature = "std-threads")]
+pub mod thread;
+pub mod utf16;
 
 pub use crate::error::{Error, Result};
+pub use crate::input::frame;
+
+use crate::services::{apt::Apt, gfx::Gfx, hid::Hid};
+
+/// Initialize the services needed by almost every application: [`Apt`], [`Gfx`] and [`Hid`].
+///
+/// This function also registers the error applet [panic hook](crate::applets::error::set_panic_hook),
+/// so that panic messages are shown on screen (and still passed to the previous panic hook).
+///
+/// The individual constructors remain available for applications with different requirements
+/// (e.g. a custom [`Gfx`] configuration via [`Gfx::with_formats_shared()`]).
+///
+/// # Errors
+///
+/// This function will return an error if any of the services was unable to be initialized, or if a handle to any of them already exists.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru::prelude::*;
+///
+/// let (apt, gfx, mut hid) = ctru::init()?;
+///
+/// let _console = Console::new(gfx.top_screen.borrow_mut());
+///
+/// while apt.main_loop() {
+///     hid.scan_input();
+///
+///     if hid.keys_down().contains(KeyPad::START) {
+///         break;
+///     }
+/// #   break;
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn init() -> Result<(Apt, Gfx, Hid)> {
+    let apt = Apt::new()?;
+    let gfx = Gfx::new()?;
+    let hid = Hid::new()?;
+
+    // Registered last, so that a failed initialization doesn't leave a hook behind.
+    applets::error::set_panic_hook(true);
+
+    Ok((apt, gfx, hid))
+}
@@ -50,6 +50,36 @@ unsafe impl Allocator for LinearAllocator {
     }
 }
 
+/// Returns `true` if `ptr` points inside the LINEAR memory sector.
+///
+/// This can be used to check that a buffer obtained from an unchecked source (e.g. a raw pointer or a plain slice)
+/// can be safely handed to hardware components which require LINEAR memory, such as the DSP.
+///
+/// # Example
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// # let _runner = test_runner::GdbRunner::default();
+/// use ctru::linear::{self, LinearAllocator};
+///
+/// let linear_data = Box::new_in(0u32, LinearAllocator);
+/// let heap_data = Box::new(0u32);
+///
+/// assert!(linear::is_linear_ptr(&*linear_data));
+/// assert!(!linear::is_linear_ptr(&*heap_data));
+/// ```
+pub fn is_linear_ptr<T: ?Sized>(ptr: *const T) -> bool {
+    let address = ptr.cast::<u8>() as usize;
+
+    // LINEAR memory is mapped at different addresses depending on the system version.
+    // See <https://www.3dbrew.org/wiki/Memory_layout#ARM11_User-land_memory_regions>
+    let in_region =
+        |start: u32, size: u32| (start as usize..start as usize + size as usize).contains(&address);
+
+    in_region(ctru_sys::OS_FCRAM_VADDR, ctru_sys::OS_FCRAM_SIZE)
+        || in_region(ctru_sys::OS_OLD_FCRAM_VADDR, ctru_sys::OS_OLD_FCRAM_SIZE)
+}
+
 /// Trait indicating a type has been allocated using [`LinearAllocator`].
 /// This can be used to enforce that a given slice was allocated in LINEAR memory.
 ///
@@ -73,3 +103,207 @@ unsafe impl<T: ?Sized> LinearAllocation for Box<T, LinearAllocator> {}
 // We could also impl for various std::collections types, but it seems unlikely
 // those would ever be used for this purpose in practice, since most of the type
 // we're dereferencing to a &[T]. The workaround would just be to convert to a Vec/Box.
+
+/// Fixed-capacity FIFO byte queue stored in LINEAR memory, with wraparound.
+///
+/// This is useful for streaming data that is produced and consumed in chunks of different sizes
+/// (such as decoded audio samples or microphone recordings), while keeping it readable by hardware components.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// use ctru::linear::LinearRingBuffer;
+///
+/// let mut ring = LinearRingBuffer::new(8);
+///
+/// assert_eq!(ring.push_slice(b"hello"), 5);
+///
+/// let mut out = [0; 3];
+/// assert_eq!(ring.pop_slice(&mut out), 3);
+/// assert_eq!(&out, b"hel");
+///
+/// // Only 6 more bytes fit, wrapping around the end of the buffer.
+/// assert_eq!(ring.push_slice(b" world!"), 6);
+/// assert_eq!(ring.available(), 8);
+/// ```
+pub struct LinearRingBuffer {
+    buffer: Box<[u8], LinearAllocator>,
+    // Index of the first readable byte.
+    head: usize,
+    // Number of readable bytes.
+    len: usize,
+}
+
+impl LinearRingBuffer {
+    /// Create a new, empty, ring buffer able to hold `capacity` bytes.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if there isn't enough free LINEAR memory to allocate the buffer.
+    pub fn new(capacity: usize) -> Self {
+        // The default allocation error handler would abort instead of panicking.
+        let buffer = Box::try_new_zeroed_slice_in(capacity, LinearAllocator)
+            .expect("not enough LINEAR memory for the ring buffer");
+
+        Self {
+            // SAFETY: zeroed memory is a valid `[u8]`.
+            buffer: unsafe { buffer.assume_init() },
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Returns the maximum number of bytes the ring buffer can hold.
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the number of bytes available to be read.
+    pub fn available(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of bytes that can be pushed before the ring buffer is full.
+    pub fn free_space(&self) -> usize {
+        self.capacity() - self.len
+    }
+
+    /// Returns `true` if there is nothing to read.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if nothing more can be pushed.
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity()
+    }
+
+    /// Discard all the readable data.
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// Append as much of `data` as fits in the ring buffer, returning the number of bytes written.
+    pub fn push_slice(&mut self, data: &[u8]) -> usize {
+        let count = data.len().min(self.free_space());
+
+        if count == 0 {
+            return 0;
+        }
+
+        let tail = (self.head + self.len) % self.capacity();
+        let first = count.min(self.capacity() - tail);
+
+        self.buffer[tail..tail + first].copy_from_slice(&data[..first]);
+        self.buffer[..count - first].copy_from_slice(&data[first..count]);
+
+        self.len += count;
+
+        count
+    }
+
+    /// Read (and remove) as much data as fits in `buf`, returning the number of bytes read.
+    pub fn pop_slice(&mut self, buf: &mut [u8]) -> usize {
+        let count = buf.len().min(self.len);
+
+        if count == 0 {
+            return 0;
+        }
+
+        let first = count.min(self.capacity() - self.head);
+
+        buf[..first].copy_from_slice(&self.buffer[self.head..self.head + first]);
+        buf[first..count].copy_from_slice(&self.buffer[..count - first]);
+
+        self.consume(count);
+
+        count
+    }
+
+    /// Returns the readable data stored contiguously from the start of the queue, without removing it.
+    ///
+    /// When the readable data wraps around the end of the buffer, only the part before the end is returned:
+    /// call [`LinearRingBuffer::consume()`] and then this function again to access the rest.
+    /// The returned slice is on the LINEAR memory, so it can be handed directly to hardware components.
+    pub fn as_contiguous_read(&self) -> &[u8] {
+        let end = (self.head + self.len).min(self.capacity());
+
+        &self.buffer[self.head..end]
+    }
+
+    /// Remove `count` bytes from the start of the queue, usually after reading them with [`LinearRingBuffer::as_contiguous_read()`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `count` is bigger than [`LinearRingBuffer::available()`].
+    pub fn consume(&mut self, count: usize) {
+        assert!(
+            count <= self.len,
+            "cannot consume {count} bytes, only {} are available",
+            self.len
+        );
+
+        self.len -= count;
+
+        self.head = if self.len == 0 {
+            // Restarting from the beginning maximizes the size of the next contiguous reads.
+            0
+        } else {
+            (self.head + count) % self.capacity()
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring_buffer_wraparound() {
+        let mut ring = LinearRingBuffer::new(4);
+        let mut out = [0; 4];
+
+        assert!(is_linear_ptr(ring.buffer.as_ptr()));
+
+        assert_eq!(ring.push_slice(&[1, 2, 3]), 3);
+        assert_eq!(ring.pop_slice(&mut out[..2]), 2);
+        assert_eq!(out[..2], [1, 2]);
+
+        // Writing wraps around the end of the buffer, and stops when full.
+        assert_eq!(ring.push_slice(&[4, 5, 6, 7]), 3);
+        assert!(ring.is_full());
+        assert_eq!(ring.push_slice(&[8]), 0);
+
+        // Contiguous reads stop at the end of the buffer.
+        assert_eq!(ring.as_contiguous_read(), [3, 4]);
+        ring.consume(2);
+        assert_eq!(ring.as_contiguous_read(), [5, 6]);
+
+        assert_eq!(ring.pop_slice(&mut out), 2);
+        assert_eq!(out[..2], [5, 6]);
+        assert!(ring.is_empty());
+
+        // Reading from an empty ring buffer is a no-op.
+        assert_eq!(ring.pop_slice(&mut out), 0);
+        assert!(ring.as_contiguous_read().is_empty());
+    }
+
+    #[test]
+    fn ring_buffer_wrapping_pop() {
+        let mut ring = LinearRingBuffer::new(5);
+        let mut out = [0; 5];
+
+        ring.push_slice(&[0; 4]);
+        ring.pop_slice(&mut out[..3]);
+
+        assert_eq!(ring.push_slice(&[1, 2, 3, 4]), 4);
+        assert_eq!(ring.available(), 5);
+        assert_eq!(ring.free_space(), 0);
+
+        // Popping across the wraparound point returns the data in order.
+        assert_eq!(ring.pop_slice(&mut out), 5);
+        assert_eq!(out, [0, 1, 2, 3, 4]);
+    }
+}
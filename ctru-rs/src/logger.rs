@@ -0,0 +1,183 @@
+//! [`log`] integration.
+//!
+//! [`ConsoleLogger`] implements [`log::Log`] by printing each record on the currently selected [`Console`](crate::console::Console),
+//! colored according to its level. This way, the `log` macros (`info!`, `warn!`, ...) used by the application and its dependencies become visible on screen.
+//!
+//! This module is only available with the `log` feature.
+
+use std::io::Write;
+
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+/// Logger printing records to the currently selected [`Console`](crate::console::Console).
+///
+/// # Filtering
+///
+/// Records are filtered with `RUST_LOG`-style directives: a comma-separated list of either global levels (e.g. `warn`)
+/// or `target=level` pairs (e.g. `my_app::net=trace`). Targets are matched by prefix, with the longest matching prefix taking precedence.
+/// Records not matching any target use the global level, which defaults to [`LevelFilter::Info`].
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru::console::Console;
+/// use ctru::logger::ConsoleLogger;
+/// use ctru::services::gfx::Gfx;
+///
+/// let gfx = Gfx::new()?;
+/// let _console = Console::new(gfx.top_screen.borrow_mut());
+///
+/// ConsoleLogger::from_env().init()?;
+///
+/// log::info!("Hello from the log crate!");
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ConsoleLogger {
+    level: LevelFilter,
+    directives: Vec<(String, LevelFilter)>,
+}
+
+impl ConsoleLogger {
+    /// Create a new logger showing all records of level [`Info`](log::Level::Info) and above.
+    pub fn new() -> Self {
+        Self {
+            level: LevelFilter::Info,
+            directives: Vec::new(),
+        }
+    }
+
+    /// Create a new logger using the filtering directives found in the `RUST_LOG` environment variable.
+    ///
+    /// If the variable isn't set, this is equivalent to [`ConsoleLogger::new()`].
+    pub fn from_env() -> Self {
+        match std::env::var("RUST_LOG") {
+            Ok(filters) => Self::with_filters(&filters),
+            Err(_) => Self::new(),
+        }
+    }
+
+    /// Create a new logger using the given `RUST_LOG`-style filtering directives.
+    ///
+    /// Invalid directives are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ctru::logger::ConsoleLogger;
+    ///
+    /// // Only show warnings and errors, except for the `net` module.
+    /// let logger = ConsoleLogger::with_filters("warn,my_app::net=debug");
+    /// ```
+    pub fn with_filters(filters: &str) -> Self {
+        let mut logger = Self::new();
+
+        for directive in filters.split(',').map(str::trim) {
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    if let Ok(level) = level.trim().parse() {
+                        logger.directives.push((target.trim().to_owned(), level));
+                    }
+                }
+                None => {
+                    if let Ok(level) = directive.parse() {
+                        logger.level = level;
+                    }
+                }
+            }
+        }
+
+        logger
+    }
+
+    /// Returns the maximum level of the records shown for `target`.
+    pub fn level_for(&self, target: &str) -> LevelFilter {
+        self.directives
+            .iter()
+            .filter(|(prefix, _)| target.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.level, |&(_, level)| level)
+    }
+
+    /// Set this logger as the global logger of the application.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a global logger was already set.
+    #[doc(alias = "set_logger")]
+    pub fn init(self) -> Result<(), SetLoggerError> {
+        let max_level = self
+            .directives
+            .iter()
+            .map(|&(_, level)| level)
+            .fold(self.level, LevelFilter::max);
+
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(max_level);
+
+        Ok(())
+    }
+}
+
+impl Default for ConsoleLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Log for ConsoleLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        // ANSI foreground colors, supported by the `Console`.
+        let color = match record.level() {
+            log::Level::Error => "\x1b[31m",
+            log::Level::Warn => "\x1b[33m",
+            log::Level::Info => "\x1b[32m",
+            log::Level::Debug => "\x1b[36m",
+            log::Level::Trace => "\x1b[35m",
+        };
+
+        // Logging must never panic, so write errors are ignored (e.g. when no `Console` is selected).
+        let _ = writeln!(
+            std::io::stdout().lock(),
+            "{color}{:<5}\x1b[0m {}: {}",
+            record.level(),
+            record.target(),
+            record.args()
+        );
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stdout().flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_directives() {
+        let logger = ConsoleLogger::with_filters("warn, my_app::net=trace,my_app=debug,bogus=loud");
+
+        assert_eq!(logger.level_for("other"), LevelFilter::Warn);
+        assert_eq!(logger.level_for("my_app"), LevelFilter::Debug);
+        assert_eq!(logger.level_for("my_app::net::udp"), LevelFilter::Trace);
+        assert_eq!(logger.directives.len(), 2);
+
+        assert_eq!(ConsoleLogger::new().level_for("any"), LevelFilter::Info);
+    }
+}
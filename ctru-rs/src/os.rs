@@ -1,5 +1,19 @@
 //! Utilities to get information about the operating system and hardware state.
 
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ctru_sys::Handle;
+
+use crate::error::ResultCode;
+use crate::services::svc::timeout_as_nanos;
+
+pub mod exception;
+pub mod memory;
+
 /// System version information. This struct is used for both kernel and firmware versions.
 ///
 /// # Example
@@ -152,3 +166,361 @@ pub fn current_3d_slider_state() -> f32 {
 pub fn is_headset_connected() -> bool {
     unsafe { ctru_sys::osIsHeadsetConnected() }
 }
+
+/// Returns whether the running console is a New 3DS (or New 3DS XL, New 2DS XL).
+#[doc(alias = "APT_CheckNew3DS")]
+pub(crate) fn is_new_3ds() -> crate::Result<bool> {
+    let mut is_new_3ds = false;
+
+    ResultCode(unsafe { ctru_sys::APT_CheckNew3DS(&mut is_new_3ds) })?;
+
+    Ok(is_new_3ds)
+}
+
+/// Calendar date and time, broken down into its components.
+///
+/// This is the Rust counterpart of `libc`'s `struct tm`, returned by [`local_time()`] and [`Tm::local()`].
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// let now = ctru::os::local_time();
+///
+/// println!(
+///     "{:04}-{:02}-{:02} {:02}:{:02}",
+///     now.year, now.month, now.day, now.hour, now.minute
+/// );
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Tm {
+    /// Full year (e.g. `2024`).
+    pub year: i32,
+    /// Month of the year, from 1 to 12.
+    pub month: u8,
+    /// Day of the month, from 1 to 31.
+    pub day: u8,
+    /// Hour of the day, from 0 to 23.
+    pub hour: u8,
+    /// Minute of the hour, from 0 to 59.
+    pub minute: u8,
+    /// Second of the minute, from 0 to 60 (to allow for leap seconds).
+    pub second: u8,
+    /// Day of the week, from 0 (Sunday) to 6 (Saturday).
+    pub weekday: u8,
+    /// Day of the year, from 0 to 365.
+    pub year_day: u16,
+}
+
+impl Tm {
+    /// Convert a [`SystemTime`] to the local time, according to the current timezone (see [`set_timezone()`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::os::Tm;
+    ///
+    /// let modified = std::fs::metadata("sdmc:/")?.modified()?;
+    ///
+    /// println!("Last modified in {}", Tm::local(modified).year);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "localtime_r")]
+    pub fn local(time: SystemTime) -> Self {
+        let secs = match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs() as libc::time_t,
+            Err(before) => -(before.duration().as_secs() as libc::time_t),
+        };
+
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+
+        // `localtime_r` only fails if the year doesn't fit in an `int`, which can't happen with a 64 bit `time_t` from a `SystemTime`.
+        unsafe { libc::localtime_r(&secs, &mut tm) };
+
+        Self {
+            year: tm.tm_year + 1900,
+            month: (tm.tm_mon + 1) as u8,
+            day: tm.tm_mday as u8,
+            hour: tm.tm_hour as u8,
+            minute: tm.tm_min as u8,
+            second: tm.tm_sec as u8,
+            weekday: tm.tm_wday as u8,
+            year_day: tm.tm_yday as u16,
+        }
+    }
+}
+
+/// Get the current local time.
+///
+/// # Notes
+///
+/// The 3DS' clock doesn't have any notion of timezones: it simply holds the date and time configured by the user in the System Settings.
+/// For this reason, with no timezone set (the default) this function returns the time as shown by the Home Menu.
+/// Use [`set_timezone()`] if the clock is known to hold a different time (e.g. UTC).
+pub fn local_time() -> Tm {
+    Tm::local(SystemTime::now())
+}
+
+/// Set the timezone used to convert times to the local time, as a POSIX `TZ` string (e.g. `"CET-1CEST,M3.5.0,M10.5.0/3"`).
+///
+/// This sets the `TZ` environment variable, so it affects all time conversions done by `libc` within the application.
+///
+/// # Errors
+///
+/// This function will return an error if `tz` is empty or contains a nul character.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// // The clock holds UTC time, but timestamps should be shown in Japan Standard Time.
+/// ctru::os::set_timezone("JST-9")?;
+///
+/// let now = ctru::os::local_time();
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[doc(alias = "tzset")]
+pub fn set_timezone(tz: &str) -> crate::Result<()> {
+    if tz.is_empty() || tz.contains('\0') {
+        return Err(crate::Error::Other(format!("invalid timezone: {tz:?}")));
+    }
+
+    std::env::set_var("TZ", tz);
+
+    unsafe { libc::tzset() };
+
+    Ok(())
+}
+
+/// Redirect `stdout` and `stderr` to the `3dslink` server that sent this program.
+///
+/// This is a convenience wrapper around [`Soc::redirect_to_3dslink`](crate::services::soc::Soc::redirect_to_3dslink),
+/// useful during development to see the output of `println!` or `dbg!` on the host PC
+/// when the application doesn't have a [`Console`](crate::console::Console) to print to.
+///
+/// The redirection lasts as long as the given [`Soc`](crate::services::soc::Soc) instance is alive.
+///
+/// # Notes
+///
+/// The program must be sent with `3dslink -s` (or `cargo 3ds run --server`), otherwise there will be
+/// no server listening on the host PC and the connection will fail.
+///
+/// # Errors
+///
+/// Returns an error if a connection cannot be established to the server,
+/// or if the output was already being redirected.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru::services::soc::Soc;
+///
+/// let mut soc = Soc::new()?;
+/// ctru::os::redirect_stdout_to_3dslink(&mut soc)?;
+///
+/// println!("I'm visible from a PC!");
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[doc(alias = "link3dsStdio")]
+pub fn redirect_stdout_to_3dslink(soc: &mut crate::services::soc::Soc) -> crate::Result<()> {
+    soc.redirect_to_3dslink(true, true)
+}
+
+/// Wait until any of the given handles is signaled, returning the index of the signaled handle.
+///
+/// If the timeout is reached, an error is returned. You can use [`Error::is_timeout`](crate::Error::is_timeout)
+/// to check if the error is due to a timeout.
+///
+/// # Errors
+///
+/// This function will return an error if `handles` is empty, if any of the handles is invalid or if the timeout is reached.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use std::time::Duration;
+/// use ctru::error::ResultCode;
+///
+/// let mut packet_event = 0;
+/// let mut disconnect_event = 0;
+///
+/// unsafe {
+///     ResultCode(ctru_sys::svcCreateEvent(&mut packet_event, ctru_sys::RESET_ONESHOT))?;
+///     ResultCode(ctru_sys::svcCreateEvent(&mut disconnect_event, ctru_sys::RESET_ONESHOT))?;
+///
+///     // Usually signaled by another thread (or the system).
+///     ResultCode(ctru_sys::svcSignalEvent(packet_event))?;
+/// }
+///
+/// match ctru::os::wait_any(&[packet_event, disconnect_event], Duration::from_millis(16)) {
+///     Ok(0) => println!("A packet is available"),
+///     Ok(_) => println!("Disconnected"),
+///     Err(e) if e.is_timeout() => println!("Nothing happened"),
+///     Err(e) => return Err(e.into()),
+/// }
+///
+/// unsafe {
+///     ctru_sys::svcCloseHandle(packet_event);
+///     ctru_sys::svcCloseHandle(disconnect_event);
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[doc(alias = "svcWaitSynchronizationN")]
+pub fn wait_any(handles: &[Handle], timeout: Duration) -> crate::Result<usize> {
+    if handles.is_empty() {
+        return Err(crate::Error::Other("no handles to wait on".into()));
+    }
+
+    let index = wait_synchronization_n(handles, false, timeout)?;
+
+    Ok(index as usize)
+}
+
+/// Wait until all of the given handles are signaled.
+///
+/// If the timeout is reached, an error is returned. You can use [`Error::is_timeout`](crate::Error::is_timeout)
+/// to check if the error is due to a timeout.
+///
+/// # Errors
+///
+/// This function will return an error if any of the handles is invalid or if the timeout is reached.
+#[doc(alias = "svcWaitSynchronizationN")]
+pub fn wait_all(handles: &[Handle], timeout: Duration) -> crate::Result<()> {
+    if handles.is_empty() {
+        return Ok(());
+    }
+
+    wait_synchronization_n(handles, true, timeout)?;
+
+    Ok(())
+}
+
+fn wait_synchronization_n(
+    handles: &[Handle],
+    wait_all: bool,
+    timeout: Duration,
+) -> crate::Result<i32> {
+    let timeout = timeout_as_nanos(timeout)?;
+    let count = i32::try_from(handles.len())
+        .map_err(|_| crate::Error::Other("too many handles to wait on".into()))?;
+
+    let mut index = 0;
+
+    ResultCode(unsafe {
+        ctru_sys::svcWaitSynchronizationN(&mut index, handles.as_ptr(), count, wait_all, timeout)
+    })?;
+
+    Ok(index)
+}
+
+/// Run a future to completion on the current thread, blocking until it is done.
+///
+/// This is a minimal single-threaded executor, useful to write I/O-bound flows (e.g. using [sockets](crate::services::soc)
+/// or [local networking](crate::services::uds)) as `async fn`s without depending on a full runtime such as `tokio`.
+///
+/// # Notes
+///
+/// While the future is pending, the thread sleeps on a kernel event until the future's [`Waker`] is woken,
+/// leaving the CPU to the other threads on the same core (which are scheduled cooperatively on the application core).
+/// There is no reactor: futures are expected to wake their waker themselves (for example from another thread or an event callback),
+/// or to wake it immediately before returning [`Poll::Pending`] if they need to be polled again.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// async fn add(a: u32, b: u32) -> u32 {
+///     a + b
+/// }
+///
+/// let sum = ctru::os::block_on(async { add(1, 2).await + add(3, 4).await });
+///
+/// assert_eq!(sum, 10);
+/// ```
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+
+    let signal = Arc::new(WakeSignal::new());
+    let waker = Waker::from(Arc::clone(&signal));
+    let mut context = Context::from_waker(&waker);
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+
+        signal.wait();
+    }
+}
+
+/// Wake-up notification used by [`block_on()`], backed by a kernel event.
+struct WakeSignal {
+    /// `None` if the event couldn't be created, in which case waiting just yields the thread.
+    event: Option<Handle>,
+}
+
+impl WakeSignal {
+    #[doc(alias = "svcCreateEvent")]
+    fn new() -> Self {
+        let mut event = 0;
+
+        let res = unsafe { ctru_sys::svcCreateEvent(&mut event, ctru_sys::RESET_ONESHOT) };
+
+        Self {
+            event: ctru_sys::R_SUCCEEDED(res).then_some(event),
+        }
+    }
+
+    fn wait(&self) {
+        match self.event {
+            Some(event) => unsafe {
+                let _ = ctru_sys::svcWaitSynchronization(event, i64::MAX);
+            },
+            None => std::thread::yield_now(),
+        }
+    }
+}
+
+impl Wake for WakeSignal {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    #[doc(alias = "svcSignalEvent")]
+    fn wake_by_ref(self: &Arc<Self>) {
+        if let Some(event) = self.event {
+            let _ = unsafe { ctru_sys::svcSignalEvent(event) };
+        }
+    }
+}
+
+impl Drop for WakeSignal {
+    fn drop(&mut self) {
+        if let Some(event) = self.event {
+            let _ = unsafe { ctru_sys::svcCloseHandle(event) };
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! ARM exception handling.
+//!
+//! By default, a CPU exception (such as the data abort caused by dereferencing a null pointer) stops the application
+//! with a raw register dump shown by the system (or by Luma3DS). [`panic_on_exception()`] instead installs a handler,
+//! via `libctru`'s `threadOnException`, which turns exceptions into panics with a readable message.
+//! Combined with [`set_panic_hook()`](crate::applets::error::set_panic_hook), the message is shown on screen by the error applet.
+//!
+//! # Notes
+//!
+//! Exception handlers are set per thread, so [`panic_on_exception()`] must be called by each thread that should be covered.
+//!
+//! The process is aborted after the panic message is reported, since the state of the faulting thread can't be recovered.
+//!
+//! Release builds carry no symbol names, so the faulting code is reported by address only. Use
+//! `arm-none-eabi-addr2line -fCe <app>.elf <address>` on the ELF file of the application to find the corresponding function and source line.
+#![doc(alias = "threadOnException")]
+
+use std::cell::OnceCell;
+use std::fmt;
+
+/// Size of the stack on which the exception handler of each thread runs.
+///
+/// A separate stack is used so that stack overflows can be reported too. It must fit the panic hook,
+/// which may launch the error applet.
+const HANDLER_STACK_SIZE: usize = 0x10000;
+
+thread_local! {
+    static HANDLER_STACK: OnceCell<Box<[u64]>> = const { OnceCell::new() };
+}
+
+/// Kind of ARM exception.
+#[doc(alias = "ERRF_ExceptionType")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExceptionKind {
+    /// An instruction was fetched from an invalid address (e.g. when calling a dangling function pointer).
+    PrefetchAbort,
+    /// Data was read from or written to an invalid address (e.g. when dereferencing a null pointer).
+    DataAbort,
+    /// An undefined instruction was executed.
+    Undefined,
+    /// The floating point unit raised an exception.
+    Vfp,
+    /// Exception type unknown to [`ctru-rs`](crate), with its raw value.
+    Other(u8),
+}
+
+/// Information about an ARM exception, reported by the handler installed with [`panic_on_exception()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ExceptionInfo {
+    /// Kind of exception.
+    pub kind: ExceptionKind,
+    /// Address of the faulting instruction.
+    pub pc: u32,
+    /// Link register at the time of the exception (usually the return address of the faulting function).
+    pub lr: u32,
+    /// Stack pointer at the time of the exception.
+    pub sp: u32,
+    /// Address whose access caused the exception, for aborts.
+    pub fault_address: Option<u32>,
+    /// Raw value of the fault status register, describing the cause of aborts.
+    pub fault_status: u32,
+}
+
+/// Install a handler turning ARM exceptions of the current thread into panics.
+///
+/// The panic message holds the [`ExceptionInfo`] of the exception.
+/// Have a look at the [module documentation](self) for more info.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// ctru::applets::error::set_panic_hook(true);
+/// ctru::os::exception::panic_on_exception();
+/// ```
+#[doc(alias = "threadOnException")]
+pub fn panic_on_exception() {
+    let stack_top = HANDLER_STACK.with(|stack| {
+        let stack = stack.get_or_init(|| vec![0; HANDLER_STACK_SIZE / 8].into_boxed_slice());
+
+        // The stack grows downwards, so the handler gets the address right after its end.
+        stack.as_ptr_range().end as *mut libc::c_void
+    });
+
+    // A null exception data pointer makes the kernel write the exception data on the handler's stack.
+    //
+    // `threadOnException` is `static inline` in `libctru`'s headers. Like `getThreadCommandBuffer`, it is linked
+    // through the wrappers `ctru-sys` generates for static functions (see `wrap_static_fns` in its build script).
+    unsafe {
+        ctru_sys::threadOnException(Some(exception_handler), stack_top, std::ptr::null_mut())
+    };
+}
+
+unsafe extern "C" fn exception_handler(
+    info: *mut ctru_sys::ERRF_ExceptionInfo,
+    regs: *mut ctru_sys::CpuRegisters,
+) {
+    let (info, regs) = unsafe { (&*info, &*regs) };
+
+    let kind = match info.type_ {
+        ctru_sys::ERRF_EXCEPTION_PREFETCH_ABORT => ExceptionKind::PrefetchAbort,
+        ctru_sys::ERRF_EXCEPTION_DATA_ABORT => ExceptionKind::DataAbort,
+        ctru_sys::ERRF_EXCEPTION_UNDEFINED => ExceptionKind::Undefined,
+        ctru_sys::ERRF_EXCEPTION_VFP => ExceptionKind::Vfp,
+        other => ExceptionKind::Other(other),
+    };
+
+    let info = ExceptionInfo {
+        kind,
+        pc: regs.pc,
+        lr: regs.lr,
+        sp: regs.sp,
+        fault_address: matches!(
+            kind,
+            ExceptionKind::PrefetchAbort | ExceptionKind::DataAbort
+        )
+        .then_some(info.far),
+        fault_status: info.fsr,
+    };
+
+    // The panic can't unwind past the handler, which has no caller to return to.
+    let _ = std::panic::catch_unwind(|| panic!("{info}"));
+
+    std::process::abort();
+}
+
+impl fmt::Display for ExceptionKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::PrefetchAbort => write!(f, "prefetch abort"),
+            Self::DataAbort => write!(f, "data abort"),
+            Self::Undefined => write!(f, "undefined instruction"),
+            Self::Vfp => write!(f, "floating point exception"),
+            Self::Other(kind) => write!(f, "unknown exception ({kind})"),
+        }
+    }
+}
+
+impl fmt::Display for ExceptionInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at pc {:#010x}", self.kind, self.pc)?;
+
+        if let Some(address) = self.fault_address {
+            write!(
+                f,
+                " accessing address {address:#010x} (fault status {:#x})",
+                self.fault_status
+            )?;
+        }
+
+        write!(f, "\nlr {:#010x}, sp {:#010x}", self.lr, self.sp)
+    }
+}
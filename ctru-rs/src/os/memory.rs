@@ -0,0 +1,229 @@
+//! Manual virtual memory management.
+//!
+//! This module wraps `svcControlMemory`, which lets the application allocate memory pages at a chosen virtual address
+//! (for example to build a custom heap, or a large arena for an emulator) outside of the heaps managed by `libctru`.
+//!
+//! # Safety
+//!
+//! **Mapping memory manually is dangerous.** The kernel only checks that the requested pages are free:
+//! it knows nothing about the memory managed by `libctru` or the Rust allocator. Mapping (or unmapping) pages used
+//! by someone else, even if allowed by the kernel, leads to memory corruption that can be very hard to debug.
+//!
+//! - The application heap managed by `libctru` starts at [`HEAP_START`] and, by default, takes all the memory available to the application.
+//!   Applications mapping memory manually should reduce its size (by defining the `__ctru_heap_size` symbol) and place their mappings above its end.
+//! - The main thread's stack is placed right below [`HEAP_END`].
+//! - User applications can't map executable memory with `svcControlMemory`, only readable and/or writable memory.
+//!   Executable mappings (e.g. for JIT compilers) need `svcControlProcessMemory`, which requires kernel privileges.
+#![doc(alias = "svcControlMemory")]
+
+use bitflags::bitflags;
+
+use crate::error::ResultCode;
+use crate::Error;
+
+/// Size of a memory page. Mapped addresses and sizes must be multiples of this value.
+pub const PAGE_SIZE: usize = 0x1000;
+
+/// Virtual address at which the application heap managed by `libctru` starts.
+pub const HEAP_START: usize = 0x0800_0000;
+
+/// Virtual address at which the heap region of the application ends.
+///
+/// The main thread's stack is located right below this address.
+pub const HEAP_END: usize = 0x1000_0000;
+
+bitflags! {
+    /// Access permissions of a [`MemoryMapping`].
+    #[doc(alias = "MemPerm")]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct MemPerm: u32 {
+        /// The memory can be read.
+        const READ = ctru_sys::MEMPERM_READ;
+        /// The memory can be written.
+        const WRITE = ctru_sys::MEMPERM_WRITE;
+        /// The memory can be read and written.
+        const READWRITE = ctru_sys::MEMPERM_READWRITE;
+    }
+}
+
+/// Pages allocated with [`map()`], freed when dropped.
+#[derive(Debug)]
+pub struct MemoryMapping {
+    addr: usize,
+    size: usize,
+}
+
+/// Allocate `size` bytes of memory pages at the virtual address `addr`, with the given permissions.
+///
+/// The new pages are zeroed.
+///
+/// # Errors
+///
+/// This function will return an error if `addr` or `size` aren't multiples of [`PAGE_SIZE`], if the range isn't fully
+/// contained in the application's heap region (between [`HEAP_START`] and [`HEAP_END`]), if `size` is 0, if `perm` is empty,
+/// or if the kernel refused the allocation (e.g. because the pages are already in use or there isn't enough free memory).
+///
+/// # Safety
+///
+/// The range `addr..addr + size` must not be used by anything else (such as the `libctru` heap) for as long as the returned
+/// [`MemoryMapping`] is alive. Have a look at the [module documentation](self) for more info.
+///
+/// # Example
+///
+/// ```no_run
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru::os::memory::{self, MemPerm, PAGE_SIZE};
+///
+/// // This address must be above the end of the (reduced) `libctru` heap, and below the main thread's stack.
+/// let addr = 0x0C00_0000;
+/// let mapping = unsafe { memory::map(addr, 16 * PAGE_SIZE, MemPerm::READWRITE)? };
+///
+/// let arena = unsafe { std::slice::from_raw_parts_mut(mapping.as_ptr(), mapping.len()) };
+/// arena[0] = 42;
+///
+/// memory::unmap(mapping)?;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[doc(alias = "svcControlMemory")]
+#[doc(alias = "MEMOP_ALLOC")]
+pub unsafe fn map(addr: usize, size: usize, perm: MemPerm) -> crate::Result<MemoryMapping> {
+    validate_range(addr, size)?;
+
+    if perm.is_empty() {
+        return Err(Error::Other(
+            "memory mappings need at least one permission".into(),
+        ));
+    }
+
+    let mut out_addr = 0;
+
+    ResultCode(unsafe {
+        ctru_sys::svcControlMemory(
+            &mut out_addr,
+            addr as u32,
+            0,
+            size as u32,
+            ctru_sys::MEMOP_ALLOC,
+            perm.bits(),
+        )
+    })?;
+
+    Ok(MemoryMapping {
+        addr: out_addr as usize,
+        size,
+    })
+}
+
+/// Free the pages of a [`MemoryMapping`].
+///
+/// This is the same as dropping the mapping, but returns the error reported by the kernel, if any.
+#[doc(alias = "MEMOP_FREE")]
+pub fn unmap(mapping: MemoryMapping) -> crate::Result<()> {
+    let mapping = std::mem::ManuallyDrop::new(mapping);
+
+    unsafe { mapping.free() }
+}
+
+impl MemoryMapping {
+    /// Returns a pointer to the start of the mapped memory.
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.addr as *mut u8
+    }
+
+    /// Returns the size (in bytes) of the mapped memory.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns `true` if the mapping is empty. This is never the case, since [`map()`] rejects empty mappings.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Change the access permissions of the mapped memory.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `perm` is empty or if the kernel refused the change.
+    ///
+    /// # Safety
+    ///
+    /// Removing permissions makes any reference to the mapped memory relying on them (e.g. a mutable slice after removing [`MemPerm::WRITE`])
+    /// cause a data abort when used.
+    #[doc(alias = "MEMOP_PROT")]
+    pub unsafe fn protect(&mut self, perm: MemPerm) -> crate::Result<()> {
+        if perm.is_empty() {
+            return Err(Error::Other(
+                "memory mappings need at least one permission".into(),
+            ));
+        }
+
+        let mut out_addr = 0;
+
+        ResultCode(unsafe {
+            ctru_sys::svcControlMemory(
+                &mut out_addr,
+                self.addr as u32,
+                0,
+                self.size as u32,
+                ctru_sys::MEMOP_PROT,
+                perm.bits(),
+            )
+        })?;
+
+        Ok(())
+    }
+
+    /// Free the mapped pages.
+    ///
+    /// # Safety
+    ///
+    /// Must be called at most once per mapping.
+    unsafe fn free(&self) -> crate::Result<()> {
+        let mut out_addr = 0;
+
+        ResultCode(unsafe {
+            ctru_sys::svcControlMemory(
+                &mut out_addr,
+                self.addr as u32,
+                0,
+                self.size as u32,
+                ctru_sys::MEMOP_FREE,
+                0,
+            )
+        })?;
+
+        Ok(())
+    }
+}
+
+impl Drop for MemoryMapping {
+    fn drop(&mut self) {
+        // There is no way to report the error from here. Use `unmap()` to check for it.
+        let _ = unsafe { self.free() };
+    }
+}
+
+fn validate_range(addr: usize, size: usize) -> crate::Result<()> {
+    if size == 0 {
+        return Err(Error::Other("memory mappings can't be empty".into()));
+    }
+
+    if addr % PAGE_SIZE != 0 || size % PAGE_SIZE != 0 {
+        return Err(Error::Other(format!(
+            "memory mapping at {addr:#x} of size {size:#x} is not aligned to the page size ({PAGE_SIZE:#x})"
+        )));
+    }
+
+    match addr.checked_add(size) {
+        Some(end) if addr >= HEAP_START && end <= HEAP_END => Ok(()),
+        _ => Err(Error::Other(format!(
+            "memory mapping at {addr:#x} of size {size:#x} is outside the heap region ({HEAP_START:#x}..{HEAP_END:#x})"
+        ))),
+    }
+}
@@ -2,14 +2,19 @@
 //!
 //! As the name implies, the AM service manages installed applications. It can:
 //! - Read the installed applications on the console and their information (depending on the install location).
-//! - Install compatible applications to the console.
+//! - Install compatible applications to the console (see [`Am::start_cia_install()`]).
+//! - Uninstall titles from the console (see [`Am::delete_app_title()`]).
 //!
-//! TODO: [`ctru-rs`](crate) doesn't support installing or uninstalling titles yet.
+//! # Warning
+//!
+//! Installing and deleting titles permanently modifies the console's storage. Deleting system titles or installing broken CIA files
+//! may render the console unusable. Always double-check the [`MediaType`] and title IDs passed to these functions.
 #![doc(alias = "app")]
 #![doc(alias = "manager")]
 
 use crate::error::ResultCode;
 use crate::services::fs::MediaType;
+use std::io;
 use std::marker::PhantomData;
 
 /// General information about a specific title entry.
@@ -178,6 +183,183 @@ impl Am {
             })
             .collect())
     }
+
+    /// Delete an application title (and its related data) from the given install location.
+    ///
+    /// Only application titles (such as games, demos, updates and DLC) can be deleted with this function.
+    /// See [`Am::delete_title()`] to delete titles of any kind.
+    ///
+    /// # Warning
+    ///
+    /// This operation is permanent and can't be undone.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::am::Am;
+    /// use ctru::services::fs::MediaType;
+    /// let mut app_manager = Am::new()?;
+    ///
+    /// app_manager.delete_app_title(MediaType::Sd, 0x000400000F800100)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "AM_DeleteAppTitle")]
+    pub fn delete_app_title(&mut self, mediatype: MediaType, title_id: u64) -> crate::Result<()> {
+        ResultCode(unsafe { ctru_sys::AM_DeleteAppTitle(mediatype.into(), title_id) })?;
+
+        Ok(())
+    }
+
+    /// Delete any title from the given install location.
+    ///
+    /// # Warning
+    ///
+    /// This operation is permanent and can't be undone. Contrary to [`Am::delete_app_title()`], this function
+    /// can delete system titles, which may render the console unusable. Prefer [`Am::delete_app_title()`] whenever possible.
+    #[doc(alias = "AM_DeleteTitle")]
+    pub fn delete_title(&mut self, mediatype: MediaType, title_id: u64) -> crate::Result<()> {
+        ResultCode(unsafe { ctru_sys::AM_DeleteTitle(mediatype.into(), title_id) })?;
+
+        Ok(())
+    }
+
+    /// Start installing a CIA file to the given install location.
+    ///
+    /// The contents of the CIA file must be written to the returned [`CiaInstall`] handle, which implements [`Write`](std::io::Write),
+    /// after which the installation must be completed with [`CiaInstall::finish()`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the installation couldn't be started.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use std::fs::File;
+    /// use std::io::{Read, Write};
+    /// use ctru::services::am::Am;
+    /// use ctru::services::fs::MediaType;
+    /// let mut app_manager = Am::new()?;
+    ///
+    /// let mut cia = File::open("sdmc:/game.cia")?;
+    /// let total = cia.metadata()?.len();
+    ///
+    /// let mut install = app_manager.start_cia_install(MediaType::Sd)?;
+    ///
+    /// let mut buffer = vec![0; 0x10000];
+    /// loop {
+    ///     let read = cia.read(&mut buffer)?;
+    ///     if read == 0 {
+    ///         break;
+    ///     }
+    ///
+    ///     install.write_all(&buffer[..read])?;
+    ///     println!("Installed {}/{total} bytes", install.bytes_written());
+    /// }
+    ///
+    /// install.finish()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "AM_StartCiaInstall")]
+    pub fn start_cia_install(&mut self, mediatype: MediaType) -> crate::Result<CiaInstall<'_>> {
+        let mut handle = 0;
+
+        ResultCode(unsafe { ctru_sys::AM_StartCiaInstall(mediatype.into(), &mut handle) })?;
+
+        Ok(CiaInstall {
+            handle,
+            offset: 0,
+            _am: PhantomData,
+        })
+    }
+}
+
+/// Handle to an in-progress CIA installation.
+///
+/// Obtained with [`Am::start_cia_install()`]. The CIA file's contents are streamed in with the [`Write`](std::io::Write) implementation.
+///
+/// If the handle is dropped without calling [`CiaInstall::finish()`], the installation is cancelled.
+pub struct CiaInstall<'a> {
+    handle: ctru_sys::Handle,
+    offset: u64,
+    _am: PhantomData<&'a mut Am>,
+}
+
+impl CiaInstall<'_> {
+    /// Returns the number of bytes written so far.
+    ///
+    /// Compare it with the size of the CIA file to track the installation's progress.
+    pub fn bytes_written(&self) -> u64 {
+        self.offset
+    }
+
+    /// Complete the installation.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the written data wasn't a complete and valid CIA file.
+    #[doc(alias = "AM_FinishCiaInstall")]
+    pub fn finish(self) -> crate::Result<()> {
+        let handle = self.handle;
+
+        // The handle is closed by `AM_FinishCiaInstall`, so the installation must not be cancelled on drop.
+        std::mem::forget(self);
+
+        ResultCode(unsafe { ctru_sys::AM_FinishCiaInstall(handle) })?;
+
+        Ok(())
+    }
+}
+
+impl io::Write for CiaInstall<'_> {
+    #[doc(alias = "FSFILE_Write")]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Larger buffers are written partially, as allowed by `Write::write()`.
+        let size = u32::try_from(buf.len()).unwrap_or(u32::MAX);
+        let mut written = 0;
+
+        let res = unsafe {
+            ctru_sys::FSFILE_Write(
+                self.handle,
+                &mut written,
+                self.offset,
+                buf.as_ptr().cast(),
+                size,
+                0,
+            )
+        };
+
+        if ctru_sys::R_FAILED(res) {
+            return Err(io::Error::other(crate::Error::from(res)));
+        }
+
+        self.offset += u64::from(written);
+
+        Ok(written as usize)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for CiaInstall<'_> {
+    #[doc(alias = "AM_CancelCIAInstall")]
+    fn drop(&mut self) {
+        let _ = unsafe { ctru_sys::AM_CancelCIAInstall(self.handle) };
+    }
 }
 
 impl Drop for Am {
@@ -8,6 +8,11 @@
 
 use crate::error::ResultCode;
 
+// Screen capture post permission values. `0` resets the permission, `1` leaves it unset (the default behaviour).
+// See <https://www.3dbrew.org/wiki/APT:SetScreenCapPostPermission>
+const SCREEN_CAP_POST_ENABLED: u32 = 2;
+const SCREEN_CAP_POST_DISABLED: u32 = 3;
+
 /// Handle to the Applet service.
 pub struct Apt(());
 
@@ -119,11 +124,139 @@ impl Apt {
         unsafe { ctru_sys::aptIsHomeAllowed() }
     }
 
+    /// Set whether the user is allowed to post screenshots of the application taken from the HOME Menu.
+    ///
+    /// # Notes
+    ///
+    /// When the user opens the HOME Menu, the system captures the current content of both screens, which can then be shared
+    /// (originally to Miiverse) from the HOME Menu. Disallowing it hides the captured screens from the HOME Menu's posting features.
+    /// The permission only affects the HOME Menu: it doesn't prevent capturing the screens in any other way (e.g. capture cards or homebrew tools).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::apt::Apt;
+    /// let mut apt = Apt::new()?;
+    ///
+    /// apt.set_screen_capture_allowed(false)?;
+    /// assert!(!apt.is_screen_capture_allowed()?);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "APT_SetScreenCapPostPermission")]
+    pub fn set_screen_capture_allowed(&mut self, allowed: bool) -> crate::Result<()> {
+        let permission = if allowed {
+            SCREEN_CAP_POST_ENABLED
+        } else {
+            SCREEN_CAP_POST_DISABLED
+        };
+
+        ResultCode(unsafe { ctru_sys::APT_SetScreenCapPostPermission(permission) })?;
+
+        Ok(())
+    }
+
+    /// Check whether the user is allowed to post screenshots of the application taken from the HOME Menu.
+    ///
+    /// Screenshots are allowed unless they were explicitly disallowed with [`Apt::set_screen_capture_allowed()`].
+    #[doc(alias = "APT_GetScreenCapPostPermission")]
+    pub fn is_screen_capture_allowed(&self) -> crate::Result<bool> {
+        let mut permission = 0;
+
+        ResultCode(unsafe { ctru_sys::APT_GetScreenCapPostPermission(&mut permission) })?;
+
+        Ok(permission != SCREEN_CAP_POST_DISABLED)
+    }
+
     /// Immediately jumps to the home menu.
     #[doc(alias = "aptJumpToHomeMenu")]
     pub fn jump_to_home_menu(&mut self) {
         unsafe { ctru_sys::aptJumpToHomeMenu() }
     }
+
+    /// Check if the console's wireless communication is enabled (i.e. the console is not in "airplane mode").
+    ///
+    /// # Notes
+    ///
+    /// This only checks whether the wireless hardware is turned on, not whether the console is connected to an access point.
+    /// Have a look at [`WifiStrength::current()`](crate::os::WifiStrength::current) to check the status of the connection.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::apt::Apt;
+    /// let apt = Apt::new()?;
+    ///
+    /// if !apt.is_wireless_enabled() {
+    ///     println!("Networking features are unavailable.");
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn is_wireless_enabled(&self) -> bool {
+        // The network state stored in the shared configuration memory is set to 7 while wireless communication is disabled.
+        // See <https://www.3dbrew.org/wiki/Configuration_Memory#Shared_Memory_Page_For_ARM11_Processes>
+        const WIRELESS_DISABLED: u8 = 7;
+
+        let config = ctru_sys::OS_SHAREDCFG_VADDR as *const ctru_sys::osSharedConfig_s;
+
+        let state = unsafe { std::ptr::addr_of!((*config).network_state).read_volatile() };
+
+        state != WIRELESS_DISABLED
+    }
+
+    /// Enable or disable the console's wireless communication.
+    ///
+    /// # Notes
+    ///
+    /// This function requires access to the `nwm::EXT` service, which is not normally granted to user applications.
+    /// Homebrew launched via the Homebrew Launcher usually has access to it, but other environments (e.g. `.cia` installs with a
+    /// restrictive exheader) may not.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the `nwm::EXT` service could not be accessed (e.g. due to missing permissions)
+    /// or if the request was rejected by the system.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::apt::Apt;
+    /// let mut apt = Apt::new()?;
+    ///
+    /// if !apt.is_wireless_enabled() {
+    ///     apt.set_wireless(true)?;
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "NWMEXT_ControlWirelessEnabled")]
+    pub fn set_wireless(&mut self, enabled: bool) -> crate::Result<()> {
+        ResultCode(unsafe { ctru_sys::nwmExtInit() })?;
+
+        let res = ResultCode(unsafe { ctru_sys::NWMEXT_ControlWirelessEnabled(enabled) });
+
+        unsafe { ctru_sys::nwmExtExit() };
+
+        res?;
+
+        Ok(())
+    }
 }
 
 impl Drop for Apt {
@@ -163,6 +296,7 @@ impl<'a> Chainloader<'a> {
     ///
     /// See also [`Title`](crate::services::am::Title]
     #[doc(alias = "aptSetChainloader")]
+    #[cfg(feature = "am")]
     pub fn set(&mut self, title: &super::am::Title<'_>) {
         unsafe { ctru_sys::aptSetChainloader(title.id(), title.media_type() as u8) }
     }
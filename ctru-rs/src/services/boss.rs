@@ -0,0 +1,228 @@
+//! BOSS service.
+//!
+//! The BOSS service handles the background downloads of SpotPass content (such as news, DLC announcements or game updates).
+//! Downloaded content is stored as "NsData" entries in the application's BOSS storage (a section of its extra data).
+//!
+//! This module only gives read-only access to the content already downloaded in the storage.
+//!
+//! TODO: [`ctru-rs`](crate) doesn't support registering or running BOSS tasks yet.
+//!
+//! # Notes
+//!
+//! BOSS is a privileged service: the application's exheader must grant access to `boss:U` (or `boss:P`).
+//! Homebrew launched via the Homebrew Launcher usually has access to it, but `.cia` installs need the service
+//! to be listed in their RSF file. The BOSS storage of an application can only be accessed if the extra data it lives in exists.
+#![doc(alias = "spotpass")]
+
+use std::sync::Mutex;
+
+use crate::error::ResultCode;
+use crate::services::fs::MediaType;
+use crate::services::ServiceReference;
+
+pub(crate) static BOSS_ACTIVE: Mutex<()> = Mutex::new(());
+
+/// Maximum number of entries retrieved by [`Boss::storage_entries()`].
+const MAX_ENTRIES: usize = 0x200;
+
+/// Filter matching all NsData entries, regardless of their type.
+const ALL_ENTRIES_FILTER: u32 = 0xFFFFFFFF;
+
+/// ID of an NsData entry in the BOSS storage.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NsDataId(pub u32);
+
+/// Handle to the BOSS service.
+pub struct Boss {
+    _service_handler: ServiceReference,
+}
+
+impl Boss {
+    /// Initialize a new service handle for the running application.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the service was unable to be initialized (e.g. due to missing permissions),
+    /// or if a [`Boss`] handle already exists.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::boss::Boss;
+    ///
+    /// let boss = Boss::new()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "bossInit")]
+    pub fn new() -> crate::Result<Self> {
+        let _service_handler = ServiceReference::new(
+            &BOSS_ACTIVE,
+            "boss:U",
+            || {
+                // A program ID of 0 makes the service use the ID of the running application.
+                ResultCode(unsafe { ctru_sys::bossInit(0, false) })?;
+
+                Ok(())
+            },
+            || unsafe {
+                ctru_sys::bossExit();
+            },
+        )?;
+
+        Ok(Self { _service_handler })
+    }
+
+    /// Select the extra data holding the BOSS storage to read from.
+    ///
+    /// `size` is the size (in bytes) of the BOSS storage within the extra data.
+    ///
+    /// # Notes
+    ///
+    /// The storage must be registered before using any of the other functions of this struct.
+    #[doc(alias = "bossSetStorageInfo")]
+    pub fn register_storage(
+        &mut self,
+        extdata_id: u64,
+        size: u32,
+        media_type: MediaType,
+    ) -> crate::Result<()> {
+        ResultCode(unsafe { ctru_sys::bossSetStorageInfo(extdata_id, size, media_type.into()) })?;
+
+        Ok(())
+    }
+
+    /// Unregister the BOSS storage selected with [`Boss::register_storage()`].
+    #[doc(alias = "bossUnregisterStorage")]
+    pub fn unregister_storage(&mut self) -> crate::Result<()> {
+        ResultCode(unsafe { ctru_sys::bossUnregisterStorage() })?;
+
+        Ok(())
+    }
+
+    /// Returns the IDs of the NsData entries in the registered BOSS storage.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::boss::Boss;
+    /// use ctru::services::fs::MediaType;
+    ///
+    /// let mut boss = Boss::new()?;
+    /// boss.register_storage(0x00001234, 0x100000, MediaType::Sd)?;
+    ///
+    /// for id in boss.storage_entries()? {
+    ///     println!("Entry {:#x}: {} bytes", id.0, boss.entry_size(id)?);
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "bossGetNsDataIdList")]
+    pub fn storage_entries(&self) -> crate::Result<Vec<NsDataId>> {
+        let mut ids = vec![0u32; MAX_ENTRIES];
+        let mut count = 0;
+
+        ResultCode(unsafe {
+            ctru_sys::bossGetNsDataIdList(
+                ALL_ENTRIES_FILTER,
+                ids.as_mut_ptr(),
+                MAX_ENTRIES as u16,
+                &mut count,
+            )
+        })?;
+
+        ids.truncate(count.into());
+
+        Ok(ids.into_iter().map(NsDataId).collect())
+    }
+
+    /// Returns the size (in bytes) of the content of an NsData entry.
+    #[doc(alias = "bossGetNsDataHeaderInfo")]
+    pub fn entry_size(&self, id: NsDataId) -> crate::Result<u32> {
+        let mut size = 0u32;
+
+        ResultCode(unsafe {
+            ctru_sys::bossGetNsDataHeaderInfo(
+                id.0,
+                ctru_sys::bossNsDataHeaderInfoType_ContentSize as u8,
+                (&mut size as *mut u32).cast(),
+                std::mem::size_of::<u32>() as u32,
+            )
+        })?;
+
+        Ok(size)
+    }
+
+    /// Read the content of an NsData entry, starting at `offset`, into `buf`.
+    ///
+    /// Returns the number of bytes read.
+    #[doc(alias = "bossReadNsData")]
+    pub fn read_entry(&self, id: NsDataId, offset: u64, buf: &mut [u8]) -> crate::Result<usize> {
+        let mut read = 0;
+        let mut unk = 0;
+
+        ResultCode(unsafe {
+            ctru_sys::bossReadNsData(
+                id.0,
+                offset,
+                buf.as_mut_ptr().cast(),
+                buf.len().try_into().unwrap_or(u32::MAX),
+                &mut read,
+                &mut unk,
+            )
+        })?;
+
+        Ok(read as usize)
+    }
+
+    /// Read the whole content of an NsData entry.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::boss::Boss;
+    /// use ctru::services::fs::MediaType;
+    ///
+    /// let mut boss = Boss::new()?;
+    /// boss.register_storage(0x00001234, 0x100000, MediaType::Sd)?;
+    ///
+    /// if let Some(&id) = boss.storage_entries()?.first() {
+    ///     let content = boss.read_entry_to_end(id)?;
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_entry_to_end(&self, id: NsDataId) -> crate::Result<Vec<u8>> {
+        let mut content = vec![0; self.entry_size(id)? as usize];
+        let mut offset = 0;
+
+        while offset < content.len() {
+            let read = self.read_entry(id, offset as u64, &mut content[offset..])?;
+
+            if read == 0 {
+                break;
+            }
+
+            offset += read;
+        }
+
+        content.truncate(offset);
+
+        Ok(content)
+    }
+}
@@ -10,10 +10,12 @@ use crate::services::ServiceReference;
 use ctru_sys::Handle;
 use private::Configuration;
 
+use std::cell::Cell;
+use std::marker::PhantomData;
 use std::sync::Mutex;
 use std::time::Duration;
 
-static CAM_ACTIVE: Mutex<()> = Mutex::new(());
+pub(crate) static CAM_ACTIVE: Mutex<()> = Mutex::new(());
 
 /// Handle to the Camera service.
 pub struct Cam {
@@ -45,6 +47,41 @@ pub enum FlipMode {
     Reverse = ctru_sys::FLIP_REVERSE,
 }
 
+/// Capture port used to transfer the images of a camera.
+///
+/// Image transfers (buffers, trimming, starting and stopping captures) are handled per-port,
+/// while image settings (exposure, white balance, effects...) are set per-camera.
+///
+/// See [`Camera::port()`] to learn how to use this.
+#[doc(alias = "CAMU_Port")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Port {
+    // Each camera is wired to a fixed port: `SELECT_IN1` and `SELECT_OUT1` to `PORT_CAM1`, `SELECT_OUT2` to `PORT_CAM2`
+    // (this is also how `libctru`'s own camera examples pair them when capturing in 3D).
+    /// Port of the inward camera and the right-side outward camera.
+    Cam1 = ctru_sys::PORT_CAM1,
+    /// Port of the left-side outward camera.
+    Cam2 = ctru_sys::PORT_CAM2,
+    /// Both ports, used when capturing with both outward cameras.
+    Both = ctru_sys::PORT_BOTH,
+}
+
+/// Configuration context of a camera.
+///
+/// Each camera holds two independent sets of image settings (view size, flip mode, effect and output format),
+/// called contexts. Preparing both and switching between them with [`Camera::set_context()`] is faster than
+/// changing the settings one by one (e.g. to toggle between a low resolution preview and a full resolution photo).
+#[doc(alias = "CAMU_Context")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Context {
+    /// Context A, active by default.
+    A = ctru_sys::CONTEXT_A,
+    /// Context B.
+    B = ctru_sys::CONTEXT_B,
+}
+
 /// Size of the camera view.
 ///
 /// See [`Camera::set_view_size()`] to learn how to use this.
@@ -77,6 +114,7 @@ pub enum ViewSize {
 /// Framerate settings.
 ///
 /// See [`Camera::set_frame_rate()`] to learn how to use this.
+/// Not all frame rates are supported with every [`ViewSize`], see [`ViewSize::supports_frame_rate()`].
 #[doc(alias = "CAMU_FramRate")]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u8)]
@@ -240,16 +278,137 @@ pub enum Trimming {
     /// Trimming configuration relatively to the center of the image.
     #[allow(missing_docs)]
     Centered { width: i16, height: i16 },
+    /// Trimming configuration keeping an arbitrary region of the image, from the top-left corner (`left`, `top`) included
+    /// to the bottom-right corner (`right`, `bottom`) excluded.
+    #[allow(missing_docs)]
+    Region {
+        left: i16,
+        top: i16,
+        right: i16,
+        bottom: i16,
+    },
     /// Trimming disabled.
     Off,
 }
 
+/// Rectangular region of the camera's view, in pixels.
+///
+/// Used to set the windows on which auto exposure and auto white balance are based.
+/// See [`Camera::set_auto_exposure_rect()`] and [`Camera::set_auto_white_balance_rect()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rect {
+    /// Starting x coordinate of the region.
+    pub x: i16,
+    /// Starting y coordinate of the region.
+    pub y: i16,
+    /// Width of the region.
+    pub width: i16,
+    /// Height of the region.
+    pub height: i16,
+}
+
+impl Rect {
+    /// Create a new region starting at (`x`, `y`) with the given size.
+    pub fn new(x: i16, y: i16, width: i16, height: i16) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Returns whether the region is entirely contained in a view of the given size.
+    pub fn fits_within(&self, view_size: ViewSize) -> bool {
+        let (view_width, view_height): (i16, i16) = view_size.into();
+
+        // Widen to avoid overflows on the sums.
+        let (x, y, width, height) = (
+            i32::from(self.x),
+            i32::from(self.y),
+            i32::from(self.width),
+            i32::from(self.height),
+        );
+
+        x >= 0
+            && y >= 0
+            && width >= 0
+            && height >= 0
+            && x + width <= i32::from(view_width)
+            && y + height <= i32::from(view_height)
+    }
+}
+
 /// Data used by the camera to calibrate image quality for a single camera.
-// TODO: Implement Image quality calibration.
+///
+/// The current calibration can be read with [`Camera::image_quality_calibration()`], tweaked with the accessors below,
+/// and applied with [`Camera::set_image_quality_calibration()`].
 #[doc(alias = "CAMU_ImageQualityCalibrationData")]
 #[derive(Default, Clone, Copy, Debug)]
 pub struct ImageQualityCalibration(pub ctru_sys::CAMU_ImageQualityCalibrationData);
 
+/// Implement a getter and a setter for a field of [`ImageQualityCalibration`].
+macro_rules! calibration_field {
+    ($(#[doc = $doc:literal])* $field:ident: $ty:ty, $getter:ident, $setter:ident) => {
+        $(#[doc = $doc])*
+        pub fn $getter(&self) -> $ty {
+            self.0.$field
+        }
+
+        #[doc = concat!("Set the value returned by [`ImageQualityCalibration::", stringify!($getter), "()`].")]
+        pub fn $setter(&mut self, value: $ty) {
+            self.0.$field = value;
+        }
+    };
+}
+
+impl ImageQualityCalibration {
+    calibration_field!(
+        /// Returns the base target brightness of the auto exposure.
+        aeBaseTarget: i16, ae_base_target, set_ae_base_target
+    );
+    calibration_field!(
+        /// Returns the red normalization coefficient of the left color correction matrix.
+        kRL: i16, k_rl, set_k_rl
+    );
+    calibration_field!(
+        /// Returns the green normalization coefficient of the left color correction matrix.
+        kGL: i16, k_gl, set_k_gl
+    );
+    calibration_field!(
+        /// Returns the blue normalization coefficient of the left color correction matrix.
+        kBL: i16, k_bl, set_k_bl
+    );
+    calibration_field!(
+        /// Returns the position of the color correction matrix.
+        ccmPosition: i16, ccm_position, set_ccm_position
+    );
+    calibration_field!(
+        /// Returns the red/green gain of the left color correction matrix, for the right camera.
+        awbCcmL9Right: u16, awb_ccm_l9_right, set_awb_ccm_l9_right
+    );
+    calibration_field!(
+        /// Returns the red/green gain of the left color correction matrix, for the left camera.
+        awbCcmL9Left: u16, awb_ccm_l9_left, set_awb_ccm_l9_left
+    );
+    calibration_field!(
+        /// Returns the blue/green gain of the left color correction matrix, for the right camera.
+        awbCcmL10Right: u16, awb_ccm_l10_right, set_awb_ccm_l10_right
+    );
+    calibration_field!(
+        /// Returns the blue/green gain of the left color correction matrix, for the left camera.
+        awbCcmL10Left: u16, awb_ccm_l10_left, set_awb_ccm_l10_left
+    );
+    calibration_field!(
+        /// Returns the position threshold of the color correction matrix, for the right camera.
+        awbX0Right: u16, awb_x0_right, set_awb_x0_right
+    );
+    calibration_field!(
+        /// Returns the position threshold of the color correction matrix, for the left camera.
+        awbX0Left: u16, awb_x0_left, set_awb_x0_left
+    );
+}
+
 /// Data used by the camera to calibrate image quality when using both outward cameras.
 // TODO: Implement Stereo camera calibration.
 #[doc(alias = "CAMU_StereoCameraCalibrationData")]
@@ -281,26 +440,48 @@ pub struct BothOutwardCam {
 }
 
 mod private {
-    use super::{BothOutwardCam, InwardCam, OutwardLeftCam, OutwardRightCam, Trimming, ViewSize};
+    use super::{
+        BothOutwardCam, Context, InwardCam, OutwardLeftCam, OutwardRightCam, Trimming, ViewSize,
+    };
 
     /// Basic configuration needed to properly use the built-in cameras.
     #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     pub struct Configuration {
-        pub view_size: ViewSize,
+        /// View size of each context, indexed as [A, B].
+        pub view_sizes: [ViewSize; 2],
         pub trimming: Trimming,
+        pub context: Context,
     }
 
     impl Configuration {
         pub fn new() -> Self {
             Self::default()
         }
+
+        /// Returns the view size of the active context.
+        pub fn view_size(&self) -> ViewSize {
+            self.view_sizes[self.context_index()]
+        }
+
+        /// Set the view size of the active context.
+        pub fn set_view_size(&mut self, size: ViewSize) {
+            self.view_sizes[self.context_index()] = size;
+        }
+
+        fn context_index(&self) -> usize {
+            match self.context {
+                Context::A => 0,
+                Context::B => 1,
+            }
+        }
     }
 
     impl Default for Configuration {
         fn default() -> Self {
             Self {
-                view_size: ViewSize::TopLCD,
+                view_sizes: [ViewSize::TopLCD; 2],
                 trimming: Trimming::Off,
+                context: Context::A,
             }
         }
     }
@@ -420,6 +601,10 @@ impl Camera for OutwardLeftCam {
     fn camera_as_raw(&self) -> ctru_sys::u32_ {
         ctru_sys::SELECT_OUT2.into()
     }
+
+    fn port(&self) -> Port {
+        Port::Cam2
+    }
 }
 
 impl Camera for BothOutwardCam {
@@ -427,8 +612,8 @@ impl Camera for BothOutwardCam {
         ctru_sys::SELECT_OUT1_OUT2.into()
     }
 
-    fn port_as_raw(&self) -> ctru_sys::u32_ {
-        ctru_sys::PORT_BOTH.into()
+    fn port(&self) -> Port {
+        Port::Both
     }
 
     fn take_picture(&mut self, buffer: &mut [u8], timeout: Duration) -> crate::Result<()> {
@@ -441,42 +626,8 @@ impl Camera for BothOutwardCam {
             });
         }
 
-        let final_view = self.final_view_size();
-
-        // The transfer unit is NOT the "max number of bytes" or whatever the docs make you think it is...
-        let transfer_unit = unsafe {
-            let mut transfer_unit = 0;
-
-            ResultCode(ctru_sys::CAMU_GetMaxBytes(
-                &mut transfer_unit,
-                final_view.0,
-                final_view.1,
-            ))?;
-
-            transfer_unit
-        };
-
-        unsafe {
-            ResultCode(ctru_sys::CAMU_SetTransferBytes(
-                self.port_as_raw(),
-                transfer_unit,
-                final_view.0,
-                final_view.1,
-            ))?;
-        };
-
-        unsafe {
-            ResultCode(ctru_sys::CAMU_Activate(self.camera_as_raw()))?;
-            ResultCode(ctru_sys::CAMU_ClearBuffer(self.port_as_raw()))?;
-        };
-
-        // Synchronize the two cameras.
-        unsafe {
-            ResultCode(ctru_sys::CAMU_SynchronizeVsyncTiming(
-                ctru_sys::SELECT_OUT1.into(),
-                ctru_sys::SELECT_OUT2.into(),
-            ))?;
-        }
+        let transfer_unit = prepare_capture(self)?;
+        clear_and_synchronize(self.port())?;
 
         // Start capturing with the camera.
         unsafe {
@@ -489,7 +640,7 @@ impl Camera for BothOutwardCam {
             ResultCode(ctru_sys::CAMU_SetReceiving(
                 &mut completion_handle,
                 buffer.as_mut_ptr().cast(),
-                ctru_sys::PORT_CAM1.into(),
+                Port::Cam1.into(),
                 (max_size / 2) as u32,
                 transfer_unit.try_into().unwrap(),
             ))?;
@@ -503,7 +654,7 @@ impl Camera for BothOutwardCam {
             ResultCode(ctru_sys::CAMU_SetReceiving(
                 &mut completion_handle,
                 buffer[max_size / 2..].as_mut_ptr().cast(),
-                ctru_sys::PORT_CAM2.into(),
+                Port::Cam2.into(),
                 (max_size / 2) as u32,
                 transfer_unit.try_into().unwrap(),
             ))?;
@@ -513,15 +664,7 @@ impl Camera for BothOutwardCam {
 
         unsafe {
             // Panicking without closing an SVC handle causes an ARM exception, we have to handle it carefully.
-            let wait_result_1 = ResultCode(ctru_sys::svcWaitSynchronization(
-                receive_event_1,
-                timeout.as_nanos().try_into().unwrap(),
-            ));
-
-            let wait_result_2 = ResultCode(ctru_sys::svcWaitSynchronization(
-                receive_event_2,
-                timeout.as_nanos().try_into().unwrap(),
-            ));
+            let wait_result = crate::os::wait_all(&[receive_event_1, receive_event_2], timeout);
 
             // We close everything first, then we check for possible errors
             let _ = ctru_sys::svcCloseHandle(receive_event_1); // We wouldn't return the error even if there was one, so no use of ResultCode is needed.
@@ -532,8 +675,7 @@ impl Camera for BothOutwardCam {
             ResultCode(ctru_sys::CAMU_ClearBuffer(self.port_as_raw()))?;
             ResultCode(ctru_sys::CAMU_Activate(ctru_sys::SELECT_NONE.into()))?;
 
-            wait_result_1?;
-            wait_result_2?;
+            wait_result?;
         };
 
         Ok(())
@@ -553,12 +695,83 @@ pub trait Camera: private::ConfigurableCamera {
     /// If you are interested in the final image's size, calculated while taking into account all processing and modifications,
     /// have a look at [`Camera::final_view_size()`].
     fn view_size(&self) -> ViewSize {
-        self.configuration().view_size
+        self.configuration().view_size()
+    }
+
+    /// Returns the [`Context`] currently used by the camera.
+    ///
+    /// Image settings (view size, flip mode, effect and output format) are applied to this context.
+    fn context(&self) -> Context {
+        self.configuration().context
+    }
+
+    /// Switch the camera to the given [`Context`], making its image settings active.
+    ///
+    /// All subsequent changes to the image settings (view size, flip mode, effect and output format) are applied to the new context.
+    ///
+    /// # Notes
+    ///
+    /// The [`Trimming`] configuration is shared by both contexts, so it is reset if the view size of the new context is different.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::cam::{Cam, Camera, Context, ViewSize};
+    /// let mut cam = Cam::new()?;
+    ///
+    /// let outward = &mut cam.outer_right_cam;
+    ///
+    /// // Prepare a small preview in context A, and a full resolution photo in context B.
+    /// outward.set_view_size(ViewSize::BottomLCD)?;
+    /// outward.set_context(Context::B)?;
+    /// outward.set_view_size(ViewSize::Vga)?;
+    ///
+    /// // Go back to the preview.
+    /// outward.set_context(Context::A)?;
+    /// assert_eq!(outward.view_size(), ViewSize::BottomLCD);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "CAMU_SwitchContext")]
+    fn set_context(&mut self, context: Context) -> crate::Result<()> {
+        unsafe {
+            ResultCode(ctru_sys::CAMU_SwitchContext(
+                self.camera_as_raw(),
+                context.into(),
+            ))?;
+        }
+
+        let previous_size = self.view_size();
+        self.configuration_mut().context = context;
+
+        if self.view_size() != previous_size {
+            self.set_trimming(Trimming::Off)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the [`Port`] used to transfer the images of the selected camera.
+    ///
+    /// # Notes
+    ///
+    /// [`OutwardLeftCam`] uses [`Port::Cam2`]. Previous versions of this crate transferred its images through
+    /// [`Port::Cam1`] (the port of the right-side outward camera), so trimming, buffer and capture settings
+    /// made on the left camera used to affect (and be read from) the right camera's port instead.
+    fn port(&self) -> Port {
+        Port::Cam1
     }
 
     /// Returns the raw port of the selected camera.
+    ///
+    /// See [`Camera::port()`] for a typed alternative.
     fn port_as_raw(&self) -> ctru_sys::u32_ {
-        ctru_sys::PORT_CAM1.into()
+        self.port().into()
     }
 
     /// Returns `true` if the camera is busy (receiving data).
@@ -621,7 +834,7 @@ pub trait Camera: private::ConfigurableCamera {
         let mut res: usize = (size.0 as usize * size.1 as usize) * std::mem::size_of::<i16>();
 
         // If we are taking a picture using both outwards cameras, we need to expect 2 images, rather than just 1
-        if self.port_as_raw() == ctru_sys::PORT_BOTH.into() {
+        if self.port() == Port::Both {
             res *= 2;
         }
 
@@ -658,6 +871,12 @@ pub trait Camera: private::ConfigurableCamera {
     fn final_view_size(&self) -> (i16, i16) {
         match self.trimming() {
             Trimming::Centered { width, height } => (width, height),
+            Trimming::Region {
+                left,
+                top,
+                right,
+                bottom,
+            } => (right - left, bottom - top),
             Trimming::Off => self.view_size().into(),
         }
     }
@@ -677,8 +896,9 @@ pub trait Camera: private::ConfigurableCamera {
     /// # Panics
     ///
     /// Setting up a [`Trimming`] configurations that exceeds the bounds of the original
-    /// image's size will result in a panic.
+    /// image's size (or a [`Trimming::Region`] with a negative size) will result in a panic.
     #[doc(alias = "CAMU_SetTrimming")]
+    #[doc(alias = "CAMU_SetTrimmingParams")]
     fn set_trimming(&mut self, trimming: Trimming) -> crate::Result<()> {
         match trimming {
             Trimming::Centered { width, height } => unsafe {
@@ -701,6 +921,35 @@ pub trait Camera: private::ConfigurableCamera {
                     view_size.1,
                 ))?;
             },
+            Trimming::Region {
+                left,
+                top,
+                right,
+                bottom,
+            } => unsafe {
+                let (view_width, view_height): (i16, i16) = self.view_size().into();
+
+                // Check whether the region is within the view.
+                assert!(
+                    0 <= left
+                        && left <= right
+                        && right <= view_width
+                        && 0 <= top
+                        && top <= bottom
+                        && bottom <= view_height,
+                    "trimmed region is not within the camera view",
+                );
+
+                ResultCode(ctru_sys::CAMU_SetTrimming(self.port_as_raw(), true))?;
+
+                ResultCode(ctru_sys::CAMU_SetTrimmingParams(
+                    self.port_as_raw(),
+                    left,
+                    top,
+                    right,
+                    bottom,
+                ))?;
+            },
             Trimming::Off => unsafe {
                 ResultCode(ctru_sys::CAMU_SetTrimming(self.port_as_raw(), false))?;
             },
@@ -714,7 +963,7 @@ pub trait Camera: private::ConfigurableCamera {
     /// Returns whether or not trimming is currently enabled for the camera.
     #[doc(alias = "CAMU_IsTrimming")]
     fn is_trimming(&self) -> bool {
-        matches!(self.trimming(), Trimming::Off)
+        !matches!(self.trimming(), Trimming::Off)
     }
 
     /// Set the exposure level of the camera.
@@ -779,7 +1028,7 @@ pub trait Camera: private::ConfigurableCamera {
             ResultCode(ctru_sys::CAMU_FlipImage(
                 self.camera_as_raw(),
                 flip.into(),
-                ctru_sys::CONTEXT_A,
+                self.context().into(),
             ))?;
             Ok(())
         }
@@ -796,11 +1045,11 @@ pub trait Camera: private::ConfigurableCamera {
             ResultCode(ctru_sys::CAMU_SetSize(
                 self.camera_as_raw(),
                 size.into(),
-                ctru_sys::CONTEXT_A,
+                self.context().into(),
             ))?;
         }
 
-        self.configuration_mut().view_size = size;
+        self.configuration_mut().set_view_size(size);
 
         self.set_trimming(Trimming::Off)?;
 
@@ -819,6 +1068,67 @@ pub trait Camera: private::ConfigurableCamera {
         }
     }
 
+    /// Set the view size, frame rate and output format of the camera at once, checking that they can be used together.
+    ///
+    /// Since [`Camera::set_view_size()`] and [`Camera::set_frame_rate()`] don't validate the resulting configuration,
+    /// this function should be preferred to set up the camera's capture parameters.
+    /// See [`ViewSize::supports_frame_rate()`] for the supported combinations.
+    ///
+    /// # Notes
+    ///
+    /// Like [`Camera::set_view_size()`], calling this function will reset the trimming configuration.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the frame rate isn't supported with the given view size,
+    /// without changing the current configuration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::cam::{Cam, Camera, FrameRate, OutputFormat, ViewSize};
+    /// let mut cam = Cam::new()?;
+    ///
+    /// let inward = &mut cam.inner_cam;
+    ///
+    /// inward.set_capture_params(ViewSize::TopLCD, FrameRate::Fps30, OutputFormat::Rgb565)?;
+    ///
+    /// // VGA images can't be captured at 30 FPS.
+    /// assert!(inward
+    ///     .set_capture_params(ViewSize::Vga, FrameRate::Fps30, OutputFormat::Rgb565)
+    ///     .is_err());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "CAMU_SetSize")]
+    #[doc(alias = "CAMU_SetFrameRate")]
+    #[doc(alias = "CAMU_SetOutputFormat")]
+    fn set_capture_params(
+        &mut self,
+        size: ViewSize,
+        frame_rate: FrameRate,
+        format: OutputFormat,
+    ) -> crate::Result<()> {
+        if !size.supports_frame_rate(frame_rate) {
+            let (width, height): (i16, i16) = size.into();
+
+            return Err(crate::Error::Other(format!(
+                "frame rate {frame_rate:?} is not supported with view size {size:?} ({width}x{height}), which can be captured at up to 15 FPS"
+            )));
+        }
+
+        self.set_view_size(size)?;
+        self.set_frame_rate(frame_rate)?;
+        self.set_output_format(format)?;
+
+        Ok(())
+    }
+
     /// Set the photo mode of the camera.
     #[doc(alias = "CAMU_SetPhotoMode")]
     fn set_photo_mode(&mut self, photo_mode: PhotoMode) -> crate::Result<()> {
@@ -842,7 +1152,7 @@ pub trait Camera: private::ConfigurableCamera {
             ResultCode(ctru_sys::CAMU_SetEffect(
                 self.camera_as_raw(),
                 effect.into(),
-                ctru_sys::CONTEXT_A,
+                self.context().into(),
             ))?;
             Ok(())
         }
@@ -879,7 +1189,7 @@ pub trait Camera: private::ConfigurableCamera {
             ResultCode(ctru_sys::CAMU_SetOutputFormat(
                 self.camera_as_raw(),
                 format.into(),
-                ctru_sys::CONTEXT_A,
+                self.context().into(),
             ))?;
             Ok(())
         }
@@ -913,6 +1223,41 @@ pub trait Camera: private::ConfigurableCamera {
         }
     }
 
+    /// Set the region in which auto exposure should be based on, using a [`Rect`].
+    ///
+    /// # Panics
+    ///
+    /// Passing a [`Rect`] that exceeds the bounds of the camera's current [`ViewSize`] will result in a panic.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::cam::{Cam, Camera, Rect, ViewSize};
+    /// let mut cam = Cam::new()?;
+    ///
+    /// let inward = &mut cam.inner_cam;
+    /// inward.set_view_size(ViewSize::TopLCD)?;
+    ///
+    /// // Base the exposure on the center of the image.
+    /// inward.set_auto_exposure_rect(Rect::new(100, 60, 200, 120))?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "CAMU_SetAutoExposureWindow")]
+    fn set_auto_exposure_rect(&mut self, rect: Rect) -> crate::Result<()> {
+        assert!(
+            rect.fits_within(self.view_size()),
+            "auto exposure window {rect:?} is out of the bounds of the camera view",
+        );
+
+        self.set_auto_exposure_window(rect.x, rect.y, rect.width, rect.height)
+    }
+
     /// Set the region in which auto white balance should be based on.
     ///
     /// # Arguments
@@ -945,6 +1290,25 @@ pub trait Camera: private::ConfigurableCamera {
         }
     }
 
+    /// Set the region in which auto white balance should be based on, using a [`Rect`].
+    ///
+    /// # Notes
+    ///
+    /// To activate automatic white balance, you must pass [`WhiteBalance::Auto`] into [`Camera::set_white_balance()`].
+    ///
+    /// # Panics
+    ///
+    /// Passing a [`Rect`] that exceeds the bounds of the camera's current [`ViewSize`] will result in a panic.
+    #[doc(alias = "CAMU_SetAutoWhiteBalanceWindow")]
+    fn set_auto_white_balance_rect(&mut self, rect: Rect) -> crate::Result<()> {
+        assert!(
+            rect.fits_within(self.view_size()),
+            "auto white balance window {rect:?} is out of the bounds of the camera view",
+        );
+
+        self.set_auto_white_balance_window(rect.x, rect.y, rect.width, rect.height)
+    }
+
     /// Set whether the noise filter should be enabled or disabled for the camera.
     #[doc(alias = "CAMU_SetNoiseFilter")]
     fn set_noise_filter(&mut self, enabled: bool) -> crate::Result<()> {
@@ -1027,34 +1391,8 @@ pub trait Camera: private::ConfigurableCamera {
             });
         }
 
-        let final_view = self.final_view_size();
-
-        // The transfer unit is NOT the "max number of bytes" or whatever the docs make you think it is...
-        let transfer_unit = unsafe {
-            let mut transfer_unit = 0;
-
-            ResultCode(ctru_sys::CAMU_GetMaxBytes(
-                &mut transfer_unit,
-                final_view.0,
-                final_view.1,
-            ))?;
-
-            transfer_unit
-        };
-
-        unsafe {
-            ResultCode(ctru_sys::CAMU_SetTransferBytes(
-                self.port_as_raw(),
-                transfer_unit,
-                final_view.0,
-                final_view.1,
-            ))?;
-        };
-
-        unsafe {
-            ResultCode(ctru_sys::CAMU_Activate(self.camera_as_raw()))?;
-            ResultCode(ctru_sys::CAMU_ClearBuffer(self.port_as_raw()))?;
-        };
+        let transfer_unit = prepare_capture(self)?;
+        clear_and_synchronize(self.port())?;
 
         let receive_event = unsafe {
             let mut completion_handle: Handle = 0;
@@ -1095,6 +1433,396 @@ pub trait Camera: private::ConfigurableCamera {
 
         Ok(())
     }
+
+    /// Start taking a picture and writing it in `buffer`, without waiting for it to be received.
+    ///
+    /// The returned guard can be polled with [`CaptureInProgress::is_ready()`] while the calling thread does other work
+    /// (e.g. rendering a frame), and must then be [finished](CaptureInProgress::finish).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the buffer is too short or if the camera is already busy.
+    ///
+    /// # Notes
+    ///
+    /// If the picture is taken using [`BothOutwardCam`], the buffer will have to be able to hold both images
+    /// (from each camera), which will be written into it sequentially.
+    /// Use [`Camera::final_byte_length()`] to know how big the buffer needs to be to hold your next image.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::cam::{Cam, Camera};
+    /// let mut cam = Cam::new()?;
+    ///
+    /// let camera = &mut cam.outer_right_cam;
+    /// let mut buffer = vec![0; camera.final_byte_length()];
+    ///
+    /// let capture = camera.begin_capture(&mut buffer)?;
+    ///
+    /// while !capture.is_ready() {
+    ///     // Do something else in the meantime...
+    ///     std::thread::yield_now();
+    /// }
+    ///
+    /// capture.finish()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "CAMU_SetReceiving")]
+    fn begin_capture<'a>(
+        &'a mut self,
+        buffer: &'a mut [u8],
+    ) -> crate::Result<CaptureInProgress<'a>> {
+        // Check whether the provided buffer is big enough to store the image.
+        let max_size = self.final_byte_length();
+        if buffer.len() < max_size {
+            return Err(Error::BufferTooShort {
+                provided: buffer.len(),
+                wanted: max_size,
+            });
+        }
+
+        let transfer_unit = prepare_capture(self)?;
+
+        // From now on, the camera is deactivated when the guard is dropped, even if starting the capture fails.
+        let mut capture = CaptureInProgress {
+            port: self.port(),
+            handles: Vec::with_capacity(2),
+            ready: Cell::new(false),
+            active: true,
+            _borrow: PhantomData,
+        };
+
+        clear_and_synchronize(capture.port)?;
+
+        let targets = match capture.port {
+            Port::Both => vec![(Port::Cam1, 0), (Port::Cam2, max_size / 2)],
+            port => vec![(port, 0)],
+        };
+        let size = max_size / targets.len();
+
+        for (port, offset) in targets {
+            let mut completion_handle: Handle = 0;
+
+            unsafe {
+                ResultCode(ctru_sys::CAMU_SetReceiving(
+                    &mut completion_handle,
+                    buffer[offset..].as_mut_ptr().cast(),
+                    port.into(),
+                    size as u32,
+                    transfer_unit.try_into().unwrap(),
+                ))?;
+            }
+
+            capture.handles.push(completion_handle);
+        }
+
+        // Start capturing with the camera.
+        unsafe { ResultCode(ctru_sys::CAMU_StartCapture(capture.port.into()))? };
+
+        Ok(capture)
+    }
+
+    /// Start capturing frames continuously, returning a [`CaptureStream`] to receive them.
+    ///
+    /// Unlike [`Camera::take_picture()`], the camera is activated only once and keeps capturing until the stream is dropped,
+    /// which is fast enough to show a live preview at the camera's [frame rate](Camera::set_frame_rate).
+    /// `timeout` is the maximum time [`CaptureStream::next_frame()`] waits for a frame.
+    ///
+    /// # Notes
+    ///
+    /// The camera's configuration can't be changed while the stream is active, since the stream borrows the camera.
+    /// Only one stream can be active at a time on each [`Port`]: [`InwardCam`] and [`OutwardRightCam`] share [`Port::Cam1`],
+    /// while [`BothOutwardCam`] uses both ports, so their streams must not overlap.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the camera couldn't be activated, or if it is already busy.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # use std::time::Duration;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::cam::{Cam, Camera, OutputFormat, ViewSize};
+    /// let mut cam = Cam::new()?;
+    ///
+    /// let camera = &mut cam.outer_right_cam;
+    /// camera.set_view_size(ViewSize::TopLCD)?;
+    /// camera.set_output_format(OutputFormat::Rgb565)?;
+    ///
+    /// let mut buffer = vec![0; camera.final_byte_length()];
+    ///
+    /// let mut stream = camera.stream(Duration::from_millis(300))?;
+    ///
+    /// for _ in 0..10 {
+    ///     stream.next_frame(&mut buffer)?;
+    ///     // Show the frame on screen...
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "CAMU_StartCapture")]
+    fn stream(&mut self, timeout: Duration) -> crate::Result<CaptureStream<'_>> {
+        let transfer_unit = prepare_capture(self)?;
+
+        // From now on, the camera is deactivated when the stream is dropped, even if starting the capture fails.
+        let stream = CaptureStream {
+            port: self.port(),
+            byte_length: self.final_byte_length(),
+            transfer_unit: transfer_unit.try_into().unwrap(),
+            timeout,
+            _camera: PhantomData,
+        };
+
+        clear_and_synchronize(stream.port)?;
+
+        unsafe { ResultCode(ctru_sys::CAMU_StartCapture(stream.port.into()))? };
+
+        Ok(stream)
+    }
+}
+
+/// Capture started by [`Camera::begin_capture()`], which hasn't been [finished](CaptureInProgress::finish) yet.
+///
+/// Dropping the guard without calling [`CaptureInProgress::finish()`] cancels the capture and deactivates the camera.
+#[must_use = "dropping the guard cancels the capture"]
+pub struct CaptureInProgress<'a> {
+    port: Port,
+    handles: Vec<Handle>,
+    ready: Cell<bool>,
+    active: bool,
+    // The camera writes to the buffer until the capture is stopped, and its configuration must not change in the meantime.
+    _borrow: PhantomData<&'a mut [u8]>,
+}
+
+impl CaptureInProgress<'_> {
+    /// Returns `true` if the picture has been fully written to the buffer, without blocking.
+    #[doc(alias = "svcWaitSynchronization")]
+    pub fn is_ready(&self) -> bool {
+        if !self.ready.get() {
+            // The completion events are reset once waited on, so the result is remembered.
+            self.ready
+                .set(crate::os::wait_all(&self.handles, Duration::ZERO).is_ok());
+        }
+
+        self.ready.get()
+    }
+
+    /// Stop the capture and deactivate the camera, giving the buffer back.
+    ///
+    /// This function doesn't wait for the capture: call it once [`CaptureInProgress::is_ready()`] returns `true`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the picture wasn't fully received yet (in which case the buffer's contents are incomplete),
+    /// or if the camera couldn't be stopped.
+    #[doc(alias = "CAMU_StopCapture")]
+    pub fn finish(mut self) -> crate::Result<()> {
+        let result = if self.is_ready() {
+            Ok(())
+        } else {
+            crate::os::wait_all(&self.handles, Duration::ZERO)
+        };
+
+        self.cleanup()?;
+
+        result
+    }
+
+    fn cleanup(&mut self) -> crate::Result<()> {
+        self.active = false;
+
+        close_handles(&std::mem::take(&mut self.handles));
+
+        unsafe {
+            ResultCode(ctru_sys::CAMU_StopCapture(self.port.into()))?;
+            ResultCode(ctru_sys::CAMU_ClearBuffer(self.port.into()))?;
+            ResultCode(ctru_sys::CAMU_Activate(ctru_sys::SELECT_NONE.into()))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for CaptureInProgress<'_> {
+    fn drop(&mut self) {
+        if self.active {
+            let _ = self.cleanup();
+        }
+    }
+}
+
+/// Continuous capture started by [`Camera::stream()`].
+///
+/// The camera keeps capturing (and is kept active) until the stream is dropped.
+pub struct CaptureStream<'cam> {
+    port: Port,
+    byte_length: usize,
+    transfer_unit: i16,
+    timeout: Duration,
+    // The camera's configuration must not change while capturing.
+    _camera: PhantomData<&'cam mut ()>,
+}
+
+impl CaptureStream<'_> {
+    /// Wait for the next frame captured by the camera, and write it in `buffer`.
+    ///
+    /// Frames captured before this function is called are skipped, so the received frame is always a fresh one.
+    ///
+    /// # Notes
+    ///
+    /// If the stream was started by [`BothOutwardCam`], the buffer will have to be able to hold both images
+    /// (from each camera), which will be written into it sequentially.
+    /// The required size is the [`Camera::final_byte_length()`] of the camera at the time the stream was started.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `buffer` is too short, or if no frame is received before the stream's timeout
+    /// (in which case the capture is restarted, so that the stream can still be used).
+    #[doc(alias = "CAMU_SetReceiving")]
+    pub fn next_frame(&mut self, buffer: &mut [u8]) -> crate::Result<()> {
+        if buffer.len() < self.byte_length {
+            return Err(Error::BufferTooShort {
+                provided: buffer.len(),
+                wanted: self.byte_length,
+            });
+        }
+
+        let targets = match self.port {
+            Port::Both => vec![(Port::Cam1, 0), (Port::Cam2, self.byte_length / 2)],
+            port => vec![(port, 0)],
+        };
+        let size = self.byte_length / targets.len();
+
+        let mut handles: Vec<Handle> = Vec::with_capacity(targets.len());
+
+        for &(port, offset) in &targets {
+            let mut completion_handle: Handle = 0;
+
+            let result = ResultCode(unsafe {
+                ctru_sys::CAMU_SetReceiving(
+                    &mut completion_handle,
+                    buffer[offset..].as_mut_ptr().cast(),
+                    port.into(),
+                    size as u32,
+                    self.transfer_unit,
+                )
+            });
+
+            if let Err(e) = result {
+                self.cancel(&handles);
+                return Err(e);
+            }
+
+            handles.push(completion_handle);
+        }
+
+        // Panicking without closing an SVC handle causes an ARM exception, we have to handle it carefully.
+        let wait_result = crate::os::wait_all(&handles, self.timeout);
+
+        if wait_result.is_err() {
+            // The transfer must not keep writing to the buffer after it is given back.
+            self.cancel(&handles);
+        } else {
+            close_handles(&handles);
+        }
+
+        wait_result
+    }
+
+    /// Stop the pending transfers, and restart the capture from scratch.
+    fn cancel(&mut self, handles: &[Handle]) {
+        unsafe {
+            let _ = ctru_sys::CAMU_StopCapture(self.port.into());
+            let _ = ctru_sys::CAMU_ClearBuffer(self.port.into());
+
+            close_handles(handles);
+
+            let _ = ctru_sys::CAMU_StartCapture(self.port.into());
+        }
+    }
+}
+
+impl Drop for CaptureStream<'_> {
+    #[doc(alias = "CAMU_StopCapture")]
+    fn drop(&mut self) {
+        unsafe {
+            let _ = ctru_sys::CAMU_StopCapture(self.port.into());
+            let _ = ctru_sys::CAMU_ClearBuffer(self.port.into());
+            let _ = ctru_sys::CAMU_Activate(ctru_sys::SELECT_NONE.into());
+        }
+    }
+}
+
+/// Configure the transfer of the camera's final image on its port, then activate the camera.
+///
+/// Returns the transfer unit to use when receiving the image.
+fn prepare_capture<C: Camera + ?Sized>(camera: &C) -> crate::Result<u32> {
+    if camera.is_busy()? {
+        return Err(Error::Other("the camera is already capturing".into()));
+    }
+
+    let final_view = camera.final_view_size();
+
+    // The transfer unit is NOT the "max number of bytes" or whatever the docs make you think it is...
+    let transfer_unit = unsafe {
+        let mut transfer_unit = 0;
+
+        ResultCode(ctru_sys::CAMU_GetMaxBytes(
+            &mut transfer_unit,
+            final_view.0,
+            final_view.1,
+        ))?;
+
+        transfer_unit
+    };
+
+    unsafe {
+        ResultCode(ctru_sys::CAMU_SetTransferBytes(
+            camera.port_as_raw(),
+            transfer_unit,
+            final_view.0,
+            final_view.1,
+        ))?;
+
+        ResultCode(ctru_sys::CAMU_Activate(camera.camera_as_raw()))?;
+    }
+
+    Ok(transfer_unit)
+}
+
+/// Discard any data left in the port's buffer, and synchronize the cameras if both of them are used.
+fn clear_and_synchronize(port: Port) -> crate::Result<()> {
+    unsafe {
+        ResultCode(ctru_sys::CAMU_ClearBuffer(port.into()))?;
+
+        if port == Port::Both {
+            ResultCode(ctru_sys::CAMU_SynchronizeVsyncTiming(
+                ctru_sys::SELECT_OUT1.into(),
+                ctru_sys::SELECT_OUT2.into(),
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn close_handles(handles: &[Handle]) {
+    for &handle in handles {
+        // We wouldn't return the error even if there was one, so no use of ResultCode is needed.
+        let _ = unsafe { ctru_sys::svcCloseHandle(handle) };
+    }
 }
 
 impl Trimming {
@@ -1111,6 +1839,42 @@ impl Trimming {
         Self::Centered { width, height }
     }
 
+    /// Create a new [`Trimming`] configuration keeping the region from (`left`, `top`) to (`right`, `bottom`) of the original image.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the region has a negative size, or if its pixel area
+    /// (`(right - left) * (bottom - top)`) is not a multiple of 128.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// use ctru::services::cam::Trimming;
+    ///
+    /// // Keep the 256x192 region at the top-left corner of the image.
+    /// let trimming = Trimming::new_region(0, 0, 256, 192);
+    /// ```
+    pub fn new_region(left: i16, top: i16, right: i16, bottom: i16) -> Self {
+        // Widen to avoid overflows.
+        let width = i32::from(right) - i32::from(left);
+        let height = i32::from(bottom) - i32::from(top);
+
+        assert!(
+            width >= 0 && height >= 0,
+            "trimmed region has a negative size"
+        );
+        // Pixel area must be a multiple of 128.
+        assert!((width * height) % 128 == 0);
+
+        Self::Region {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
     /// Create a new [`Trimming`] configuration using a standard view size centered to the original image.
     pub fn new_centered_with_view(size: ViewSize) -> Self {
         let size: (i16, i16) = size.into();
@@ -1154,6 +1918,7 @@ impl Cam {
     pub fn new() -> crate::Result<Cam> {
         let _service_handler = ServiceReference::new(
             &CAM_ACTIVE,
+            "cam:u",
             || {
                 ResultCode(unsafe { ctru_sys::camInit() })?;
 
@@ -1218,6 +1983,107 @@ impl Cam {
     }
 }
 
+/// Convert a [`OutputFormat::Yuv422`] image of the given size to RGB565 pixels, which can be written
+/// to a [`FramebufferFormat::Rgb565`] framebuffer.
+///
+/// The source holds pixel pairs sharing the same chroma, in the order (Y0, U, Y1, V), as produced by the cameras.
+/// The conversion uses the BT.601 coefficients (with limited range luma and chroma).
+///
+/// # Errors
+///
+/// This function will return an error if `src` is shorter than `width * height * 2` bytes, or if `dst` is shorter than `width * height` pixels.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru::services::cam;
+///
+/// // A black and a white pixel.
+/// let yuv = [16, 128, 235, 128];
+/// let mut rgb = [0; 2];
+///
+/// cam::yuv422_to_rgb565(&yuv, 2, 1, &mut rgb)?;
+/// assert_eq!(rgb, [0x0000, 0xFFFF]);
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn yuv422_to_rgb565(src: &[u8], width: u16, height: u16, dst: &mut [u16]) -> crate::Result<()> {
+    let pixels = usize::from(width) * usize::from(height);
+    check_yuv422_buffers(src, pixels, dst.len(), pixels)?;
+
+    for (i, pixel) in dst[..pixels].iter_mut().enumerate() {
+        let [r, g, b] = yuv422_pixel(src, i);
+
+        *pixel = ((u16::from(r) >> 3) << 11) | ((u16::from(g) >> 2) << 5) | (u16::from(b) >> 3);
+    }
+
+    Ok(())
+}
+
+/// Convert a [`OutputFormat::Yuv422`] image of the given size to RGB888 pixels, stored as 3 bytes in (R, G, B) order.
+///
+/// See [`yuv422_to_rgb565()`] for details about the conversion. Note that [`FramebufferFormat::Bgr8`] framebuffers
+/// store the color components in the opposite order.
+///
+/// # Errors
+///
+/// This function will return an error if `src` is shorter than `width * height * 2` bytes, or if `dst` is shorter than `width * height * 3` bytes.
+pub fn yuv422_to_rgb888(src: &[u8], width: u16, height: u16, dst: &mut [u8]) -> crate::Result<()> {
+    let pixels = usize::from(width) * usize::from(height);
+    check_yuv422_buffers(src, pixels, dst.len(), pixels * 3)?;
+
+    for (i, pixel) in dst[..pixels * 3].chunks_exact_mut(3).enumerate() {
+        pixel.copy_from_slice(&yuv422_pixel(src, i));
+    }
+
+    Ok(())
+}
+
+fn check_yuv422_buffers(
+    src: &[u8],
+    pixels: usize,
+    dst_len: usize,
+    dst_wanted: usize,
+) -> crate::Result<()> {
+    if src.len() < pixels * 2 {
+        return Err(Error::BufferTooShort {
+            provided: src.len(),
+            wanted: pixels * 2,
+        });
+    }
+
+    if dst_len < dst_wanted {
+        return Err(Error::BufferTooShort {
+            provided: dst_len,
+            wanted: dst_wanted,
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns the RGB components of the pixel at `index` in a YUV422 image.
+fn yuv422_pixel(src: &[u8], index: usize) -> [u8; 3] {
+    let pair = index / 2 * 4;
+
+    let y = i32::from(src[index * 2]) - 16;
+    let u = i32::from(src[pair + 1]) - 128;
+    // The last pixel of an image with an odd amount of pixels has no V component.
+    let v = i32::from(src.get(pair + 3).copied().unwrap_or(128)) - 128;
+
+    // BT.601 coefficients, in 8-bit fixed point.
+    let r = (298 * y + 409 * v + 128) >> 8;
+    let g = (298 * y - 100 * u - 208 * v + 128) >> 8;
+    let b = (298 * y + 516 * u + 128) >> 8;
+
+    [r, g, b].map(|c| c.clamp(0, 255) as u8)
+}
+
 impl TryFrom<FramebufferFormat> for OutputFormat {
     type Error = ();
 
@@ -1240,6 +2106,42 @@ impl TryFrom<OutputFormat> for FramebufferFormat {
     }
 }
 
+impl ViewSize {
+    /// Returns `true` if the camera can capture images of this size at the given frame rate.
+    ///
+    /// # Notes
+    ///
+    /// View sizes taller than 240 pixels ([`ViewSize::Vga`], [`ViewSize::Cif`] and [`ViewSize::DSX4`]) need the full
+    /// VGA readout of the camera sensor, which only runs at up to 15 FPS. Using them with faster frame rates results in corrupted frames.
+    /// The supported combinations are:
+    ///
+    /// | View size                                         | Supported frame rates                      |
+    /// |---------------------------------------------------|--------------------------------------------|
+    /// | `Vga`, `Cif`, `DSX4`                              | Up to 15 FPS (`Fps15`, `Fps15To*`, `Fps10`, `Fps8_5`, `Fps5`) |
+    /// | `TopLCD`, `BottomLCD`, `QQVga`, `QCif`, `DS`      | All                                        |
+    pub fn supports_frame_rate(&self, frame_rate: FrameRate) -> bool {
+        match self {
+            Self::Vga | Self::Cif | Self::DSX4 => frame_rate.max_fps() <= 15,
+            _ => true,
+        }
+    }
+}
+
+impl FrameRate {
+    /// Returns the maximum number of frames per second captured with this frame rate.
+    pub fn max_fps(&self) -> u8 {
+        match self {
+            Self::Fps15 | Self::Fps15To5 | Self::Fps15To2 | Self::Fps15To10 => 15,
+            Self::Fps10 => 10,
+            // Rounded up.
+            Self::Fps8_5 => 9,
+            Self::Fps5 => 5,
+            Self::Fps20 | Self::Fps20To5 | Self::Fps20To10 => 20,
+            Self::Fps30 | Self::Fps30To5 | Self::Fps30To10 => 30,
+        }
+    }
+}
+
 impl From<ViewSize> for (i16, i16) {
     fn from(value: ViewSize) -> Self {
         match value {
@@ -1255,6 +2157,9 @@ impl From<ViewSize> for (i16, i16) {
     }
 }
 
+from_impl!(Port, ctru_sys::CAMU_Port);
+from_impl!(Port, ctru_sys::u32_);
+from_impl!(Context, ctru_sys::CAMU_Context);
 from_impl!(FlipMode, ctru_sys::CAMU_Flip);
 from_impl!(ViewSize, ctru_sys::CAMU_Size);
 from_impl!(FrameRate, ctru_sys::CAMU_FrameRate);
@@ -1265,3 +2170,87 @@ from_impl!(Contrast, ctru_sys::CAMU_Contrast);
 from_impl!(LensCorrection, ctru_sys::CAMU_LensCorrection);
 from_impl!(OutputFormat, ctru_sys::CAMU_OutputFormat);
 from_impl!(ShutterSound, ctru_sys::CAMU_ShutterSoundType);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2x2 image with a black, a white and two red pixels.
+    const YUV: [u8; 8] = [16, 128, 235, 128, 81, 90, 81, 240];
+
+    #[test]
+    fn is_trimming() {
+        let mut cam = Cam::new().unwrap();
+        let camera = &mut cam.inner_cam;
+
+        camera
+            .set_trimming(Trimming::new_centered_with_view(ViewSize::DS))
+            .unwrap();
+        assert!(camera.is_trimming());
+
+        camera.set_trimming(Trimming::Off).unwrap();
+        assert!(!camera.is_trimming());
+    }
+
+    #[test]
+    fn image_quality_calibration_roundtrip() {
+        let mut cam = Cam::new().unwrap();
+        let camera = &mut cam.inner_cam;
+
+        let original = camera.image_quality_calibration().unwrap();
+
+        let mut calibration = original;
+        calibration.set_ae_base_target(original.ae_base_target().wrapping_add(1));
+        camera.set_image_quality_calibration(calibration).unwrap();
+
+        let read = camera.image_quality_calibration().unwrap();
+        assert_eq!(read.ae_base_target(), calibration.ae_base_target());
+        assert_eq!(read.awb_x0_left(), original.awb_x0_left());
+
+        camera.set_image_quality_calibration(original).unwrap();
+    }
+
+    #[test]
+    fn camera_ports() {
+        let configuration = Configuration::new();
+
+        assert_eq!(InwardCam { configuration }.port(), Port::Cam1);
+        assert_eq!(OutwardRightCam { configuration }.port(), Port::Cam1);
+        assert_eq!(OutwardLeftCam { configuration }.port(), Port::Cam2);
+        assert_eq!(BothOutwardCam { configuration }.port(), Port::Both);
+        assert_eq!(
+            OutwardLeftCam { configuration }.port_as_raw(),
+            ctru_sys::PORT_CAM2.into()
+        );
+    }
+
+    #[test]
+    fn yuv422_rgb888() {
+        let mut rgb = [0; 12];
+        yuv422_to_rgb888(&YUV, 2, 2, &mut rgb).unwrap();
+
+        let expected = [[0, 0, 0], [255, 255, 255], [255, 0, 0], [255, 0, 0]];
+
+        for (pixel, expected) in rgb.chunks_exact(3).zip(expected) {
+            for (&c, e) in pixel.iter().zip(expected) {
+                assert!(c.abs_diff(e) <= 2, "{pixel:?} != {expected:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn yuv422_rgb565() {
+        let mut rgb = [0; 4];
+        yuv422_to_rgb565(&YUV, 2, 2, &mut rgb).unwrap();
+
+        assert_eq!(rgb, [0x0000, 0xFFFF, 0xF800, 0xF800]);
+
+        assert!(matches!(
+            yuv422_to_rgb565(&YUV[..6], 2, 2, &mut rgb),
+            Err(Error::BufferTooShort {
+                provided: 6,
+                wanted: 8
+            })
+        ));
+    }
+}
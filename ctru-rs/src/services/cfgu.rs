@@ -177,6 +177,112 @@ impl Cfgu {
         Ok(Language::try_from(language as i8).unwrap())
     }
 
+    /// Returns the user's birthday as set in the System Settings, as a (month, day) pair.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the configuration block couldn't be read,
+    /// or if the user hasn't set their birthday.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::cfgu::Cfgu;
+    /// let cfgu = Cfgu::new()?;
+    ///
+    /// let (month, day) = cfgu.birthday()?;
+    ///
+    /// println!("Your birthday is on {day}/{month}");
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "CFGU_GetConfigInfoBlk2")]
+    pub fn birthday(&self) -> crate::Result<(u8, u8)> {
+        // See <https://www.3dbrew.org/wiki/Config_Savegame#0x000A0001>
+        const BIRTHDAY_BLOCK_ID: u32 = 0x000A0001;
+
+        let mut birthday = [0u8; 2];
+
+        ResultCode(unsafe {
+            ctru_sys::CFGU_GetConfigInfoBlk2(
+                birthday.len() as u32,
+                BIRTHDAY_BLOCK_ID,
+                birthday.as_mut_ptr().cast(),
+            )
+        })?;
+
+        let [month, day] = birthday;
+
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return Err(crate::Error::Other(String::from(
+                "the user's birthday is not set",
+            )));
+        }
+
+        Ok((month, day))
+    }
+
+    /// Returns the ISO 3166-1 alpha-2 code (e.g. `b"JP"`) of the country set in the System Settings, as ASCII bytes.
+    ///
+    /// Unlike the [`Region`] of the console, the country can be changed by the user.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the configuration block couldn't be read, or if the country isn't set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::cfgu::Cfgu;
+    /// let cfgu = Cfgu::new()?;
+    ///
+    /// let country = cfgu.country_code()?;
+    ///
+    /// println!("Your country is {}", String::from_utf8_lossy(&country));
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "CFGU_GetCountryCodeString")]
+    pub fn country_code(&self) -> crate::Result<[u8; 2]> {
+        // See <https://www.3dbrew.org/wiki/Config_Savegame#0x000B0000>
+        const COUNTRY_BLOCK_ID: u32 = 0x000B0000;
+
+        let mut country_info = [0u8; 4];
+
+        ResultCode(unsafe {
+            ctru_sys::CFGU_GetConfigInfoBlk2(
+                country_info.len() as u32,
+                COUNTRY_BLOCK_ID,
+                country_info.as_mut_ptr().cast(),
+            )
+        })?;
+
+        // The last byte holds the system's numeric country ID.
+        let country_id = country_info[3];
+
+        if country_id == 0 {
+            return Err(crate::Error::Other(String::from(
+                "the user's country is not set",
+            )));
+        }
+
+        let mut code: u16 = 0;
+
+        ResultCode(unsafe { ctru_sys::CFGU_GetCountryCodeString(country_id.into(), &mut code) })?;
+
+        Ok(code.to_le_bytes())
+    }
+
     /// Check if NFC is supported by the console.
     ///
     /// # Example
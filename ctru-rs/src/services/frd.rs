@@ -0,0 +1,181 @@
+//! Friends service.
+//!
+//! This service gives read-only access to information about the console's friend account,
+//! such as the user's screen name, presence and friend list.
+//!
+//! TODO: [`ctru-rs`](crate) doesn't support managing friends or updating the user's presence yet.
+#![doc(alias = "friends")]
+
+use std::sync::Mutex;
+
+use crate::error::ResultCode;
+use crate::services::ServiceReference;
+
+pub(crate) static FRD_ACTIVE: Mutex<()> = Mutex::new(());
+
+/// Maximum number of friends that can be registered on a console.
+const FRIEND_LIST_SIZE: usize = ctru_sys::FRIEND_LIST_SIZE as usize;
+
+/// Key identifying a friend.
+#[doc(alias = "FriendKey")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FriendKey {
+    principal_id: u32,
+    local_friend_code: u64,
+}
+
+impl FriendKey {
+    /// Returns the friend's principal ID.
+    pub fn principal_id(&self) -> u32 {
+        self.principal_id
+    }
+
+    /// Returns the friend's local friend code.
+    pub fn local_friend_code(&self) -> u64 {
+        self.local_friend_code
+    }
+}
+
+impl From<ctru_sys::FriendKey> for FriendKey {
+    fn from(value: ctru_sys::FriendKey) -> Self {
+        Self {
+            principal_id: value.principalId,
+            local_friend_code: value.localFriendCode,
+        }
+    }
+}
+
+/// Presence information of the user, as broadcasted to their friends.
+#[doc(alias = "MyPresence")]
+#[derive(Copy, Clone)]
+pub struct Presence(ctru_sys::MyPresence);
+
+impl Presence {
+    /// Returns a reference to the raw presence data.
+    pub fn as_raw(&self) -> &ctru_sys::MyPresence {
+        &self.0
+    }
+}
+
+/// Handle to the Friends service.
+pub struct Frd {
+    _service_handler: ServiceReference,
+}
+
+impl Frd {
+    /// Initialize a new service handle.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the service was unable to be initialized,
+    /// or if a [`Frd`] handle already exists.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::frd::Frd;
+    ///
+    /// let frd = Frd::new()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "frdInit")]
+    pub fn new() -> crate::Result<Self> {
+        let _service_handler = ServiceReference::new(
+            &FRD_ACTIVE,
+            "frd:u",
+            || {
+                // Always use `frd:u`, since `frd:a` isn't available to most applications.
+                ResultCode(unsafe { ctru_sys::frdInit(true) })?;
+
+                Ok(())
+            },
+            || unsafe {
+                ctru_sys::frdExit();
+            },
+        )?;
+
+        Ok(Self { _service_handler })
+    }
+
+    /// Returns the user's current presence information.
+    #[doc(alias = "FRD_GetMyPresence")]
+    pub fn my_presence(&self) -> crate::Result<Presence> {
+        let mut presence = ctru_sys::MyPresence::default();
+
+        ResultCode(unsafe { ctru_sys::FRD_GetMyPresence(&mut presence) })?;
+
+        Ok(Presence(presence))
+    }
+
+    /// Returns the user's screen name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::frd::Frd;
+    /// let frd = Frd::new()?;
+    ///
+    /// println!("Hello, {}!", frd.my_screen_name()?);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "FRD_GetMyScreenName")]
+    pub fn my_screen_name(&self) -> crate::Result<String> {
+        let mut name = [0u16; ctru_sys::FRIEND_SCREEN_NAME_SIZE as usize];
+
+        ResultCode(unsafe { ctru_sys::FRD_GetMyScreenName(name.as_mut_ptr(), name.len()) })?;
+
+        let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+
+        Ok(String::from_utf16_lossy(&name[..len]))
+    }
+
+    /// Returns the list of the user's friends.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::frd::Frd;
+    /// let frd = Frd::new()?;
+    ///
+    /// let friends = frd.friend_key_list()?;
+    ///
+    /// println!("You have {} friends", friends.len());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "FRD_GetFriendKeyList")]
+    pub fn friend_key_list(&self) -> crate::Result<Vec<FriendKey>> {
+        let mut keys = vec![ctru_sys::FriendKey::default(); FRIEND_LIST_SIZE];
+        let mut count = 0;
+
+        ResultCode(unsafe {
+            ctru_sys::FRD_GetFriendKeyList(
+                keys.as_mut_ptr(),
+                &mut count,
+                0,
+                FRIEND_LIST_SIZE as u32,
+            )
+        })?;
+
+        keys.truncate(count as usize);
+
+        Ok(keys.into_iter().map(FriendKey::from).collect())
+    }
+}
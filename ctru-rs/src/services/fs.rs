@@ -1,8 +1,21 @@
 //! FileSystem service.
 //!
-//! Currently, this module contains only datatypes to easily operate with unsafe [`ctru_sys`] code regarding the file-system functionality.
+//! This module contains datatypes to easily operate with unsafe [`ctru_sys`] code regarding the file-system functionality,
+//! as well as some helpers built on top of [`std::fs`].
+//!
+//! Archives accessible via [`std::fs`] are selected with the path's prefix, such as `sdmc:/` for the SD card
+//! or `romfs:/` for the application's [`RomFS`](crate::services::romfs).
+//!
+//! # Notes
+//!
+//! Every read or write on a [`File`](std::fs::File) results in a separate request to the FS service, which has a high fixed cost.
+//! Writing many small chunks of data should be batched with a [`BufWriter`](std::io::BufWriter), or replaced by a single call to [`write()`].
 #![doc(alias = "filesystem")]
 
+use std::fs::{self, DirEntry, File, ReadDir};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
 use bitflags::bitflags;
 
 bitflags! {
@@ -111,6 +124,393 @@ pub enum ArchiveID {
     DemoSavedata = ctru_sys::ARCHIVE_DEMO_SAVEDATA,
 }
 
+/// Recursive iterator over the contents of a directory.
+///
+/// Entries are yielded in depth-first order, with every directory yielded before its contents.
+/// The root directory itself is not yielded.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru::services::fs::WalkDir;
+///
+/// for entry in WalkDir::new("sdmc:/3ds")? {
+///     println!("{}", entry?.path().display());
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub struct WalkDir {
+    stack: Vec<ReadDir>,
+}
+
+impl WalkDir {
+    /// Start walking the directory at `root`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `root` cannot be read as a directory.
+    pub fn new(root: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self {
+            stack: vec![fs::read_dir(root)?],
+        })
+    }
+}
+
+impl Iterator for WalkDir {
+    type Item = io::Result<DirEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let dir = self.stack.last_mut()?;
+
+            match dir.next() {
+                Some(Ok(entry)) => {
+                    let file_type = match entry.file_type() {
+                        Ok(file_type) => file_type,
+                        Err(e) => return Some(Err(e)),
+                    };
+
+                    if file_type.is_dir() {
+                        match fs::read_dir(entry.path()) {
+                            Ok(read_dir) => self.stack.push(read_dir),
+                            Err(e) => return Some(Err(e)),
+                        }
+                    }
+
+                    return Some(Ok(entry));
+                }
+                Some(Err(e)) => return Some(Err(e)),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Recursively copy the contents of the directory at `from` into the directory at `to`, returning the total number of bytes copied.
+///
+/// The destination directory (and its parents) are created if they don't exist, and existing files are overwritten.
+/// Since archives are selected by the path's prefix, this can be used to copy directories between archives,
+/// e.g. to install data from the RomFS to the SD card.
+///
+/// `progress` is called after each file is copied, with the number of bytes copied so far.
+///
+/// # Errors
+///
+/// This function will return an error if any of the directories cannot be read or created, or if any file fails to be copied.
+/// Files copied before the error occurred are left at the destination.
+///
+/// An error of kind [`io::ErrorKind::InvalidInput`] is returned before copying any file if `to` is `from` itself or is inside it,
+/// since the copy would otherwise include itself until the archive is full.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// # let _romfs = ctru::services::romfs::RomFS::new()?;
+/// #
+/// use ctru::services::fs;
+///
+/// let copied = fs::copy_dir_all("romfs:/", "sdmc:/my-app/data", |bytes| {
+///     println!("Copied {bytes} bytes...");
+/// })?;
+///
+/// println!("Installation complete ({copied} bytes)");
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn copy_dir_all(
+    from: impl AsRef<Path>,
+    to: impl AsRef<Path>,
+    mut progress: impl FnMut(u64),
+) -> io::Result<u64> {
+    let from = from.as_ref();
+    let to = to.as_ref();
+
+    fs::create_dir_all(to)?;
+
+    if fs::canonicalize(to)?.starts_with(fs::canonicalize(from)?) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "cannot copy a directory into itself",
+        ));
+    }
+
+    let mut copied = 0;
+
+    for entry in WalkDir::new(from)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        let relative = path.strip_prefix(from).map_err(io::Error::other)?;
+        let destination = to.join(relative);
+
+        if entry.file_type()?.is_dir() {
+            fs::create_dir_all(destination)?;
+        } else {
+            copied += fs::copy(&path, destination)?;
+            progress(copied);
+        }
+    }
+
+    Ok(copied)
+}
+
 from_impl!(MediaType, ctru_sys::FS_MediaType);
 from_impl!(PathType, ctru_sys::FS_PathType);
 from_impl!(ArchiveID, ctru_sys::FS_ArchiveID);
+
+/// Write `contents` to the file at `path` with a single write request, creating the file if it doesn't exist
+/// and replacing its previous contents otherwise.
+///
+/// The file's size is set before writing, so the archive doesn't need to grow it while the data is written.
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be created or written to.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru::services::fs;
+///
+/// let save_data = vec![0u8; 0x10000];
+///
+/// fs::write("sdmc:/my-app/save.bin", &save_data)?;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let contents = contents.as_ref();
+
+    let mut file = File::create(path)?;
+
+    file.set_len(contents.len() as u64)?;
+    file.write_all(contents)?;
+
+    file.flush()
+}
+
+/// Write `contents` to the file at `path` atomically, so that the file never holds partially written data.
+///
+/// The data is first written to a temporary file next to `path` (with `.tmp` appended to its name), which is then
+/// renamed to `path`. If writing fails, the temporary file is removed and the file at `path` is left untouched.
+///
+/// # Notes
+///
+/// Renaming can't replace an existing file on the 3DS, so the previous file at `path` is removed right before the rename.
+/// If power is lost in between, `path` will be missing but its temporary file will hold the complete new contents:
+/// applications can recover from this by checking for the temporary file when `path` doesn't exist.
+///
+/// # Errors
+///
+/// This function will return an error if the temporary file cannot be written, or if it cannot replace the file at `path`.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru::services::fs;
+///
+/// let save_data = vec![0u8; 0x10000];
+///
+/// // Even if the console is turned off while saving, the previous save is never half-overwritten.
+/// fs::write_atomic("sdmc:/my-app/save.bin", &save_data)?;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[doc(alias = "FSUSER_RenameFile")]
+pub fn write_atomic(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let path = path.as_ref();
+
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+
+    let written = (|| {
+        let contents = contents.as_ref();
+
+        let mut file = File::create(&temp)?;
+
+        file.set_len(contents.len() as u64)?;
+        file.write_all(contents)?;
+
+        // Make sure the data reaches the storage before the previous file is removed.
+        file.sync_all()
+    })();
+
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+
+    match fs::remove_file(path) {
+        Ok(()) => (),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+        Err(e) => {
+            let _ = fs::remove_file(&temp);
+            return Err(e);
+        }
+    }
+
+    // The temporary file now holds the only copy of the data, so it must not be removed even if renaming fails.
+    fs::rename(&temp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copy_nested_dir() {
+        let root = Path::new("sdmc:/ctru-rs-fs-test");
+        let source = root.join("source");
+        let destination = root.join("destination");
+
+        let _ = fs::remove_dir_all(root);
+
+        fs::create_dir_all(source.join("nested/deeper")).unwrap();
+        fs::write(source.join("a.txt"), b"hello").unwrap();
+        fs::write(source.join("nested/b.txt"), b"world!").unwrap();
+        fs::write(source.join("nested/deeper/c.txt"), b"").unwrap();
+
+        let mut calls = 0;
+        let copied = copy_dir_all(&source, &destination, |_| calls += 1).unwrap();
+
+        assert_eq!(copied, 11);
+        assert_eq!(calls, 3);
+        assert_eq!(
+            fs::read(destination.join("nested/b.txt")).unwrap(),
+            b"world!"
+        );
+
+        let mut walked: Vec<_> = WalkDir::new(&destination)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        walked.sort();
+
+        assert_eq!(
+            walked,
+            [
+                destination.join("a.txt"),
+                destination.join("nested"),
+                destination.join("nested/b.txt"),
+                destination.join("nested/deeper"),
+                destination.join("nested/deeper/c.txt"),
+            ]
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn copy_dir_into_itself() {
+        let source = Path::new("sdmc:/ctru-rs-fs-test-self");
+
+        let _ = fs::remove_dir_all(source);
+        fs::create_dir_all(source).unwrap();
+        fs::write(source.join("a.txt"), b"hello").unwrap();
+
+        let error = copy_dir_all(source, source.join("copy"), |_| ()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(!source.join("copy/a.txt").exists());
+
+        fs::remove_dir_all(source).unwrap();
+    }
+
+    #[test]
+    fn write_1mb() {
+        use std::io::BufWriter;
+
+        const SIZE: usize = 1024 * 1024;
+
+        let root = Path::new("sdmc:/ctru-rs-fs-write-test");
+        let _ = fs::remove_dir_all(root);
+        fs::create_dir_all(root).unwrap();
+
+        let contents: Vec<u8> = (0..SIZE).map(|i| i as u8).collect();
+
+        write(root.join("one-shot.bin"), &contents).unwrap();
+        assert_eq!(fs::read(root.join("one-shot.bin")).unwrap(), contents);
+
+        // Records the size of every write reaching the file.
+        struct RecordingWriter {
+            file: File,
+            writes: Vec<usize>,
+        }
+
+        impl Write for RecordingWriter {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                let written = self.file.write(buf)?;
+                self.writes.push(written);
+
+                Ok(written)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                self.file.flush()
+            }
+        }
+
+        const BATCH: usize = 64 * 1024;
+
+        let file = RecordingWriter {
+            file: File::create(root.join("buffered.bin")).unwrap(),
+            writes: Vec::new(),
+        };
+
+        // Byte-by-byte writes only reach the FS service once the buffer is full.
+        let mut writer = BufWriter::with_capacity(BATCH, file);
+        for byte in &contents {
+            writer.write_all(std::slice::from_ref(byte)).unwrap();
+        }
+
+        let mut file = writer.into_inner().unwrap();
+        file.flush().unwrap();
+
+        assert_eq!(file.writes, [BATCH; SIZE / BATCH]);
+        assert_eq!(fs::read(root.join("buffered.bin")).unwrap(), contents);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn write_atomic_replaces() {
+        let root = Path::new("sdmc:/ctru-rs-fs-atomic-test");
+        let _ = fs::remove_dir_all(root);
+        fs::create_dir_all(root).unwrap();
+
+        let path = root.join("save.bin");
+
+        write_atomic(&path, b"first").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"first");
+
+        write_atomic(&path, b"second save").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"second save");
+
+        assert!(!root.join("save.bin.tmp").exists());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,1220 @@
+//! Graphics service.
+//!
+//! The GFX service controls (in a somewhat high-level way) the console's LCD screens.
+//! The screens are subordinate to the GFX service handle and can be used by only one borrower at a time.
+#![doc(alias = "graphics")]
+
+pub mod vram;
+
+use std::cell::{Cell, Ref, RefCell, RefMut};
+use std::marker::PhantomData;
+use std::sync::Mutex;
+
+use crate::error::{Result, ResultCode};
+use crate::sealed::Sealed;
+use crate::services::gspgpu::{self, FramebufferFormat};
+use crate::services::ServiceReference;
+
+/// Trait to handle common functionality for all screens.
+///
+/// This trait is implemented by the screen structs for working with frame buffers and
+/// drawing to the screens. Graphics-related code can be made generic over this
+/// trait to work with any of the given screens.
+#[doc(alias = "gfxScreen_t")]
+pub trait Screen: Sealed {
+    /// Returns the `libctru` value for the Screen kind.
+    fn as_raw(&self) -> ctru_sys::gfxScreen_t;
+
+    /// Returns the Screen side (left or right).
+    fn side(&self) -> Side;
+
+    /// Returns a [`RawFrameBuffer`] for the screen (if the framebuffer was allocated on the HEAP).
+    ///
+    /// # Notes
+    ///
+    /// The pointer of the framebuffer returned by this function can change after each call
+    /// to this function if double buffering is enabled, so it's suggested to NOT save it for later use.
+    ///
+    /// # Panics
+    ///
+    /// If the [`Gfx`] service was initialised via [`Gfx::with_formats_vram()`] this function will crash the program with an ARM exception.
+    #[doc(alias = "gfxGetFramebuffer")]
+    fn raw_framebuffer(&mut self) -> RawFrameBuffer {
+        let format = self.framebuffer_format();
+        let mut width: u16 = 0;
+        let mut height: u16 = 0;
+        let ptr = unsafe {
+            ctru_sys::gfxGetFramebuffer(self.as_raw(), self.side().into(), &mut width, &mut height)
+        };
+        RawFrameBuffer {
+            ptr,
+            width: width.into(),
+            height: height.into(),
+            format,
+            screen: PhantomData,
+        }
+    }
+
+    /// Returns a safe view over the screen's current framebuffer.
+    ///
+    /// Since the view mutably borrows the screen, only one [`Framebuffer`] can exist for the same screen at a time.
+    ///
+    /// # Notes
+    ///
+    /// As with [`Screen::raw_framebuffer()`], the returned framebuffer is only valid until the next swap if double buffering is enabled.
+    ///
+    /// # Panics
+    ///
+    /// If the [`Gfx`] service was initialised via [`Gfx::with_formats_vram()`] this function will crash the program with an ARM exception.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::gfx::{Gfx, Screen};
+    /// let gfx = Gfx::new()?;
+    /// let mut bottom_screen = gfx.bottom_screen.borrow_mut();
+    ///
+    /// let mut framebuffer = bottom_screen.framebuffer();
+    ///
+    /// // Draw a red line in the middle of the screen.
+    /// for x in 0..framebuffer.width() {
+    ///     framebuffer.set_pixel(x, framebuffer.height() / 2, [255, 0, 0]);
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "gfxGetFramebuffer")]
+    fn framebuffer(&mut self) -> Framebuffer<'_> {
+        let raw = self.raw_framebuffer();
+
+        Framebuffer {
+            // SAFETY: the framebuffer is allocated for the given size and format by `libctru`,
+            // and the screen is mutably borrowed for the lifetime of the slice.
+            data: unsafe { std::slice::from_raw_parts_mut(raw.ptr, raw.len()) },
+            raw_width: raw.width,
+            raw_height: raw.height,
+            format: raw.format,
+        }
+    }
+
+    /// Gets the framebuffer format.
+    #[doc(alias = "gfxGetScreenFormat")]
+    fn framebuffer_format(&self) -> FramebufferFormat {
+        unsafe { ctru_sys::gfxGetScreenFormat(self.as_raw()) }.into()
+    }
+
+    /// Change the framebuffer format.
+    ///
+    /// [`Swap::swap_buffers`] must be called after this method for the configuration
+    /// change to take effect.
+    #[doc(alias = "gfxSetScreenFormat")]
+    fn set_framebuffer_format(&mut self, fmt: FramebufferFormat) {
+        unsafe { ctru_sys::gfxSetScreenFormat(self.as_raw(), fmt.into()) }
+    }
+}
+
+/// The top LCD screen.
+///
+/// Mutable access to this struct is required to write to the top screen's frame buffer.
+///
+/// To enable 3D mode, it can be converted into a [`TopScreen3D`].
+pub struct TopScreen {
+    left: TopScreenLeft,
+    right: TopScreenRight,
+}
+
+/// The top LCD screen set in stereoscopic 3D mode.
+///
+/// A helper container for both sides of the top screen. Once the [`TopScreen`] is
+/// converted into this, 3D mode will be enabled until this struct is dropped.
+pub struct TopScreen3D<'screen> {
+    screen: &'screen RefCell<TopScreen>,
+}
+
+/// Trait for screens that can have its frame buffers swapped, when double buffering is enabled.
+///
+/// This trait applies to all [`Screen`]s that have swappable frame buffers.
+pub trait Swap: Sealed {
+    /// Swaps the video buffers.
+    ///
+    /// Even if double buffering is disabled, "swapping" the buffers has the side effect
+    /// of committing any configuration changes to the buffers (e.g. [`TopScreen::set_wide_mode()`],
+    /// [`Screen::set_framebuffer_format()`], [`Swap::set_double_buffering()`]), so it should still be used.
+    ///
+    /// This should be called once per frame at most.
+    #[doc(alias = "gfxScreenSwapBuffers")]
+    fn swap_buffers(&mut self);
+
+    /// Set whether to use double buffering.
+    ///
+    /// # Notes
+    ///
+    /// Double buffering is enabled by default.
+    /// [`Swap::swap_buffers`] must be called after this function for the configuration
+    /// change to take effect.
+    #[doc(alias = "gfxSetDoubleBuffering")]
+    fn set_double_buffering(&mut self, enabled: bool);
+}
+
+impl Swap for TopScreen3D<'_> {
+    fn swap_buffers(&mut self) {
+        unsafe {
+            ctru_sys::gfxScreenSwapBuffers(ctru_sys::GFX_TOP, true);
+        }
+    }
+
+    fn set_double_buffering(&mut self, enabled: bool) {
+        unsafe { ctru_sys::gfxSetDoubleBuffering(ctru_sys::GFX_TOP, enabled) }
+    }
+}
+
+impl Swap for TopScreen {
+    fn swap_buffers(&mut self) {
+        unsafe {
+            ctru_sys::gfxScreenSwapBuffers(ctru_sys::GFX_TOP, false);
+        }
+    }
+
+    fn set_double_buffering(&mut self, enabled: bool) {
+        unsafe { ctru_sys::gfxSetDoubleBuffering(ctru_sys::GFX_TOP, enabled) }
+    }
+}
+
+impl Swap for BottomScreen {
+    fn swap_buffers(&mut self) {
+        unsafe {
+            ctru_sys::gfxScreenSwapBuffers(ctru_sys::GFX_BOTTOM, false);
+        }
+    }
+
+    fn set_double_buffering(&mut self, enabled: bool) {
+        unsafe { ctru_sys::gfxSetDoubleBuffering(ctru_sys::GFX_BOTTOM, enabled) }
+    }
+}
+
+/// A screen with buffers that can be flushed.
+///
+/// This trait applies to any [`Screen`] that has data written to its frame buffer.
+pub trait Flush: Sealed {
+    /// Flushes the video buffer(s) for this screen.
+    ///
+    /// Note that you must still call [`Swap::swap_buffers`] after this method for the buffer contents to be displayed.
+    #[doc(alias = "gfxFlushBuffers")]
+    fn flush_buffers(&mut self);
+}
+
+impl<S: Screen> Flush for S {
+    fn flush_buffers(&mut self) {
+        let framebuffer = self.raw_framebuffer();
+
+        // Flush the data array. `self.raw_framebuffer` should get the correct parameters for all kinds of screens
+        let _ = unsafe {
+            ctru_sys::GSPGPU_FlushDataCache(framebuffer.ptr.cast(), framebuffer.len() as u32)
+        };
+    }
+}
+
+impl Flush for TopScreen3D<'_> {
+    /// Unlike most other implementations of [`Flush`], this flushes the buffers for both
+    /// the left and right sides of the top screen.
+    fn flush_buffers(&mut self) {
+        let (mut left, mut right) = self.split_mut();
+        left.flush_buffers();
+        right.flush_buffers();
+    }
+}
+
+/// The left side of the top screen, when using 3D mode.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct TopScreenLeft;
+
+/// The right side of the top screen, when using 3D mode.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct TopScreenRight;
+
+/// The bottom LCD screen.
+///
+/// Mutable access to this struct is required to write to the bottom screen's frame buffer.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct BottomScreen;
+
+/// Representation of a framebuffer for one [`Side`] of the top screen, or the entire bottom screen.
+///
+/// The inner pointer is only valid for one frame if double
+/// buffering is enabled. Data written to `ptr` will be rendered to the screen.
+#[derive(Debug)]
+pub struct RawFrameBuffer<'screen> {
+    /// Pointer to graphics data to be rendered.
+    pub ptr: *mut u8,
+    /// The width of the framebuffer in pixels.
+    pub width: usize,
+    /// The height of the framebuffer in pixels.
+    pub height: usize,
+    /// The format of the framebuffer's pixels.
+    pub format: FramebufferFormat,
+    /// Keep a mutable reference to the Screen for which this framebuffer is tied.
+    screen: PhantomData<&'screen mut dyn Screen>,
+}
+
+impl RawFrameBuffer<'_> {
+    /// Returns the size of the framebuffer in bytes, according to its [format](RawFrameBuffer::format).
+    pub fn len(&self) -> usize {
+        self.width * self.height * self.format.pixel_depth_bytes()
+    }
+
+    /// Returns `true` if the framebuffer has no pixels.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the raw (rotated) data of the framebuffer as a mutable slice.
+    ///
+    /// Have a look at [`Framebuffer`] for the layout of the data, or use [`Screen::framebuffer()`] to draw in screen coordinates.
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        // SAFETY: the framebuffer is allocated for the given size and format by `libctru`,
+        // and the screen is mutably borrowed for the lifetime of `self`.
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len()) }
+    }
+}
+
+/// Safe view over the framebuffer of a [`Screen`], returned by [`Screen::framebuffer()`].
+///
+/// The framebuffer is handled in screen coordinates: (0, 0) is the top left corner of the screen as seen by the user,
+/// and [`Framebuffer::width()`] is the length of the longer side (400 pixels for the top screen, 320 for the bottom one).
+///
+/// # Notes
+///
+/// The 3DS' screens are portrait LCD panels rotated by 90 degrees, so the data returned by [`Framebuffer::as_slice()`] is stored
+/// column by column, starting from the bottom left corner of the screen. [`Framebuffer::set_pixel()`] takes care of the rotation.
+pub struct Framebuffer<'screen> {
+    data: &'screen mut [u8],
+    raw_width: usize,
+    raw_height: usize,
+    format: FramebufferFormat,
+}
+
+impl Framebuffer<'_> {
+    /// Returns the width of the screen, in pixels.
+    pub fn width(&self) -> usize {
+        self.raw_height
+    }
+
+    /// Returns the height of the screen, in pixels.
+    pub fn height(&self) -> usize {
+        self.raw_width
+    }
+
+    /// Returns the format of the framebuffer's pixels.
+    pub fn format(&self) -> FramebufferFormat {
+        self.format
+    }
+
+    /// Returns the raw (rotated) data of the framebuffer.
+    pub fn as_slice(&self) -> &[u8] {
+        self.data
+    }
+
+    /// Returns the raw (rotated) data of the framebuffer as a mutable slice.
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        self.data
+    }
+
+    /// Set the pixel at (x, y) in screen coordinates to the given RGB color.
+    ///
+    /// The color is converted to the framebuffer's [format](Framebuffer::format), and the alpha channel (if any) is set to fully opaque.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the coordinates are out of the screen's bounds.
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: [u8; 3]) {
+        assert!(
+            x < self.width() && y < self.height(),
+            "pixel ({x}, {y}) is out of bounds for a {}x{} screen",
+            self.width(),
+            self.height()
+        );
+
+        let depth = self.format.pixel_depth_bytes();
+        let offset = (x * self.raw_width + (self.raw_width - 1 - y)) * depth;
+
+        let pixel = rgb_to_pixel(self.format, color);
+        self.data[offset..offset + depth].copy_from_slice(&pixel[..depth]);
+    }
+}
+
+/// Side of the [`TopScreen`]'s framebuffer.
+///
+/// The top screen of the 3DS can have two separate sets of framebuffers to support its 3D functionality
+#[doc(alias = "gfx3dSide_t")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Side {
+    /// The left framebuffer. This framebuffer is also the one used when 3D is disabled
+    Left = ctru_sys::GFX_LEFT,
+    /// The right framebuffer
+    Right = ctru_sys::GFX_RIGHT,
+}
+
+/// Handle to the GFX service.
+///
+/// This service is a wrapper around the lower-level [GSPGPU](crate::services::gspgpu) service that
+/// provides helper functions and utilities for software rendering.
+pub struct Gfx {
+    /// Top screen representation.
+    pub top_screen: RefCell<TopScreen>,
+    /// Bottom screen representation.
+    pub bottom_screen: RefCell<BottomScreen>,
+    // Whether `Gfx::frame` is running, to catch nested calls.
+    in_frame: Cell<bool>,
+    _service_handler: ServiceReference,
+}
+
+pub(crate) static GFX_ACTIVE: Mutex<()> = Mutex::new(());
+
+impl Gfx {
+    /// Initialize a new default service handle.
+    ///
+    /// # Notes
+    ///
+    /// The new `Gfx` instance will allocate the needed framebuffers in the CPU-GPU shared memory region (to ensure compatibiltiy with all possible uses of the `Gfx` service).
+    /// As such, it's the same as calling:
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// # use ctru::services::gfx::Gfx;
+    /// # use ctru::services::gspgpu::FramebufferFormat;
+    /// #
+    /// Gfx::with_formats_shared(FramebufferFormat::Bgr8, FramebufferFormat::Bgr8)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Have a look at [`Gfx::with_formats_vram()`] if you aren't interested in manipulating the framebuffers using the CPU.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::gfx::Gfx;
+    ///
+    /// let gfx = Gfx::new()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "gfxInit")]
+    pub fn new() -> Result<Self> {
+        Gfx::with_formats_shared(FramebufferFormat::Bgr8, FramebufferFormat::Bgr8)
+    }
+
+    /// Initialize a new service handle with the chosen framebuffer formats on the HEAP for the top and bottom screens.
+    ///
+    /// Use [`Gfx::new()`] instead of this function to initialize the module with default parameters
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::gfx::Gfx;
+    /// use ctru::services::gspgpu::FramebufferFormat;
+    ///
+    /// // Top screen uses RGBA8, bottom screen uses RGB565.
+    /// // The screen buffers are allocated in the standard HEAP memory, and not in VRAM.
+    /// let gfx = Gfx::with_formats_shared(FramebufferFormat::Rgba8, FramebufferFormat::Rgb565)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "gfxInit")]
+    pub fn with_formats_shared(
+        top_fb_fmt: FramebufferFormat,
+        bottom_fb_fmt: FramebufferFormat,
+    ) -> Result<Self> {
+        Self::with_configuration(top_fb_fmt, bottom_fb_fmt, false)
+    }
+
+    /// Initialize a new service handle with the chosen framebuffer formats on the VRAM for the top and bottom screens.
+    ///
+    /// # Notes
+    ///
+    /// Though unsafe to do so, it's suggested to use VRAM buffers when working exclusively with the GPU,
+    /// since they result in faster performance and less memory waste.
+    /// Other GPU-side buffers can be allocated on the VRAM using [`VramBox`](vram::VramBox).
+    ///
+    /// # Safety
+    ///
+    /// By initializing the [`Gfx`] service as such, all functionality that relies on CPU manipulation of the framebuffers will
+    /// be completely unavailable (usually resulting in an ARM panic if wrongly used).
+    ///
+    /// Usage of functionality such as [`Console`](crate::console::Console) and [`Screen::raw_framebuffer()`] will result in ARM exceptions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::{gfx::Gfx, gspgpu::FramebufferFormat};
+    ///
+    /// // Top screen uses RGBA8, bottom screen uses RGB565.
+    /// // The screen buffers are allocated in the in VRAM, so they will NOT be accessible from the CPU.
+    /// let gfx = unsafe { Gfx::with_formats_vram(FramebufferFormat::Rgba8, FramebufferFormat::Rgb565)? };
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "gfxInit")]
+    pub unsafe fn with_formats_vram(
+        top_fb_fmt: FramebufferFormat,
+        bottom_fb_fmt: FramebufferFormat,
+    ) -> Result<Self> {
+        Self::with_configuration(top_fb_fmt, bottom_fb_fmt, true)
+    }
+
+    // Internal function to handle the initialization of `Gfx`.
+    fn with_configuration(
+        top_fb_fmt: FramebufferFormat,
+        bottom_fb_fmt: FramebufferFormat,
+        vram_buffer: bool,
+    ) -> Result<Self> {
+        let handler = ServiceReference::new(
+            &GFX_ACTIVE,
+            "gsp::Gpu",
+            || unsafe {
+                ctru_sys::gfxInit(top_fb_fmt.into(), bottom_fb_fmt.into(), vram_buffer);
+
+                Ok(())
+            },
+            || unsafe { ctru_sys::gfxExit() },
+        )?;
+
+        Ok(Self {
+            top_screen: RefCell::new(TopScreen::new()),
+            bottom_screen: RefCell::new(BottomScreen),
+            in_frame: Cell::new(false),
+            _service_handler: handler,
+        })
+    }
+
+    /// Waits for the vertical blank event.
+    ///
+    /// Use this to synchronize your application with the refresh rate of the LCD screens
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::apt::Apt;
+    /// use ctru::services::gfx::Gfx;
+    /// let apt = Apt::new()?;
+    /// let gfx = Gfx::new()?;
+    ///
+    /// // Simple main loop.
+    /// while apt.main_loop() {
+    ///     // Main program logic
+    ///
+    ///     // Wait for the screens to refresh.
+    ///     // This blocks the current thread to make it run at 60Hz.
+    ///     gfx.wait_for_vblank();
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn wait_for_vblank(&self) {
+        gspgpu::wait_for_event(gspgpu::Event::VBlank0, true);
+    }
+
+    /// Flushes the current framebuffers of all screens.
+    ///
+    /// This is the same as calling [`Flush::flush_buffers()`] on each screen, without having to borrow them.
+    #[doc(alias = "gfxFlushBuffers")]
+    pub fn flush_buffers(&self) {
+        unsafe { ctru_sys::gfxFlushBuffers() }
+    }
+
+    /// Swaps the framebuffers of all screens, presenting the frame drawn since the previous swap.
+    ///
+    /// This is the same as calling [`Swap::swap_buffers()`] on each screen (swapping both sides of the top screen if 3D mode is enabled),
+    /// without having to borrow them.
+    ///
+    /// # Notes
+    ///
+    /// With double buffering enabled, each frame should follow this order:
+    ///
+    /// 1. Draw to the framebuffers returned by [`Screen::framebuffer()`] (the back buffers, which aren't being displayed).
+    /// 2. Flush them with [`Gfx::flush_buffers()`], so that the GPU sees the data written by the CPU.
+    /// 3. Swap them with [`Gfx::swap_buffers()`]. The new frame is presented at the next screen refresh.
+    /// 4. Wait for the screen refresh with [`Gfx::wait_for_vblank()`], so that the next frame isn't drawn over the buffer still being displayed.
+    ///
+    /// The framebuffers (and thus the pointers returned by [`Screen::raw_framebuffer()`]) change after each swap.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::apt::Apt;
+    /// use ctru::services::gfx::{Gfx, Screen};
+    /// let apt = Apt::new()?;
+    /// let gfx = Gfx::new()?;
+    ///
+    /// while apt.main_loop() {
+    ///     {
+    ///         let mut bottom_screen = gfx.bottom_screen.borrow_mut();
+    ///         bottom_screen.framebuffer().as_mut_slice().fill(0xFF);
+    ///     }
+    ///
+    ///     gfx.flush_buffers();
+    ///     gfx.swap_buffers();
+    ///     gfx.wait_for_vblank();
+    /// #   break;
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "gfxSwapBuffers")]
+    pub fn swap_buffers(&self) {
+        unsafe { ctru_sys::gfxSwapBuffers() }
+    }
+
+    /// Draw a single frame with `f`, then present it.
+    ///
+    /// The closure gets a [`FrameContext`] to draw on the screens' framebuffers. Once it returns, the framebuffers are
+    /// flushed and swapped, and the current thread waits for the screen refresh (in the order described in [`Gfx::swap_buffers()`]),
+    /// so that each call draws exactly one frame.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if it is called from within the closure of another [`Gfx::frame()`] call,
+    /// since the frame being drawn would be presented half-done.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::apt::Apt;
+    /// use ctru::services::gfx::Gfx;
+    /// let apt = Apt::new()?;
+    /// let gfx = Gfx::new()?;
+    ///
+    /// while apt.main_loop() {
+    ///     gfx.frame(|frame| {
+    ///         frame.bottom_screen().as_mut_slice().fill(0xFF);
+    ///     });
+    /// #   break;
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn frame(&self, f: impl FnOnce(&mut FrameContext<'_>)) {
+        assert!(
+            !self.in_frame.replace(true),
+            "Gfx::frame() can't be called while another frame is being drawn"
+        );
+
+        // Reset the flag even if the closure panics.
+        let _guard = FrameGuard(&self.in_frame);
+
+        let mut context = FrameContext {
+            gfx: self,
+            top_screen: None,
+            bottom_screen: None,
+        };
+
+        f(&mut context);
+
+        // The screens must be released before presenting the frame.
+        drop(context);
+
+        self.flush_buffers();
+        self.swap_buffers();
+        self.wait_for_vblank();
+    }
+}
+
+/// Access to the screens' framebuffers while drawing a frame with [`Gfx::frame()`].
+///
+/// Each screen is borrowed the first time its framebuffer is requested, until the end of the frame.
+///
+/// # Panics
+///
+/// Requesting the framebuffer of a screen which is already borrowed elsewhere (e.g. by a [`Console`](crate::console::Console))
+/// panics, since the frame would be drawn over.
+pub struct FrameContext<'gfx> {
+    gfx: &'gfx Gfx,
+    top_screen: Option<RefMut<'gfx, TopScreen>>,
+    bottom_screen: Option<RefMut<'gfx, BottomScreen>>,
+}
+
+struct FrameGuard<'gfx>(&'gfx Cell<bool>);
+
+impl FrameContext<'_> {
+    /// Returns the framebuffer of the top screen (its left side, when stereoscopic 3D is enabled).
+    pub fn top_screen(&mut self) -> Framebuffer<'_> {
+        self.top().framebuffer()
+    }
+
+    /// Returns the framebuffer of the right side of the top screen, which is only shown when stereoscopic 3D is enabled.
+    pub fn top_screen_right(&mut self) -> Framebuffer<'_> {
+        self.top().split_mut().1.framebuffer()
+    }
+
+    /// Returns the framebuffer of the bottom screen.
+    pub fn bottom_screen(&mut self) -> Framebuffer<'_> {
+        let gfx = self.gfx;
+
+        self.bottom_screen
+            .get_or_insert_with(|| {
+                gfx.bottom_screen
+                    .try_borrow_mut()
+                    .expect("the bottom screen is already borrowed, so it can't be drawn on")
+            })
+            .framebuffer()
+    }
+
+    fn top(&mut self) -> &mut TopScreen {
+        let gfx = self.gfx;
+
+        self.top_screen.get_or_insert_with(|| {
+            gfx.top_screen
+                .try_borrow_mut()
+                .expect("the top screen is already borrowed, so it can't be drawn on")
+        })
+    }
+}
+
+impl Drop for FrameGuard<'_> {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
+
+/// LCD screen whose backlight can be controlled via [`Backlight`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LcdScreen {
+    /// The top LCD screen.
+    Top,
+    /// The bottom LCD screen.
+    Bottom,
+}
+
+impl LcdScreen {
+    fn as_raw(self) -> u32 {
+        match self {
+            Self::Top => ctru_sys::GSPLCD_SCREEN_TOP,
+            Self::Bottom => ctru_sys::GSPLCD_SCREEN_BOTTOM,
+        }
+    }
+}
+
+/// Handle to the `gsp::Lcd` service, used to control the brightness of the screens' backlight.
+///
+/// The brightness of both screens is saved when the handle is created and restored when it's dropped.
+pub struct Backlight {
+    original_brightness: [u32; 2],
+    _service_handler: ServiceReference,
+}
+
+pub(crate) static GSPLCD_ACTIVE: Mutex<()> = Mutex::new(());
+
+impl Backlight {
+    /// Minimum brightness level supported by the hardware.
+    pub const MIN_BRIGHTNESS: u8 = 1;
+
+    /// Maximum brightness level supported by the hardware.
+    pub const MAX_BRIGHTNESS: u8 = 5;
+
+    /// Initialize a new service handle.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the service was unable to be initialized,
+    /// or if a [`Backlight`] handle already exists.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::gfx::Backlight;
+    ///
+    /// let backlight = Backlight::new()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "gspLcdInit")]
+    pub fn new() -> Result<Self> {
+        let handler = ServiceReference::new(
+            &GSPLCD_ACTIVE,
+            "gsp::Lcd",
+            || {
+                ResultCode(unsafe { ctru_sys::gspLcdInit() })?;
+
+                Ok(())
+            },
+            || unsafe { ctru_sys::gspLcdExit() },
+        )?;
+
+        let mut original_brightness = [0; 2];
+
+        for (screen, brightness) in [LcdScreen::Top, LcdScreen::Bottom]
+            .into_iter()
+            .zip(original_brightness.iter_mut())
+        {
+            ResultCode(unsafe { ctru_sys::GSPLCD_GetBrightness(screen.as_raw(), brightness) })?;
+        }
+
+        Ok(Self {
+            original_brightness,
+            _service_handler: handler,
+        })
+    }
+
+    /// Returns the current brightness level of the given screen.
+    #[doc(alias = "GSPLCD_GetBrightness")]
+    pub fn brightness(&self, screen: LcdScreen) -> Result<u8> {
+        let mut brightness = 0;
+
+        ResultCode(unsafe { ctru_sys::GSPLCD_GetBrightness(screen.as_raw(), &mut brightness) })?;
+
+        Ok(brightness as u8)
+    }
+
+    /// Set the brightness level of the given screen.
+    ///
+    /// # Notes
+    ///
+    /// The brightness level must be within [`Backlight::MIN_BRIGHTNESS`] and [`Backlight::MAX_BRIGHTNESS`],
+    /// which correspond to the levels available in the Home Menu.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `level` is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::gfx::{Backlight, LcdScreen};
+    ///
+    /// let mut backlight = Backlight::new()?;
+    ///
+    /// // Dim the bottom screen while it's not in use.
+    /// backlight.set_brightness(LcdScreen::Bottom, Backlight::MIN_BRIGHTNESS)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "GSPLCD_SetBrightness")]
+    pub fn set_brightness(&mut self, screen: LcdScreen, level: u8) -> Result<()> {
+        if !(Self::MIN_BRIGHTNESS..=Self::MAX_BRIGHTNESS).contains(&level) {
+            return Err(crate::Error::Other(format!(
+                "brightness level {level} is out of range ({}-{})",
+                Self::MIN_BRIGHTNESS,
+                Self::MAX_BRIGHTNESS
+            )));
+        }
+
+        ResultCode(unsafe { ctru_sys::GSPLCD_SetBrightness(screen.as_raw(), level.into()) })?;
+
+        Ok(())
+    }
+}
+
+impl Drop for Backlight {
+    fn drop(&mut self) {
+        for (screen, brightness) in [LcdScreen::Top, LcdScreen::Bottom]
+            .into_iter()
+            .zip(self.original_brightness)
+        {
+            unsafe {
+                let _ = ctru_sys::GSPLCD_SetBrightness(screen.as_raw(), brightness);
+            }
+        }
+    }
+}
+
+/// Capture the contents of a screen's framebuffer as 24 bit RGB data (3 bytes per pixel, in R, G, B order).
+///
+/// The framebuffer is de-rotated, so the returned image uses the usual row-major, top-left origin layout expected by image encoders.
+/// The image is as wide as the framebuffer's [`height`](RawFrameBuffer::height) and as tall as its [`width`](RawFrameBuffer::width),
+/// since the 3DS' screens are portrait LCD panels rotated by 90 degrees (e.g. 400x240 pixels for the top screen, 320x240 for the bottom one).
+///
+/// Use [`TopScreenLeft`] and [`TopScreenRight`] (via [`TopScreen3D::split_mut()`]) to capture each side of the top screen separately.
+///
+/// # Notes
+///
+/// The captured framebuffer is the one currently being drawn to, so this function should be called before [`Swap::swap_buffers()`]
+/// if double buffering is enabled. The alpha channel of formats that have one is discarded.
+///
+/// # Panics
+///
+/// If the [`Gfx`] service was initialised via [`Gfx::with_formats_vram()`] this function will crash the program with an ARM exception.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru::services::gfx::{self, Gfx};
+/// let gfx = Gfx::new()?;
+///
+/// let screenshot = gfx::capture_screen(&mut *gfx.bottom_screen.borrow_mut());
+///
+/// assert_eq!(screenshot.len(), 320 * 240 * 3);
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn capture_screen(screen: &mut impl Screen) -> Vec<u8> {
+    let framebuffer = screen.raw_framebuffer();
+    let format = framebuffer.format;
+
+    let depth = format.pixel_depth_bytes();
+    let (fb_width, fb_height) = (framebuffer.width, framebuffer.height);
+
+    // SAFETY: the framebuffer is allocated for the given size and format by `libctru`.
+    let data = unsafe { std::slice::from_raw_parts(framebuffer.ptr, framebuffer.len()) };
+
+    let mut image = Vec::with_capacity(fb_width * fb_height * 3);
+
+    // Image rows are framebuffer columns, starting from the last one.
+    for y in 0..fb_width {
+        for x in 0..fb_height {
+            let offset = (x * fb_width + (fb_width - 1 - y)) * depth;
+
+            image.extend_from_slice(&pixel_to_rgb(format, &data[offset..offset + depth]));
+        }
+    }
+
+    image
+}
+
+/// Convert an RGB color to a single pixel with the given format, as stored in memory.
+///
+/// Only the first [`FramebufferFormat::pixel_depth_bytes()`] bytes of the result are meaningful.
+fn rgb_to_pixel(format: FramebufferFormat, [r, g, b]: [u8; 3]) -> [u8; 4] {
+    // Reduce an 8 bit color channel to `bits` bits.
+    let reduce = |value: u8, bits: u32| -> u16 { u16::from(value) >> (8 - bits) };
+
+    let packed = match format {
+        FramebufferFormat::Rgba8 => return [0xFF, b, g, r],
+        FramebufferFormat::Bgr8 => return [b, g, r, 0],
+        FramebufferFormat::Rgb565 => reduce(r, 5) << 11 | reduce(g, 6) << 5 | reduce(b, 5),
+        FramebufferFormat::Rgb5A1 => reduce(r, 5) << 11 | reduce(g, 5) << 6 | reduce(b, 5) << 1 | 1,
+        FramebufferFormat::Rgba4 => {
+            reduce(r, 4) << 12 | reduce(g, 4) << 8 | reduce(b, 4) << 4 | 0xF
+        }
+    };
+
+    let [low, high] = packed.to_le_bytes();
+
+    [low, high, 0, 0]
+}
+
+/// Convert a single pixel stored in memory with the given format to RGB.
+fn pixel_to_rgb(format: FramebufferFormat, pixel: &[u8]) -> [u8; 3] {
+    // Expand a color channel of `bits` bits to 8 bits.
+    let expand = |value: u16, bits: u32| -> u8 {
+        let max = (1 << bits) - 1;
+        ((u32::from(value) * 255 + max / 2) / max) as u8
+    };
+
+    match format {
+        FramebufferFormat::Rgba8 => [pixel[3], pixel[2], pixel[1]],
+        FramebufferFormat::Bgr8 => [pixel[2], pixel[1], pixel[0]],
+        FramebufferFormat::Rgb565 => {
+            let value = u16::from_le_bytes([pixel[0], pixel[1]]);
+            [
+                expand(value >> 11, 5),
+                expand((value >> 5) & 0x3F, 6),
+                expand(value & 0x1F, 5),
+            ]
+        }
+        FramebufferFormat::Rgb5A1 => {
+            let value = u16::from_le_bytes([pixel[0], pixel[1]]);
+            [
+                expand(value >> 11, 5),
+                expand((value >> 6) & 0x1F, 5),
+                expand((value >> 1) & 0x1F, 5),
+            ]
+        }
+        FramebufferFormat::Rgba4 => {
+            let value = u16::from_le_bytes([pixel[0], pixel[1]]);
+            [
+                expand(value >> 12, 4),
+                expand((value >> 8) & 0xF, 4),
+                expand((value >> 4) & 0xF, 4),
+            ]
+        }
+    }
+}
+
+impl TopScreen3D<'_> {
+    /// Immutably borrow the two sides of the screen as `(left, right)`.
+    pub fn split(&self) -> (Ref<TopScreenLeft>, Ref<TopScreenRight>) {
+        Ref::map_split(self.screen.borrow(), |screen| (&screen.left, &screen.right))
+    }
+
+    /// Mutably borrow the two sides of the screen as `(left, right)`.
+    pub fn split_mut(&self) -> (RefMut<TopScreenLeft>, RefMut<TopScreenRight>) {
+        RefMut::map_split(self.screen.borrow_mut(), |screen| {
+            (&mut screen.left, &mut screen.right)
+        })
+    }
+}
+
+/// Convert the [`TopScreen`] into a [`TopScreen3D`] and activate stereoscopic 3D.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru::services::gfx::{Gfx, TopScreen, TopScreen3D};
+/// let gfx = Gfx::new()?;
+///
+/// let mut top_screen = TopScreen3D::from(&gfx.top_screen);
+///
+/// let (left, right) = top_screen.split_mut();
+///
+/// // Rendering must be done twice for each side
+/// // (with a slight variation in perspective to simulate the eye-to-eye distance).
+/// render(left);
+/// render(right);
+/// #
+/// # Ok(())
+/// # }
+/// #
+/// # use ctru::services::gfx::Screen;
+/// # use std::cell::RefMut;
+/// # fn render(screen: RefMut<'_, dyn Screen>) {}
+/// ```
+impl<'screen> From<&'screen RefCell<TopScreen>> for TopScreen3D<'screen> {
+    #[doc(alias = "gfxSet3D")]
+    fn from(top_screen: &'screen RefCell<TopScreen>) -> Self {
+        unsafe {
+            ctru_sys::gfxSet3D(true);
+        }
+
+        TopScreen3D { screen: top_screen }
+    }
+}
+
+impl Drop for TopScreen3D<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            ctru_sys::gfxSet3D(false);
+        }
+    }
+}
+
+impl TopScreen {
+    fn new() -> Self {
+        Self {
+            left: TopScreenLeft,
+            right: TopScreenRight,
+        }
+    }
+
+    /// Mutably borrow the two sides of the screen as `(left, right)`, e.g. to render each eye on separate threads.
+    ///
+    /// Since the [`TopScreen`] stays mutably borrowed while the sides are in use, it can't be drawn on at the same time.
+    ///
+    /// # Notes
+    ///
+    /// The right side is only shown when stereoscopic 3D is enabled (see [`TopScreen3D`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::gfx::{Gfx, Screen};
+    /// let gfx = Gfx::new()?;
+    /// let mut top_screen = gfx.top_screen.borrow_mut();
+    ///
+    /// let (left, right) = top_screen.split_mut();
+    ///
+    /// left.raw_framebuffer().as_mut_slice().fill(0);
+    /// right.raw_framebuffer().as_mut_slice().fill(0);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn split_mut(&mut self) -> (&mut TopScreenLeft, &mut TopScreenRight) {
+        (&mut self.left, &mut self.right)
+    }
+
+    /// Enable or disable wide mode on the top screen.
+    ///
+    /// # Notes
+    ///
+    /// [`Swap::swap_buffers`] must be called after this method for the configuration
+    /// to take effect.
+    ///
+    /// Wide mode does NOT work on Old 2DS models (but still does on New 2DS XL models).
+    #[doc(alias = "gfxSetWide")]
+    pub fn set_wide_mode(&mut self, enable: bool) {
+        unsafe {
+            ctru_sys::gfxSetWide(enable);
+        }
+    }
+
+    /// Returns whether or not wide mode is enabled on the top screen.
+    #[doc(alias = "gfxIsWide")]
+    pub fn is_wide(&self) -> bool {
+        unsafe { ctru_sys::gfxIsWide() }
+    }
+}
+
+// When 3D mode is disabled, only the left side is used, so this Screen impl
+// just forwards everything to the TopScreenLeft.
+impl Screen for TopScreen {
+    fn as_raw(&self) -> ctru_sys::gfxScreen_t {
+        self.left.as_raw()
+    }
+
+    fn side(&self) -> Side {
+        self.left.side()
+    }
+}
+
+impl Screen for TopScreenLeft {
+    fn as_raw(&self) -> ctru_sys::gfxScreen_t {
+        ctru_sys::GFX_TOP
+    }
+
+    fn side(&self) -> Side {
+        Side::Left
+    }
+}
+
+impl Screen for TopScreenRight {
+    fn as_raw(&self) -> ctru_sys::gfxScreen_t {
+        ctru_sys::GFX_TOP
+    }
+
+    fn side(&self) -> Side {
+        Side::Right
+    }
+}
+
+impl Screen for BottomScreen {
+    fn as_raw(&self) -> ctru_sys::gfxScreen_t {
+        ctru_sys::GFX_BOTTOM
+    }
+
+    fn side(&self) -> Side {
+        Side::Left
+    }
+}
+
+from_impl!(Side, ctru_sys::gfx3dSide_t);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn gfx_duplicate() {
+        // NOTE: this is expected to fail if using the console test runner, since
+        // that necessarily creates a Gfx as part of its test setup:
+        let _gfx = Gfx::new().unwrap();
+
+        assert!(matches!(Gfx::new(), Err(Error::ServiceAlreadyActive)));
+    }
+
+    #[test]
+    #[should_panic(expected = "another frame is being drawn")]
+    fn gfx_nested_frame() {
+        let gfx = Gfx::new().unwrap();
+
+        gfx.frame(|_| gfx.frame(|_| {}));
+    }
+
+    #[test]
+    fn pixel_formats_to_rgb() {
+        assert_eq!(
+            pixel_to_rgb(FramebufferFormat::Rgba8, &[0xFF, 0x30, 0x20, 0x10]),
+            [0x10, 0x20, 0x30]
+        );
+        assert_eq!(
+            pixel_to_rgb(FramebufferFormat::Bgr8, &[0x30, 0x20, 0x10]),
+            [0x10, 0x20, 0x30]
+        );
+        assert_eq!(
+            pixel_to_rgb(FramebufferFormat::Rgb565, &0xF800u16.to_le_bytes()),
+            [255, 0, 0]
+        );
+        assert_eq!(
+            pixel_to_rgb(FramebufferFormat::Rgb5A1, &0x003Fu16.to_le_bytes()),
+            [0, 0, 255]
+        );
+        assert_eq!(
+            pixel_to_rgb(FramebufferFormat::Rgba4, &0x0F0Fu16.to_le_bytes()),
+            [0, 255, 0]
+        );
+    }
+
+    #[test]
+    fn rgb_to_pixel_round_trip() {
+        use FramebufferFormat::*;
+
+        for format in [Rgba8, Bgr8, Rgb565, Rgb5A1, Rgba4] {
+            for color in [
+                [0, 0, 0],
+                [255, 255, 255],
+                [255, 0, 0],
+                [0, 255, 0],
+                [0, 0, 255],
+            ] {
+                let pixel = rgb_to_pixel(format, color);
+
+                assert_eq!(pixel_to_rgb(format, &pixel), color, "{format:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! VRAM allocation.
+//!
+//! VRAM is a dedicated 6 MiB memory sector reserved to the GPU, which offers faster access times than the main RAM.
+//! It's mostly useful to store GPU-side resources such as textures, vertex buffers, depth buffers and render targets.
+//!
+//! # Notes
+//!
+//! Unlike [LINEAR memory](crate::linear), VRAM isn't meant to be accessed by the CPU:
+//! data is usually loaded into it by the GPU itself (e.g. via DMA transfers from LINEAR memory) and trying to read from (or write to)
+//! it the same way as normal memory may result in ARM exceptions or reading stale data.
+//! For this reason, the types in this module never hand out references to their contents, only pointers and physical addresses
+//! meant to be passed to the GPU.
+//!
+//! VRAM allocations can't be used with other hardware components that require LINEAR memory, such as the DSP.
+//!
+//! # Additional Resources
+//!
+//! - <https://github.com/devkitPro/libctru/blob/master/libctru/source/allocator/vram.cpp>
+//! - <https://www.3dbrew.org/wiki/Memory_layout>
+#![doc(alias = "vram")]
+
+use std::alloc::AllocError;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::NonNull;
+
+/// Returns the amount of free space left in the VRAM sector.
+#[doc(alias = "vramSpaceFree")]
+pub fn free_space() -> u32 {
+    unsafe { ctru_sys::vramSpaceFree() }
+}
+
+/// Owned allocation on the VRAM, large enough to hold a value of type `T`.
+///
+/// The allocation is freed once the [`VramBox`] is dropped.
+///
+/// # Notes
+///
+/// Contrary to [`Box`], the contents of a [`VramBox`] are not (and can't be) initialized by the CPU,
+/// so they should be considered undefined until the GPU writes to them.
+/// For the same reason, [`VramBox`] doesn't implement [`Deref`](std::ops::Deref), and its contents are never dropped.
+///
+/// [`VramBox`] doesn't implement [`LinearAllocation`](crate::linear::LinearAllocation), so it can't be used
+/// where LINEAR memory is required:
+///
+/// ```compile_fail
+/// # let _runner = test_runner::GdbRunner::default();
+/// use ctru::services::gfx::vram::VramBox;
+/// use ctru::services::ndsp::{wave::Wave, AudioFormat};
+///
+/// let audio_data = VramBox::<[u8; 96]>::new().unwrap();
+///
+/// // Wave buffers must be accessible by the DSP, which can't read from VRAM.
+/// let wave = Wave::new(audio_data, AudioFormat::PCM16Stereo, false);
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru::services::gfx::vram::VramBox;
+///
+/// // Depth buffer for a 400x240 render target, with 24 bit depth and 8 bit stencil.
+/// let depth_buffer = VramBox::<[u32; 400 * 240]>::new()?;
+///
+/// // The physical address is what the GPU is given to access the buffer.
+/// println!("Depth buffer at {:#X}", depth_buffer.physical_address());
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub struct VramBox<T: Copy> {
+    ptr: NonNull<T>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Copy> VramBox<T> {
+    /// Allocate enough VRAM to hold a value of type `T`, aligned as required by `T`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there isn't enough contiguous free space left in the VRAM.
+    #[doc(alias = "vramAlloc", alias = "vramMemAlign")]
+    pub fn new() -> Result<Self, AllocError> {
+        // `vramMemAlign` doesn't handle zero-sized allocations.
+        let size = mem::size_of::<T>().max(1);
+        let pointer = unsafe { ctru_sys::vramMemAlign(size, mem::align_of::<T>()) };
+
+        NonNull::new(pointer.cast())
+            .map(|ptr| Self {
+                ptr,
+                _marker: PhantomData,
+            })
+            .ok_or(AllocError)
+    }
+
+    /// Returns a pointer to the allocation, in the application's virtual address space.
+    ///
+    /// # Notes
+    ///
+    /// The returned pointer shouldn't be dereferenced by the CPU. See the [module documentation](self) for more info.
+    pub fn as_ptr(&self) -> *const T {
+        self.ptr.as_ptr()
+    }
+
+    /// Returns a mutable pointer to the allocation, in the application's virtual address space.
+    ///
+    /// # Notes
+    ///
+    /// The returned pointer shouldn't be dereferenced by the CPU. See the [module documentation](self) for more info.
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.ptr.as_ptr()
+    }
+
+    /// Returns the physical address of the allocation, as used by the GPU.
+    #[doc(alias = "osConvertVirtToPhys")]
+    pub fn physical_address(&self) -> u32 {
+        unsafe { ctru_sys::osConvertVirtToPhys(self.ptr.as_ptr().cast()) }
+    }
+
+    /// Returns the size of the allocation in bytes.
+    pub fn size(&self) -> usize {
+        mem::size_of::<T>()
+    }
+}
+
+impl<T: Copy> Drop for VramBox<T> {
+    #[doc(alias = "vramFree")]
+    fn drop(&mut self) {
+        unsafe { ctru_sys::vramFree(self.ptr.as_ptr().cast()) };
+    }
+}
+
+// SAFETY: the allocation is uniquely owned and is never accessed through shared references by the CPU.
+unsafe impl<T: Copy + Send> Send for VramBox<T> {}
+unsafe impl<T: Copy + Sync> Sync for VramBox<T> {}
@@ -8,13 +8,16 @@
 #![doc(alias = "gamepad")]
 
 use std::sync::Mutex;
+use std::time::Duration;
 
 use crate::error::ResultCode;
+use crate::services::apt::Apt;
+use crate::services::svc::timeout_as_nanos;
 use crate::services::ServiceReference;
 
 use bitflags::bitflags;
 
-static HID_ACTIVE: Mutex<()> = Mutex::new(());
+pub(crate) static HID_ACTIVE: Mutex<()> = Mutex::new(());
 
 bitflags! {
     /// A set of flags corresponding to the button and directional pad inputs present on the 3DS.
@@ -93,29 +96,90 @@ pub enum Error {
 /// Representation of the acceleration vector read by the accelerometer.
 ///
 /// Have a look at [`Hid::set_accelerometer()`] for more information.
-#[allow(missing_docs)]
+#[doc(alias = "accelVector")]
 #[derive(Default, Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Acceleration {
-    x: i16,
-    y: i16,
-    z: i16,
+    /// Acceleration along the X axis.
+    pub x: i16,
+    /// Acceleration along the Y axis.
+    pub y: i16,
+    /// Acceleration along the Z axis.
+    pub z: i16,
 }
 
 /// Representation of the angular rate read by the gyroscope.
 ///
 /// Have a look at [`Hid::set_gyroscope()`] for more information.
-#[allow(missing_docs)]
+#[doc(alias = "angularRate")]
 #[derive(Default, Copy, Clone, Debug, PartialEq, Eq)]
 pub struct AngularRate {
-    roll: i16,
-    pitch: i16,
-    yaw: i16,
+    /// Rotation rate around the X axis.
+    pub roll: i16,
+    /// Rotation rate around the Y axis.
+    pub pitch: i16,
+    /// Rotation rate around the Z axis.
+    pub yaw: i16,
+}
+
+/// Transition of the touch screen between the last two calls to [`Hid::scan_input()`].
+///
+/// Have a look at [`Hid::touch()`] for more information.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Touch {
+    /// The touch screen was not touched on either frame.
+    None,
+    /// The touch screen started being touched at (x, y).
+    Began {
+        /// Horizontal position, in bottom screen pixels.
+        x: u16,
+        /// Vertical position, in bottom screen pixels.
+        y: u16,
+    },
+    /// The touch screen is still being touched, now at (x, y). The position moved by (dx, dy) since the previous frame.
+    Moved {
+        /// Horizontal position, in bottom screen pixels.
+        x: u16,
+        /// Vertical position, in bottom screen pixels.
+        y: u16,
+        /// Horizontal movement since the previous frame.
+        dx: i16,
+        /// Vertical movement since the previous frame.
+        dy: i16,
+    },
+    /// The touch screen stopped being touched.
+    Ended,
+}
+
+impl Acceleration {
+    /// Returns the reading in the raw `libctru` representation.
+    pub fn raw(&self) -> ctru_sys::accelVector {
+        ctru_sys::accelVector {
+            x: self.x,
+            y: self.y,
+            z: self.z,
+        }
+    }
+}
+
+impl AngularRate {
+    /// Returns the reading in the raw `libctru` representation, which stores the roll, pitch and yaw as x, y and z.
+    pub fn raw(&self) -> ctru_sys::angularRate {
+        ctru_sys::angularRate {
+            x: self.roll,
+            y: self.pitch,
+            z: self.yaw,
+        }
+    }
 }
 
 /// Handle to the HID service.
 pub struct Hid {
     active_accelerometer: bool,
     active_gyroscope: bool,
+    active_extended_input: bool,
+    // Touch positions of the previous and current frames, as reported by `touch_screen_pixel`.
+    previous_touch: Option<(u16, u16)>,
+    current_touch: Option<(u16, u16)>,
     _service_handler: ServiceReference,
 }
 
@@ -145,6 +209,7 @@ impl Hid {
     pub fn new() -> crate::Result<Hid> {
         let handler = ServiceReference::new(
             &HID_ACTIVE,
+            "hid:USER",
             || {
                 ResultCode(unsafe { ctru_sys::hidInit() })?;
 
@@ -161,6 +226,9 @@ impl Hid {
         Ok(Self {
             active_accelerometer: false,
             active_gyroscope: false,
+            active_extended_input: false,
+            previous_touch: None,
+            current_touch: None,
             _service_handler: handler,
         })
     }
@@ -188,6 +256,140 @@ impl Hid {
     #[doc(alias = "hidScanInput")]
     pub fn scan_input(&mut self) {
         unsafe { ctru_sys::hidScanInput() };
+
+        self.previous_touch = self.current_touch;
+        self.current_touch = self.touch_screen_pixel();
+    }
+
+    /// Block the current thread until any button is pressed, returning the buttons pressed.
+    ///
+    /// This is a convenience for "press any key to continue" prompts: it scans the input in a loop,
+    /// checking [`Apt::main_loop()`] on each iteration so that the HOME Menu and the power button keep working.
+    /// Returns `None` if the application should close before any button is pressed.
+    ///
+    /// # Notes
+    ///
+    /// The loop calls [`std::thread::yield_now()`] between scans. The 3DS scheduler is cooperative, so this only lets
+    /// threads with the same priority run on the current core: threads with a lower priority won't run until this function returns.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::apt::Apt;
+    /// use ctru::services::hid::{Hid, KeyPad};
+    /// let apt = Apt::new()?;
+    /// let mut hid = Hid::new()?;
+    ///
+    /// println!("Press any key to continue...");
+    ///
+    /// if let Some(keys) = hid.wait_for_any_key(&apt) {
+    ///     if keys.contains(KeyPad::START) {
+    ///         println!("You pressed START!");
+    ///     }
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn wait_for_any_key(&mut self, apt: &Apt) -> Option<KeyPad> {
+        while apt.main_loop() {
+            self.scan_input();
+
+            let keys = self.keys_down();
+            if !keys.is_empty() {
+                return Some(keys);
+            }
+
+            std::thread::yield_now();
+        }
+
+        None
+    }
+
+    /// Block the current thread until the HID module updates the input state, or until `timeout` is reached.
+    ///
+    /// Waiting on the hardware event (instead of scanning in a busy loop) lets other threads run in the meantime,
+    /// including the ones with a lower priority. Passing [`None`] waits without a timeout.
+    ///
+    /// # Notes
+    ///
+    /// The event is signaled every time the HID module refreshes the state of the buttons, circle pad and touch screen
+    /// (as well as the accelerometer and gyroscope, when enabled), which happens periodically even when nothing changed.
+    /// Call [`Hid::scan_input()`] after waking up and check [`Hid::keys_changed()`] to know whether the user actually did something.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the timeout is reached. You can use [`Error::is_timeout`](crate::Error::is_timeout)
+    /// to check if the error is due to a timeout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use std::time::Duration;
+    /// use ctru::services::hid::Hid;
+    /// let mut hid = Hid::new()?;
+    ///
+    /// // Park the thread until the input state is refreshed.
+    /// match hid.wait_for_event(Some(Duration::from_millis(100))) {
+    ///     Ok(()) => {
+    ///         hid.scan_input();
+    ///
+    ///         if !hid.keys_changed().is_empty() {
+    ///             println!("A button was pressed or released");
+    ///         }
+    ///     }
+    ///     Err(e) if e.is_timeout() => println!("Nothing happened"),
+    ///     Err(e) => return Err(e.into()),
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "hidWaitForAnyEvent")]
+    pub fn wait_for_event(&self, timeout: Option<Duration>) -> crate::Result<()> {
+        // A negative timeout waits indefinitely.
+        let timeout = timeout.map(timeout_as_nanos).transpose()?.unwrap_or(-1);
+
+        // Discard the pending events, so that only updates happening from now on wake the thread up.
+        ResultCode(unsafe { ctru_sys::hidWaitForAnyEvent(true, 0, timeout) })?;
+
+        Ok(())
+    }
+
+    /// Returns a bitflag struct representing which buttons changed state (either pressed or released)
+    /// between the last two calls to [`Hid::scan_input()`].
+    ///
+    /// This is the same as `hid.keys_down() | hid.keys_up()`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::hid::Hid;
+    /// let mut hid = Hid::new()?;
+    ///
+    /// hid.scan_input();
+    ///
+    /// if !hid.keys_changed().is_empty() {
+    ///     println!("Something happened!");
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn keys_changed(&self) -> KeyPad {
+        self.keys_down() | self.keys_up()
     }
 
     /// Returns a bitflag struct representing which buttons have just been pressed
@@ -314,6 +516,84 @@ impl Hid {
         (res.px, res.py)
     }
 
+    /// Returns the position of the current touch in bottom screen pixels (x, y), or `None` if the touch screen isn't being touched.
+    ///
+    /// # Notes
+    ///
+    /// The coordinates are mapped by the system using the console's touch screen calibration,
+    /// and are clamped to the size of the bottom screen (320x240), so they can be used for hit-testing of UI elements.
+    ///
+    /// Unlike [`Hid::touch_position()`], this function distinguishes between a touch at the top left corner and no touch at all.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::hid::Hid;
+    /// let mut hid = Hid::new()?;
+    ///
+    /// hid.scan_input();
+    ///
+    /// if let Some((x, y)) = hid.touch_screen_pixel() {
+    ///     println!("Touched at ({x}, {y})");
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "hidTouchRead")]
+    pub fn touch_screen_pixel(&self) -> Option<(u16, u16)> {
+        const WIDTH: u16 = 320;
+        const HEIGHT: u16 = 240;
+
+        if !self.keys_held().contains(KeyPad::TOUCH) {
+            return None;
+        }
+
+        let (x, y) = self.touch_position();
+
+        Some((x.min(WIDTH - 1), y.min(HEIGHT - 1)))
+    }
+
+    /// Returns how the touch screen changed between the last two calls to [`Hid::scan_input()`].
+    ///
+    /// This makes it easy to detect taps and drags without keeping track of the previous touch position.
+    /// Positions are reported in bottom screen pixels, like [`Hid::touch_screen_pixel()`].
+    ///
+    /// # Notes
+    ///
+    /// [`Touch::Moved`] is reported on every frame the touch screen is held after the first one, even if the position didn't change
+    /// (in which case `dx` and `dy` are 0).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::hid::{Hid, Touch};
+    /// let mut hid = Hid::new()?;
+    ///
+    /// hid.scan_input();
+    ///
+    /// match hid.touch() {
+    ///     Touch::Began { x, y } => println!("Touched at ({x}, {y})"),
+    ///     Touch::Moved { dx, dy, .. } => println!("Dragged by ({dx}, {dy})"),
+    ///     Touch::Ended => println!("Released"),
+    ///     Touch::None => {}
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn touch(&self) -> Touch {
+        Touch::from_frames(self.previous_touch, self.current_touch)
+    }
+
     /// Returns the current circle pad position in relative (x, y).
     ///
     /// # Notes
@@ -348,6 +628,53 @@ impl Hid {
         (res.dx, res.dy)
     }
 
+    /// Returns the current C-Stick position in relative (x, y).
+    ///
+    /// The C-Stick (and the ZL and ZR buttons, reported through [`KeyPad::ZL`] and [`KeyPad::ZR`]) is read via the `ir:rst` service,
+    /// so the [extended input](Hid::enable_extended_input) must be enabled. Otherwise, (0, 0) is returned.
+    ///
+    /// # Notes
+    ///
+    /// (0, 0) represents the center of the C-Stick.
+    ///
+    /// The C-Stick is only built into New 3DS family consoles. On Old 3DS family consoles, it is only available
+    /// when a Circle Pad Pro is attached, and this function always returns (0, 0) otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::hid::Hid;
+    /// let mut hid = Hid::new()?;
+    ///
+    /// hid.enable_extended_input()?;
+    ///
+    /// hid.scan_input();
+    ///
+    /// let (stick_x, stick_y) = hid.cstick_position();
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "hidCstickRead")]
+    #[doc(alias = "irrstCstickRead")]
+    pub fn cstick_position(&self) -> (i16, i16) {
+        if !self.active_extended_input {
+            return (0, 0);
+        }
+
+        let mut res = ctru_sys::circlePosition { dx: 0, dy: 0 };
+
+        unsafe {
+            ctru_sys::hidCstickRead(&mut res);
+        }
+
+        (res.dx, res.dy)
+    }
+
     /// Returns the current volume slider position (between 0 and 1).
     ///
     /// # Notes
@@ -446,6 +773,59 @@ impl Hid {
         Ok(())
     }
 
+    /// Enable the extended input provided by the `ir:rst` service.
+    ///
+    /// Once enabled, [`Hid::scan_input()`] will also read the state of the ZL and ZR buttons
+    /// and of the C-Stick (whose position can then be read with [`Hid::cstick_position()`]), which will then be reported by [`Hid::keys_held()`] (as well as [`Hid::keys_down()`] and [`Hid::keys_up()`])
+    /// through [`KeyPad::ZL`], [`KeyPad::ZR`] and the `KeyPad::CSTICK_*` flags.
+    ///
+    /// Calling this function while the extended input is already enabled does nothing.
+    ///
+    /// # Notes
+    ///
+    /// This input is built into New 3DS family consoles. On Old 3DS family consoles it is only available
+    /// when a Circle Pad Pro is attached, otherwise enabling it succeeds but the extended inputs are never reported.
+    ///
+    /// The `ir:rst` service handle is kept open until the [`Hid`] handle is dropped.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the `ir:rst` service could not be initialized.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::hid::{Hid, KeyPad};
+    /// let mut hid = Hid::new()?;
+    ///
+    /// hid.enable_extended_input()?;
+    ///
+    /// hid.scan_input();
+    ///
+    /// if hid.keys_held().contains(KeyPad::ZL) {
+    ///     println!("You are holding the ZL button!")
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "irrstInit")]
+    pub fn enable_extended_input(&mut self) -> crate::Result<()> {
+        if self.active_extended_input {
+            return Ok(());
+        }
+
+        ResultCode(unsafe { ctru_sys::irrstInit() })?;
+
+        self.active_extended_input = true;
+
+        Ok(())
+    }
+
     /// Returns the acceleration vector (x, y, z) registered by the accelerometer.
     ///
     /// # Errors
@@ -486,11 +866,7 @@ impl Hid {
             ctru_sys::hidAccelRead(&mut res);
         }
 
-        Ok(Acceleration {
-            x: res.x,
-            y: res.y,
-            z: res.z,
-        })
+        Ok(res.into())
     }
 
     /// Returns the angular rate registered by the gyroscope.
@@ -533,11 +909,52 @@ impl Hid {
             ctru_sys::hidGyroRead(&mut res);
         }
 
-        Ok(AngularRate {
-            roll: res.x,
-            pitch: res.y,
-            yaw: res.z,
-        })
+        Ok(res.into())
+    }
+}
+
+impl Drop for Hid {
+    #[doc(alias = "irrstExit")]
+    fn drop(&mut self) {
+        if self.active_extended_input {
+            unsafe { ctru_sys::irrstExit() };
+        }
+    }
+}
+
+impl Touch {
+    fn from_frames(previous: Option<(u16, u16)>, current: Option<(u16, u16)>) -> Self {
+        match (previous, current) {
+            (None, None) => Self::None,
+            (None, Some((x, y))) => Self::Began { x, y },
+            (Some((px, py)), Some((x, y))) => Self::Moved {
+                x,
+                y,
+                dx: x as i16 - px as i16,
+                dy: y as i16 - py as i16,
+            },
+            (Some(_), None) => Self::Ended,
+        }
+    }
+}
+
+impl From<ctru_sys::accelVector> for Acceleration {
+    fn from(value: ctru_sys::accelVector) -> Self {
+        Self {
+            x: value.x,
+            y: value.y,
+            z: value.z,
+        }
+    }
+}
+
+impl From<ctru_sys::angularRate> for AngularRate {
+    fn from(value: ctru_sys::angularRate) -> Self {
+        Self {
+            roll: value.x,
+            pitch: value.y,
+            yaw: value.z,
+        }
     }
 }
 
@@ -563,3 +980,46 @@ impl std::fmt::Display for Error {
 }
 
 impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn touch_transitions() {
+        let frames = [
+            None,
+            Some((10, 20)),
+            Some((15, 18)),
+            Some((15, 18)),
+            None,
+            None,
+        ];
+
+        let transitions: Vec<Touch> = frames
+            .windows(2)
+            .map(|frames| Touch::from_frames(frames[0], frames[1]))
+            .collect();
+
+        assert_eq!(
+            transitions,
+            [
+                Touch::Began { x: 10, y: 20 },
+                Touch::Moved {
+                    x: 15,
+                    y: 18,
+                    dx: 5,
+                    dy: -2
+                },
+                Touch::Moved {
+                    x: 15,
+                    y: 18,
+                    dx: 0,
+                    dy: 0
+                },
+                Touch::Ended,
+                Touch::None,
+            ]
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! IR (Infrared) service.
+//!
+//! The ir:u service gives raw access to the console's infrared transceiver, exchanging plain bytes
+//! with IR peripherals or with other consoles.
+//!
+//! Have a look at [`ir_user`](crate::services::ir_user) to communicate with the Circle Pad Pro (and the New 3DS' built-in C-stick) instead,
+//! which use a higher-level packet protocol.
+//!
+//! # Notes
+//!
+//! The ir:u and ir:USER services drive the same hardware, so [`Ir`] and [`IrUser`](crate::services::ir_user::IrUser)
+//! should not be used at the same time.
+#![doc(alias = "infrared")]
+
+use std::alloc::Layout;
+use std::sync::Mutex;
+
+use crate::error::ResultCode;
+use crate::services::ServiceReference;
+use crate::Error;
+
+pub(crate) static IR_ACTIVE: Mutex<()> = Mutex::new(());
+
+/// Shared memory must be page-aligned and sized in multiples of the page size.
+const PAGE_SIZE: usize = 0x1000;
+
+/// Handle to the ir:u service.
+pub struct Ir {
+    // The service must be closed before its shared memory is freed, so this field is declared (and dropped) first.
+    _service_handler: ServiceReference,
+    shared_memory: SharedMemory,
+}
+
+/// Page-aligned buffer shared with the ir:u service.
+struct SharedMemory {
+    ptr: *mut u8,
+    layout: Layout,
+}
+
+impl Ir {
+    /// Initialize a new service handle.
+    ///
+    /// `buffer_size` is the size (in bytes) of the memory shared with the service, which limits the amount of data
+    /// transferred by a single call to [`Ir::send()`] or [`Ir::receive()`]. It is rounded up to a multiple of 4 KiB.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `buffer_size` is too large, if the service was unable to be initialized,
+    /// or if an [`Ir`] handle already exists.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::ir::Ir;
+    ///
+    /// let ir = Ir::new(0x1000)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "iruInit")]
+    pub fn new(buffer_size: usize) -> crate::Result<Self> {
+        let size = buffer_size
+            .max(1)
+            .checked_next_multiple_of(PAGE_SIZE)
+            .ok_or_else(|| Error::Other("IR buffer size too large".into()))?;
+        let layout = Layout::from_size_align(size, PAGE_SIZE)
+            .map_err(|e| Error::Other(format!("invalid IR buffer size: {e}")))?;
+
+        let ptr = unsafe { std::alloc::alloc_zeroed(layout) };
+        if ptr.is_null() {
+            std::alloc::handle_alloc_error(layout);
+        }
+
+        // Freed on drop, even if the service fails to start.
+        let shared_memory = SharedMemory { ptr, layout };
+
+        let _service_handler = ServiceReference::new(
+            &IR_ACTIVE,
+            "ir:u",
+            || {
+                ResultCode(unsafe { ctru_sys::iruInit(ptr.cast(), size as u32) })?;
+
+                Ok(())
+            },
+            || unsafe {
+                ctru_sys::iruExit();
+            },
+        )?;
+
+        Ok(Self {
+            _service_handler,
+            shared_memory,
+        })
+    }
+
+    /// Returns the maximum number of bytes transferred by a single call to [`Ir::send()`] or [`Ir::receive()`].
+    pub fn buffer_size(&self) -> usize {
+        self.shared_memory.layout.size()
+    }
+
+    /// Send `data` over IR, blocking until the transfer is complete.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `data` is bigger than [`Ir::buffer_size()`], or if the transfer failed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::ir::Ir;
+    /// let mut ir = Ir::new(0x1000)?;
+    ///
+    /// ir.send(b"hello")?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "IRU_SendData")]
+    pub fn send(&mut self, data: &[u8]) -> crate::Result<()> {
+        self.check_size(data.len())?;
+
+        // `libctru` copies the data to the shared memory, but takes a mutable pointer.
+        let mut data = data.to_vec();
+
+        ResultCode(unsafe { ctru_sys::IRU_SendData(data.as_mut_ptr(), data.len() as u32, 1) })?;
+
+        Ok(())
+    }
+
+    /// Receive data over IR into `buf`, returning the number of bytes received.
+    ///
+    /// # Notes
+    ///
+    /// The service has no timeout: this function blocks the current thread until the receive transfer completes,
+    /// that is until enough data to fill `buf` is received (or the transfer is otherwise ended by the peripheral).
+    /// Keep `buf` as short as the expected message to avoid blocking indefinitely.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `buf` is bigger than [`Ir::buffer_size()`], or if the transfer failed.
+    #[doc(alias = "IRU_RecvData")]
+    pub fn receive(&mut self, buf: &mut [u8]) -> crate::Result<usize> {
+        self.check_size(buf.len())?;
+
+        let mut received = 0;
+
+        ResultCode(unsafe {
+            ctru_sys::IRU_RecvData(buf.as_mut_ptr(), buf.len() as u32, 0, &mut received, 1)
+        })?;
+
+        Ok((received as usize).min(buf.len()))
+    }
+
+    /// Returns the raw bit rate value currently used for transfers.
+    #[doc(alias = "IRU_GetBitRate")]
+    pub fn bit_rate(&self) -> crate::Result<u8> {
+        let mut bit_rate = 0;
+
+        ResultCode(unsafe { ctru_sys::IRU_GetBitRate(&mut bit_rate) })?;
+
+        Ok(bit_rate)
+    }
+
+    /// Set the raw bit rate value used for transfers.
+    ///
+    /// Both ends of the communication must use the same bit rate.
+    /// See the [3dbrew wiki](https://www.3dbrew.org/wiki/IRU:SetBitRate) for the supported values.
+    #[doc(alias = "IRU_SetBitRate")]
+    pub fn set_bit_rate(&mut self, bit_rate: u8) -> crate::Result<()> {
+        ResultCode(unsafe { ctru_sys::IRU_SetBitRate(bit_rate) })?;
+
+        Ok(())
+    }
+
+    fn check_size(&self, len: usize) -> crate::Result<()> {
+        if len > self.buffer_size() {
+            return Err(Error::Other(format!(
+                "IR transfer of {len} bytes exceeds the buffer size of {} bytes",
+                self.buffer_size()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for SharedMemory {
+    fn drop(&mut self) {
+        unsafe { std::alloc::dealloc(self.ptr, self.layout) };
+    }
+}
@@ -20,7 +20,7 @@ use std::ffi::CString;
 use std::ptr::slice_from_raw_parts;
 use std::sync::Mutex;
 
-static IR_USER_ACTIVE: Mutex<()> = Mutex::new(());
+pub(crate) static IR_USER_ACTIVE: Mutex<()> = Mutex::new(());
 static IR_USER_STATE: Mutex<Option<IrUserState>> = Mutex::new(None);
 
 /// The "ir:USER" service. This service is used to talk to IR devices such as
@@ -68,6 +68,7 @@ impl IrUser {
     ) -> crate::Result<Self> {
         let service_reference = ServiceReference::new(
             &IR_USER_ACTIVE,
+            "ir:USER",
             || unsafe {
                 // Get the ir:USER service handle
                 let mut service_handle = Handle::default();
@@ -0,0 +1,296 @@
+//! Microphone service.
+//!
+//! The MIC service is used to record audio samples from the console's built-in microphone.
+//! Samples are written by the system into a shared memory buffer, which works as a ring buffer
+//! that can be read from with [`Mic::read_samples()`].
+#![doc(alias = "microphone")]
+#![doc(alias = "audio")]
+
+use std::alloc::{self, Layout};
+use std::ptr::NonNull;
+use std::sync::Mutex;
+
+use crate::error::ResultCode;
+use crate::services::ServiceReference;
+
+/// Alignment (and size granularity) required for the shared memory buffer.
+const BUFFER_ALIGNMENT: usize = 0x1000;
+
+pub(crate) static MIC_ACTIVE: Mutex<()> = Mutex::new(());
+
+/// Encoding of the samples recorded by the microphone.
+#[doc(alias = "MICU_Encoding")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Encoding {
+    /// Unsigned 8 bit PCM.
+    PCM8 = ctru_sys::MICU_ENCODING_PCM8,
+    /// Unsigned 16 bit PCM.
+    PCM16 = ctru_sys::MICU_ENCODING_PCM16,
+    /// Signed 8 bit PCM.
+    PCM8Signed = ctru_sys::MICU_ENCODING_PCM8_SIGNED,
+    /// Signed 16 bit PCM.
+    PCM16Signed = ctru_sys::MICU_ENCODING_PCM16_SIGNED,
+}
+
+/// Sample rate used by the microphone.
+#[doc(alias = "MICU_SampleRate")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SampleRate {
+    /// 32728.498 Hz.
+    Hz32730 = ctru_sys::MICU_SAMPLE_RATE_32730,
+    /// 16364.479 Hz.
+    Hz16360 = ctru_sys::MICU_SAMPLE_RATE_16360,
+    /// 10909.499 Hz.
+    Hz10910 = ctru_sys::MICU_SAMPLE_RATE_10910,
+    /// 8182.1245 Hz.
+    Hz8180 = ctru_sys::MICU_SAMPLE_RATE_8180,
+}
+
+/// Page-aligned buffer shared with the MIC service.
+struct SharedBuffer {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+impl SharedBuffer {
+    fn new(size: usize) -> Self {
+        let size = size.max(1).next_multiple_of(BUFFER_ALIGNMENT);
+        let layout = Layout::from_size_align(size, BUFFER_ALIGNMENT).unwrap();
+
+        let ptr = unsafe { alloc::alloc_zeroed(layout) };
+
+        match NonNull::new(ptr) {
+            Some(ptr) => Self { ptr, layout },
+            None => alloc::handle_alloc_error(layout),
+        }
+    }
+}
+
+impl Drop for SharedBuffer {
+    fn drop(&mut self) {
+        unsafe { alloc::dealloc(self.ptr.as_ptr(), self.layout) };
+    }
+}
+
+/// Handle to the MIC service.
+pub struct Mic {
+    // The service must be closed before the shared buffer is freed, so this field has to be declared first.
+    _service_handler: ServiceReference,
+    buffer: SharedBuffer,
+    encoding: Option<Encoding>,
+    read_offset: usize,
+}
+
+impl Mic {
+    /// Initialize a new service handle, using a shared buffer of (at least) `buffer_size` bytes to store the recorded samples.
+    ///
+    /// # Notes
+    ///
+    /// The buffer size is rounded up to a multiple of `0x1000` bytes.
+    /// The last 4 bytes of the buffer are reserved by the system, and cannot hold any samples.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the service was unable to be initialized,
+    /// or if a [`Mic`] handle already exists.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::mic::Mic;
+    ///
+    /// let mic = Mic::new(0x30000)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "micInit")]
+    pub fn new(buffer_size: usize) -> crate::Result<Self> {
+        let buffer = SharedBuffer::new(buffer_size);
+
+        let ptr = buffer.ptr.as_ptr();
+        let size = buffer.layout.size() as u32;
+
+        let _service_handler = ServiceReference::new(
+            &MIC_ACTIVE,
+            "mic:u",
+            || {
+                ResultCode(unsafe { ctru_sys::micInit(ptr, size) })?;
+
+                Ok(())
+            },
+            || unsafe {
+                let _ = ctru_sys::MICU_StopSampling();
+
+                ctru_sys::micExit();
+            },
+        )?;
+
+        Ok(Self {
+            _service_handler,
+            buffer,
+            encoding: None,
+            read_offset: 0,
+        })
+    }
+
+    /// Start recording samples into the shared buffer, with the given sample rate and encoding.
+    ///
+    /// The shared buffer is used as a ring buffer: once the end is reached, the system continues writing from the beginning.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::mic::{Encoding, Mic, SampleRate};
+    ///
+    /// let mut mic = Mic::new(0x30000)?;
+    ///
+    /// mic.start_sampling(SampleRate::Hz16360, Encoding::PCM16Signed)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "MICU_StartSampling")]
+    pub fn start_sampling(&mut self, rate: SampleRate, encoding: Encoding) -> crate::Result<()> {
+        let data_size = unsafe { ctru_sys::micGetSampleDataSize() };
+
+        ResultCode(unsafe {
+            ctru_sys::MICU_StartSampling(encoding.into(), rate.into(), 0, data_size, true)
+        })?;
+
+        self.encoding = Some(encoding);
+        self.read_offset = 0;
+
+        Ok(())
+    }
+
+    /// Stop recording samples.
+    #[doc(alias = "MICU_StopSampling")]
+    pub fn stop_sampling(&mut self) -> crate::Result<()> {
+        ResultCode(unsafe { ctru_sys::MICU_StopSampling() })?;
+
+        self.encoding = None;
+
+        Ok(())
+    }
+
+    /// Returns whether the microphone is currently recording samples.
+    #[doc(alias = "MICU_IsSampling")]
+    pub fn is_sampling(&self) -> crate::Result<bool> {
+        let mut sampling = false;
+
+        ResultCode(unsafe { ctru_sys::MICU_IsSampling(&mut sampling) })?;
+
+        Ok(sampling)
+    }
+
+    /// Set the microphone's gain (amplification).
+    ///
+    /// # Notes
+    ///
+    /// Valid values range from 0 to 119 (0x77).
+    #[doc(alias = "MICU_SetGain")]
+    pub fn set_gain(&mut self, gain: u8) -> crate::Result<()> {
+        ResultCode(unsafe { ctru_sys::MICU_SetGain(gain) })?;
+
+        Ok(())
+    }
+
+    /// Returns the microphone's current gain.
+    #[doc(alias = "MICU_GetGain")]
+    pub fn gain(&self) -> crate::Result<u8> {
+        let mut gain = 0;
+
+        ResultCode(unsafe { ctru_sys::MICU_GetGain(&mut gain) })?;
+
+        Ok(gain)
+    }
+
+    /// Read the samples recorded since the last call into `dst`, returning the number of samples read.
+    ///
+    /// Samples are converted to signed 16 bit PCM regardless of the [`Encoding`] used while recording
+    /// (8 bit samples are scaled to the full 16 bit range).
+    ///
+    /// # Notes
+    ///
+    /// If this function isn't called often enough, the system will wrap around the ring buffer and overwrite samples
+    /// that haven't been read yet. The buffer size passed to [`Mic::new()`] should be large enough to hold the samples
+    /// recorded between two reads.
+    ///
+    /// Returns `0` if [`Mic::start_sampling()`] wasn't called.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::mic::{Encoding, Mic, SampleRate};
+    ///
+    /// let mut mic = Mic::new(0x30000)?;
+    /// mic.start_sampling(SampleRate::Hz16360, Encoding::PCM16Signed)?;
+    ///
+    /// let mut samples = [0i16; 1024];
+    /// let count = mic.read_samples(&mut samples);
+    ///
+    /// println!("Recorded {count} samples");
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "micGetLastSampleOffset")]
+    pub fn read_samples(&mut self, dst: &mut [i16]) -> usize {
+        let Some(encoding) = self.encoding else {
+            return 0;
+        };
+
+        let data_size = unsafe { ctru_sys::micGetSampleDataSize() } as usize;
+        let write_offset = unsafe { ctru_sys::micGetLastSampleOffset() } as usize;
+
+        let sample_size = match encoding {
+            Encoding::PCM8 | Encoding::PCM8Signed => 1,
+            Encoding::PCM16 | Encoding::PCM16Signed => 2,
+        };
+
+        let base = self.buffer.ptr.as_ptr();
+
+        let mut count = 0;
+
+        while count < dst.len() && self.read_offset != write_offset {
+            // The buffer is written asynchronously by the system, so the reads must not be optimized away.
+            let byte = |offset: usize| unsafe { base.add(offset).read_volatile() };
+
+            dst[count] = match encoding {
+                Encoding::PCM8 => (i16::from(byte(self.read_offset)) - 128) << 8,
+                Encoding::PCM8Signed => i16::from(byte(self.read_offset) as i8) << 8,
+                Encoding::PCM16 => {
+                    let raw =
+                        u16::from_le_bytes([byte(self.read_offset), byte(self.read_offset + 1)]);
+                    (raw ^ 0x8000) as i16
+                }
+                Encoding::PCM16Signed => {
+                    i16::from_le_bytes([byte(self.read_offset), byte(self.read_offset + 1)])
+                }
+            };
+
+            self.read_offset = (self.read_offset + sample_size) % data_size;
+            count += 1;
+        }
+
+        count
+    }
+}
+
+from_impl!(Encoding, ctru_sys::MICU_Encoding);
+from_impl!(SampleRate, ctru_sys::MICU_SampleRate);
@@ -10,22 +10,44 @@
 //! which enables all network communications via sockets.
 //!
 //! In [`ctru-rs`](crate) some services only allow a single handle to be created at a time, to ensure a safe and controlled environment.
+//!
+//! # Features
+//!
+//! Some of the service wrappers (such as [`cam`], [`ndsp`] or [`uds`]) are gated behind Cargo features with the same name
+//! (`ir-user` for [`ir_user`]). They are all enabled by default via the `all-services` feature, which can be disabled
+//! to only build the wrappers the application actually uses.
 
+#[cfg(feature = "am")]
 pub mod am;
 pub mod apt;
+#[cfg(feature = "boss")]
+pub mod boss;
+#[cfg(feature = "cam")]
 pub mod cam;
 pub mod cfgu;
+#[cfg(feature = "frd")]
+pub mod frd;
 pub mod fs;
 pub mod gfx;
 pub mod gspgpu;
 pub mod hid;
+#[cfg(feature = "ir")]
+pub mod ir;
+#[cfg(feature = "ir-user")]
 pub mod ir_user;
+#[cfg(feature = "mic")]
+pub mod mic;
+#[cfg(feature = "ndsp")]
 pub mod ndsp;
 pub mod ps;
+#[cfg(feature = "qtm")]
+pub mod qtm;
 mod reference;
 pub mod soc;
+#[cfg(feature = "sslc")]
 pub mod sslc;
 pub mod svc;
+#[cfg(feature = "uds")]
 pub mod uds;
 
 cfg_if::cfg_if! {
@@ -52,3 +74,116 @@ cfg_if::cfg_if! {
 }
 
 pub(crate) use self::reference::ServiceReference;
+
+use std::sync::Mutex;
+
+/// Services which only allow a single handle at a time.
+///
+/// See [`is_poisoned()`] to learn how to use this.
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Service {
+    /// [`Backlight`](gfx::Backlight)
+    Backlight,
+    /// [`Boss`](boss::Boss)
+    #[cfg(feature = "boss")]
+    Boss,
+    /// [`Cam`](cam::Cam)
+    #[cfg(feature = "cam")]
+    Cam,
+    /// [`Frd`](frd::Frd)
+    #[cfg(feature = "frd")]
+    Frd,
+    /// [`Gfx`](gfx::Gfx)
+    Gfx,
+    /// [`Hid`](hid::Hid)
+    Hid,
+    /// [`Ir`](ir::Ir)
+    #[cfg(feature = "ir")]
+    Ir,
+    /// [`IrUser`](ir_user::IrUser)
+    #[cfg(feature = "ir-user")]
+    IrUser,
+    /// [`Mic`](mic::Mic)
+    #[cfg(feature = "mic")]
+    Mic,
+    /// [`Ndsp`](ndsp::Ndsp)
+    #[cfg(feature = "ndsp")]
+    Ndsp,
+    /// [`Qtm`](qtm::Qtm)
+    #[cfg(feature = "qtm")]
+    Qtm,
+    /// [`RomFS`](romfs::RomFS)
+    #[cfg(all(feature = "romfs", romfs_exists))]
+    RomFS,
+    /// [`Soc`](soc::Soc)
+    Soc,
+    /// [`Uds`](uds::Uds)
+    #[cfg(feature = "uds")]
+    Uds,
+}
+
+impl Service {
+    fn counter(self) -> &'static Mutex<()> {
+        match self {
+            Self::Backlight => &gfx::GSPLCD_ACTIVE,
+            #[cfg(feature = "boss")]
+            Self::Boss => &boss::BOSS_ACTIVE,
+            #[cfg(feature = "cam")]
+            Self::Cam => &cam::CAM_ACTIVE,
+            #[cfg(feature = "frd")]
+            Self::Frd => &frd::FRD_ACTIVE,
+            Self::Gfx => &gfx::GFX_ACTIVE,
+            Self::Hid => &hid::HID_ACTIVE,
+            #[cfg(feature = "ir")]
+            Self::Ir => &ir::IR_ACTIVE,
+            #[cfg(feature = "ir-user")]
+            Self::IrUser => &ir_user::IR_USER_ACTIVE,
+            #[cfg(feature = "mic")]
+            Self::Mic => &mic::MIC_ACTIVE,
+            #[cfg(feature = "ndsp")]
+            Self::Ndsp => &ndsp::NDSP_ACTIVE,
+            #[cfg(feature = "qtm")]
+            Self::Qtm => &qtm::QTM_ACTIVE,
+            #[cfg(all(feature = "romfs", romfs_exists))]
+            Self::RomFS => &romfs::ROMFS_ACTIVE,
+            Self::Soc => &soc::SOC_ACTIVE,
+            #[cfg(feature = "uds")]
+            Self::Uds => &uds::UDS_ACTIVE,
+        }
+    }
+}
+
+/// Returns `true` if a handle to `service` was dropped while its thread was panicking, and the service hasn't been initialized again since.
+///
+/// # Notes
+///
+/// All the services listed in [`Service`] are tracked this way. When a new handle to a poisoned service is created,
+/// the service is closed and restarted from a clean state before returning the new handle, which clears the poisoned state.
+/// Checking this function *before* creating the new handle is useful to know whether the state of the service (and of the data
+/// the application associates to it) may be inconsistent, for example to reset that data.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru::services::{self, Service, hid::Hid};
+///
+/// let recovered = services::is_poisoned(Service::Hid);
+/// let hid = Hid::new()?;
+///
+/// if recovered {
+///     println!("The previous HID handle was lost in a panic");
+/// }
+///
+/// assert!(!services::is_poisoned(Service::Hid));
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn is_poisoned(service: Service) -> bool {
+    service.counter().is_poisoned()
+}
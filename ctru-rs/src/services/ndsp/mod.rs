@@ -14,7 +14,10 @@
 // this module are `no_run`, since Citra doesn't provide a stub for the DSP firmware:
 // https://github.com/citra-emu/citra/issues/6111
 
+pub mod stream;
+pub mod wav;
 pub mod wave;
+pub use wav::{WavError, WavWriter};
 use wave::{Status, Wave};
 
 use crate::error::ResultCode;
@@ -22,12 +25,23 @@ use crate::linear::LinearAllocation;
 use crate::services::ServiceReference;
 
 use std::cell::{RefCell, RefMut};
+use std::collections::VecDeque;
 use std::error;
 use std::fmt;
-use std::sync::Mutex;
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
 
 const NUMBER_OF_CHANNELS: u8 = 24;
 
+/// Maximum number of waves queued at the same time on a single channel. See [`Channel::max_queued_waves()`].
+const MAX_QUEUED_WAVES: usize = 16;
+
+/// Size (in bytes) of a DSP-ADPCM frame, made of a 1-byte header and 7 bytes of samples.
+const ADPCM_FRAME_SIZE: usize = 8;
+
+/// Amount of 4-bit samples in a DSP-ADPCM frame.
+const ADPCM_SAMPLES_PER_FRAME: usize = 14;
+
 /// Audio output mode.
 #[doc(alias = "ndspOutputMode")]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -41,6 +55,17 @@ pub enum OutputMode {
     Surround = ctru_sys::NDSP_OUTPUT_SURROUND,
 }
 
+/// Device through which the audio output is played.
+///
+/// See [`Ndsp::output_device()`] to learn how to use this.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OutputDevice {
+    /// The console's built-in speakers.
+    Speakers,
+    /// Headphones plugged into the headphone jack.
+    Headphones,
+}
+
 /// PCM formats supported by the audio engine.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u8)]
@@ -53,6 +78,11 @@ pub enum AudioFormat {
     PCM8Stereo = ctru_sys::NDSP_FORMAT_STEREO_PCM8,
     /// PCM 16bit interleaved dual-channel.
     PCM16Stereo = ctru_sys::NDSP_FORMAT_STEREO_PCM16,
+    /// DSP-ADPCM 4bit single-channel.
+    ///
+    /// Samples are grouped in 8-byte frames, each made of a 1-byte header followed by 14 samples.
+    /// ADPCM waves also need the decoder coefficients of the encoded data, see [`Wave::new_adpcm()`].
+    Adpcm = ctru_sys::NDSP_FORMAT_MONO_ADPCM,
 }
 
 /// Representation of the volume mix for a channel.
@@ -96,6 +126,35 @@ pub enum Error {
     WaveBusy(u8),
     /// The sample amount requested was larger than the maximum.
     SampleCountOutOfBounds(usize, usize),
+    /// The format of the wave doesn't match the format set for the channel with the specified ID.
+    FormatMismatch {
+        /// ID of the channel.
+        id: u8,
+        /// Format set for the channel.
+        channel: AudioFormat,
+        /// Format of the wave.
+        wave: AudioFormat,
+    },
+    /// The wave was rejected by the channel with the specified ID (e.g. because it has no samples to play).
+    ///
+    /// The wave's status is set to [`Status::Error`](wave::Status::Error).
+    WaveRejected(u8),
+    /// The queue of the channel with the specified ID already holds [`Channel::max_queued_waves()`] waves.
+    QueueFull(u8),
+    /// The wave passed to [`Channel::play_wave()`] on the channel with the specified ID has no [sample rate](Wave::sample_rate).
+    MissingSampleRate(u8),
+}
+
+/// Error returned by [`AudioMix::try_new()`] when a volume value is invalid.
+///
+/// The contained value is the index of the invalid volume within the [raw mix layout](AudioMix::as_raw).
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MixError {
+    /// The volume at the given index is NaN.
+    NotANumber(usize),
+    /// The volume at the given index is negative.
+    Negative(usize),
 }
 
 /// NDSP Channel representation.
@@ -115,17 +174,80 @@ pub enum Error {
 /// The handle to a channel can be retrieved with [`Ndsp::channel()`]
 pub struct Channel<'ndsp> {
     id: u8,
-    _rf: RefMut<'ndsp, ()>, // we don't need to hold any data
+    state: RefMut<'ndsp, ChannelState>,
+}
+
+// Channel settings that can't be read back from `libctru`, kept across different `Channel` handles.
+#[derive(Default)]
+struct ChannelState {
+    mix: AudioMix,
+    muted: bool,
+    /// Sequence IDs of the waves queued via [`Channel::queue_wave()`], in queue order.
+    queued: VecDeque<u16>,
+    /// ADPCM coefficients last set on the channel.
+    adpcm_coefficients: Option<[i16; 16]>,
+}
+
+impl ChannelState {
+    /// Returns the sequence IDs of the waves which haven't finished playing yet.
+    fn pending(&self, id: u8) -> impl Iterator<Item = &u16> {
+        let (current, playing) = unsafe {
+            (
+                ctru_sys::ndspChnGetWaveBufSeq(id.into()),
+                ctru_sys::ndspChnIsPlaying(id.into()),
+            )
+        };
+
+        self.queued.iter().filter(move |&&seq| {
+            // Sequence IDs are assigned incrementally (wrapping around) by `libctru`.
+            let distance = seq.wrapping_sub(current) as i16;
+
+            distance > 0 || (distance == 0 && playing)
+        })
+    }
 }
 
-static NDSP_ACTIVE: Mutex<()> = Mutex::new(());
+pub(crate) static NDSP_ACTIVE: Mutex<()> = Mutex::new(());
+
+/// Callback registered via [`Channel::on_frame()`].
+type FrameCallback = Box<dyn FnMut() + Send>;
+
+/// Audio frame notifications, driven by the callback `libctru` calls from its NDSP thread after each audio frame.
+struct FrameSync {
+    /// Amount of audio frames processed since the service was initialized.
+    frames: Mutex<u64>,
+    signal: Condvar,
+    /// Callbacks registered via [`Channel::on_frame()`], indexed by channel ID.
+    callbacks: Mutex<[Option<FrameCallback>; NUMBER_OF_CHANNELS as usize]>,
+}
+
+static FRAME_SYNC: FrameSync = FrameSync {
+    frames: Mutex::new(0),
+    signal: Condvar::new(),
+    callbacks: Mutex::new([const { None }; NUMBER_OF_CHANNELS as usize]),
+};
+
+/// Maximum time spent waiting for the next audio frame before checking a wave's status again.
+///
+/// Frames are usually processed every ~5 ms, but the NDSP thread stops while the DSP is asleep (e.g. when the console's lid is closed).
+const FRAME_TIMEOUT: Duration = Duration::from_millis(100);
+
+unsafe extern "C" fn frame_callback(_data: *mut libc::c_void) {
+    // Panics can't unwind out of this function, so they abort the process.
+    for callback in FRAME_SYNC.callbacks.lock().unwrap().iter_mut().flatten() {
+        callback();
+    }
+
+    *FRAME_SYNC.frames.lock().unwrap() += 1;
+    FRAME_SYNC.signal.notify_all();
+}
 
 /// Handle to the DSP service.
 ///
 /// Only one handle for this service can exist at a time.
 pub struct Ndsp {
     _service_handler: ServiceReference,
-    channel_flags: [RefCell<()>; NUMBER_OF_CHANNELS as usize],
+    channel_flags: [RefCell<ChannelState>; NUMBER_OF_CHANNELS as usize],
 }
 
 impl Ndsp {
@@ -154,12 +276,16 @@ impl Ndsp {
     pub fn new() -> crate::Result<Self> {
         let _service_handler = ServiceReference::new(
             &NDSP_ACTIVE,
+            "dsp::DSP",
             || {
                 ResultCode(unsafe { ctru_sys::ndspInit() })?;
 
+                unsafe { ctru_sys::ndspSetCallback(Some(frame_callback), std::ptr::null_mut()) };
+
                 Ok(())
             },
             || unsafe {
+                ctru_sys::ndspSetCallback(None, std::ptr::null_mut());
                 ctru_sys::ndspExit();
             },
         )?;
@@ -197,7 +323,7 @@ impl Ndsp {
             Some(ref_cell) => {
                 let flag = ref_cell.try_borrow_mut();
                 match flag {
-                    Ok(_rf) => Ok(Channel { id, _rf }),
+                    Ok(state) => Ok(Channel { id, state }),
                     Err(_) => Err(Error::ChannelAlreadyInUse(id)),
                 }
             }
@@ -226,6 +352,41 @@ impl Ndsp {
     pub fn set_output_mode(&mut self, mode: OutputMode) {
         unsafe { ctru_sys::ndspSetOutputMode(mode.into()) };
     }
+
+    /// Returns the device currently playing the audio output.
+    ///
+    /// # Notes
+    ///
+    /// The system automatically switches the output to the headphones when they are plugged in, and there is no way
+    /// for applications to force a specific device. The state of the headphone jack is read from the shared system memory
+    /// updated by the kernel (see [`os::is_headset_connected()`](crate::os::is_headset_connected)), so this function doesn't
+    /// communicate with any service.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::ndsp::{Ndsp, OutputDevice, OutputMode};
+    /// let mut ndsp = Ndsp::new()?;
+    ///
+    /// // Stereo sound is pointless on the speakers of a 2DS.
+    /// if ndsp.output_device() == OutputDevice::Speakers {
+    ///     ndsp.set_output_mode(OutputMode::Mono);
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "osIsHeadsetConnected")]
+    pub fn output_device(&self) -> OutputDevice {
+        if crate::os::is_headset_connected() {
+            OutputDevice::Headphones
+        } else {
+            OutputDevice::Speakers
+        }
+    }
 }
 
 impl Channel<'_> {
@@ -249,6 +410,8 @@ impl Channel<'_> {
     #[doc(alias = "ndspChnReset")]
     pub fn reset(&mut self) {
         unsafe { ctru_sys::ndspChnReset(self.id.into()) };
+
+        *self.state = ChannelState::default();
     }
 
     /// Initialize the channel's parameters with default values.
@@ -400,12 +563,21 @@ impl Channel<'_> {
     /// # Ok(())
     /// # }
     /// ```
-    // TODO: Channels treat all waves as equal and do not read their format when playing them. Another good reason to re-write the service.
+    // Channels treat all waves as equal and do not read their format when playing them:
+    // `queue_wave()` checks the formats match, and `play_wave()` sets the channel's format from the wave's.
     #[doc(alias = "ndspChnSetFormat")]
     pub fn set_format(&mut self, format: AudioFormat) {
         unsafe { ctru_sys::ndspChnSetFormat(self.id.into(), format.into()) };
     }
 
+    /// Returns the channel's output format.
+    ///
+    /// Returns [`None`] if the channel uses a format not represented by [`AudioFormat`].
+    #[doc(alias = "ndspChnGetFormat")]
+    pub fn format(&self) -> Option<AudioFormat> {
+        AudioFormat::from_raw(unsafe { ctru_sys::ndspChnGetFormat(self.id.into()) })
+    }
+
     /// Set the channel's interpolation mode.
     ///
     /// # Example
@@ -433,6 +605,8 @@ impl Channel<'_> {
     ///
     /// Look at [`AudioMix`] for more information on the volume mix.
     ///
+    /// If the channel is [muted](Channel::set_muted), the new mix is only applied once the channel is unmuted.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -452,6 +626,73 @@ impl Channel<'_> {
     /// ```
     #[doc(alias = "ndspChnSetMix")]
     pub fn set_mix(&mut self, mix: &AudioMix) {
+        self.state.mix = *mix;
+
+        if !self.state.muted {
+            self.apply_mix(mix);
+        }
+    }
+
+    /// Returns the last volume mix set for the channel (even if the channel is currently muted).
+    pub fn mix(&self) -> AudioMix {
+        self.state.mix
+    }
+
+    /// Mute or unmute the channel.
+    ///
+    /// While muted, the channel's volume mix is zeroed. The mix previously set via [`Channel::set_mix()`]
+    /// is restored once the channel is unmuted.
+    ///
+    /// # Notes
+    ///
+    /// The muted state is kept until the channel is [reset](Channel::reset), even if this handle is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::ndsp::{AudioMix, Ndsp};
+    /// let ndsp = Ndsp::new()?;
+    /// let mut channel_0 = ndsp.channel(0)?;
+    ///
+    /// let mut mix = AudioMix::zeroed();
+    /// mix.set_front(0.5, 1.0);
+    /// channel_0.set_mix(&mix);
+    ///
+    /// channel_0.set_muted(true);
+    /// assert!(channel_0.is_muted());
+    ///
+    /// // The channel plays again with the previous mix.
+    /// channel_0.set_muted(false);
+    /// assert_eq!(channel_0.mix(), mix);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "ndspChnSetMix")]
+    pub fn set_muted(&mut self, muted: bool) {
+        if muted == self.state.muted {
+            return;
+        }
+
+        self.state.muted = muted;
+
+        if muted {
+            self.apply_mix(&AudioMix::zeroed());
+        } else {
+            let mix = self.state.mix;
+            self.apply_mix(&mix);
+        }
+    }
+
+    /// Returns `true` if the channel is muted.
+    pub fn is_muted(&self) -> bool {
+        self.state.muted
+    }
+
+    fn apply_mix(&mut self, mix: &AudioMix) {
         unsafe { ctru_sys::ndspChnSetMix(self.id.into(), mix.as_raw().as_ptr().cast_mut()) }
     }
 
@@ -478,7 +719,23 @@ impl Channel<'_> {
         unsafe { ctru_sys::ndspChnSetRate(self.id.into(), rate) };
     }
 
-    // TODO: wrap ADPCM format helpers.
+    /// Set the coefficients used by the DSP to decode [ADPCM](AudioFormat::Adpcm) waves played on the channel.
+    ///
+    /// The coefficients are provided by the encoder, along with the ADPCM data (e.g. in the header of `.dspadpcm` files).
+    /// [`Channel::queue_wave()`] sets them automatically for waves built with [`Wave::new_adpcm()`].
+    ///
+    /// # Notes
+    ///
+    /// The coefficients are shared by all the waves played on the channel, including the ones already queued.
+    #[doc(alias = "ndspChnSetAdpcmCoefs")]
+    pub fn set_adpcm_coefficients(&mut self, coefficients: &[i16; 16]) {
+        // `libctru` copies the coefficients, but takes a mutable pointer.
+        let mut coefficients = *coefficients;
+
+        unsafe { ctru_sys::ndspChnSetAdpcmCoefs(self.id.into(), coefficients.as_mut_ptr()) };
+
+        self.state.adpcm_coefficients = Some(coefficients);
+    }
 
     /// Clear the wave buffer queue and stop playback.
     ///
@@ -501,11 +758,157 @@ impl Channel<'_> {
     #[doc(alias = "ndspChnWaveBufClear")]
     pub fn clear_queue(&mut self) {
         unsafe { ctru_sys::ndspChnWaveBufClear(self.id.into()) };
+
+        self.state.queued.clear();
+        // The next ADPCM wave should set its own coefficients, even if they match the previous ones.
+        self.state.adpcm_coefficients = None;
+    }
+
+    /// Block the current thread until `wave` has finished playing, returning its final [`Status`].
+    ///
+    /// The thread sleeps until the NDSP service processes the next audio frame (about every 5 ms) before checking the wave's
+    /// status again, letting other threads run in the meantime. If the wave isn't [queued](Status::Queued) or [playing](Status::Playing),
+    /// this function returns immediately.
+    ///
+    /// # Notes
+    ///
+    /// Looping waves never finish playing by themselves: this function only returns once they are taken off the queue
+    /// (e.g. with [`Channel::clear_queue()`] on another thread).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #![feature(allocator_api)]
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// # use ctru::linear::LinearAllocator;
+    /// use ctru::services::ndsp::wave::{Status, Wave};
+    /// use ctru::services::ndsp::{AudioFormat, Ndsp};
+    /// let ndsp = Ndsp::new()?;
+    /// let mut channel_0 = ndsp.channel(0)?;
+    ///
+    /// # let audio_data: Box<[_], _> = Box::new_in([0u8; 96], LinearAllocator);
+    /// let mut wave = Wave::new(audio_data, AudioFormat::PCM16Stereo, false);
+    /// wave.set_sample_rate(44100.);
+    ///
+    /// channel_0.play_wave(&mut wave)?;
+    ///
+    /// assert_eq!(channel_0.wait_wave_done(&wave), Status::Done);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn wait_wave_done<Buffer: LinearAllocation + AsRef<[u8]>>(
+        &self,
+        wave: &Wave<Buffer>,
+    ) -> Status {
+        let mut frames = FRAME_SYNC.frames.lock().unwrap();
+
+        loop {
+            // The status is checked while holding the lock, so that the notification of the next frame can't be missed.
+            match wave.status() {
+                Status::Queued | Status::Playing => (),
+                status => return status,
+            }
+
+            let current = *frames;
+            frames = FRAME_SYNC
+                .signal
+                .wait_timeout_while(frames, FRAME_TIMEOUT, |frames| *frames == current)
+                .unwrap()
+                .0;
+        }
+    }
+
+    /// Register a callback called after each audio frame processed by the NDSP service (about every 5 ms).
+    ///
+    /// This replaces the callback previously registered for this channel, if any.
+    /// The callback is unregistered when the channel handle is dropped.
+    ///
+    /// # Notes
+    ///
+    /// The callback runs on the high priority thread used by `libctru` to feed the DSP, which is
+    /// stalled until the callback returns: keep it short, e.g. by only signaling another thread to refill a wave.
+    /// The callback must not panic, and must not register or unregister frame callbacks itself.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use std::sync::atomic::{AtomicU32, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// use ctru::services::ndsp::Ndsp;
+    /// let ndsp = Ndsp::new()?;
+    /// let channel_0 = ndsp.channel(0)?;
+    ///
+    /// let frames = Arc::new(AtomicU32::new(0));
+    /// let counter = Arc::clone(&frames);
+    ///
+    /// channel_0.on_frame(move || {
+    ///     counter.fetch_add(1, Ordering::Relaxed);
+    /// });
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "ndspSetCallback")]
+    pub fn on_frame(&self, callback: impl FnMut() + Send + 'static) {
+        FRAME_SYNC.callbacks.lock().unwrap()[self.id as usize] = Some(Box::new(callback));
+    }
+
+    /// Returns the maximum number of waves that can be queued at the same time on a channel.
+    ///
+    /// # Notes
+    ///
+    /// `libctru` keeps the queue of every channel as a linked list of the queued [`Wave`]s, feeding them to the DSP
+    /// as the previous ones finish playing. This limit is enforced by [`Channel::queue_wave()`] to give streaming code
+    /// some backpressure (and to bound the playback latency of newly queued waves): once the queue is full,
+    /// wait for a queued wave to be done before queueing the next one.
+    pub const fn max_queued_waves() -> usize {
+        MAX_QUEUED_WAVES
+    }
+
+    /// Returns the number of waves queued on the channel (via [`Channel::queue_wave()`]) that haven't finished playing yet.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::ndsp::{Channel, Ndsp};
+    /// let ndsp = Ndsp::new()?;
+    /// let channel_0 = ndsp.channel(0)?;
+    ///
+    /// if channel_0.queued_waves() < Channel::max_queued_waves() {
+    ///     // Decode and queue the next wave.
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "ndspChnGetWaveBufSeq")]
+    pub fn queued_waves(&self) -> usize {
+        self.state.pending(self.id).count()
     }
 
     /// Add a wave buffer to the channel's queue.
     /// If there are no other buffers in queue, playback for this buffer will start.
     ///
+    /// # Errors
+    ///
+    /// This function will return an error if the wave is already busy playing, or if the wave's format doesn't match the
+    /// [format set for the channel](Channel::set_format). The channel would otherwise play the wave's data as if it had the channel's format.
+    /// Use [`Channel::queue_wave_unchecked()`] to skip the format check.
+    ///
+    /// Waves without any samples to play are rejected with [`Error::WaveRejected`], and their status is set to [`Status::Error`].
+    ///
+    /// If [`Channel::max_queued_waves()`] waves are already queued on the channel, [`Error::QueueFull`] is returned instead.
+    ///
     /// # Warning
     ///
     /// `libctru` expects the user to manually keep the info data (in this case [`Wave`]) alive during playback.
@@ -529,8 +932,11 @@ impl Channel<'_> {
     /// // Provide your own audio data.
     /// let mut wave = Wave::new(audio_data, AudioFormat::PCM16Stereo, false);
     ///
-    /// // Clear the audio queue and stop playback.
-    /// channel_0.queue_wave(&mut wave);
+    /// // The channel's format must match the wave's format.
+    /// channel_0.set_format(AudioFormat::PCM16Stereo);
+    ///
+    /// // Add the wave to the queue and start playback.
+    /// channel_0.queue_wave(&mut wave)?;
     /// #
     /// # Ok(())
     /// # }
@@ -541,15 +947,149 @@ impl Channel<'_> {
     pub fn queue_wave<Buffer: LinearAllocation + AsRef<[u8]>>(
         &mut self,
         wave: &mut Wave<Buffer>,
+    ) -> std::result::Result<(), Error> {
+        if let Some(channel) = self.format() {
+            if channel != wave.format() {
+                return Err(Error::FormatMismatch {
+                    id: self.id,
+                    channel,
+                    wave: wave.format(),
+                });
+            }
+        }
+
+        self.queue_wave_unchecked(wave)
+    }
+
+    /// Configure the channel to play `wave`, and add it to the channel's queue.
+    ///
+    /// The channel's format and sample rate are set to the wave's [format](Wave::format) and [sample rate](Wave::sample_rate),
+    /// so that the wave is played at the right speed. Otherwise, this behaves like [`Channel::queue_wave()`].
+    ///
+    /// # Notes
+    ///
+    /// The format and sample rate are channel-wide settings, which also apply to the waves already in the channel's queue.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the wave has no sample rate, if it is already busy playing,
+    /// if it has no samples to play, or if the channel's queue is full.
+    /// In all of these cases, the channel's format and sample rate are left untouched.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #![feature(allocator_api)]
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// # use ctru::linear::LinearAllocator;
+    /// use ctru::services::ndsp::wave::Wave;
+    /// use ctru::services::ndsp::{AudioFormat, Ndsp};
+    /// let ndsp = Ndsp::new()?;
+    /// let mut channel_0 = ndsp.channel(0)?;
+    ///
+    /// # let audio_data: Box<[_], _> = Box::new_in([0u8; 96], LinearAllocator);
+    ///
+    /// // Provide your own audio data.
+    /// let mut wave = Wave::new(audio_data, AudioFormat::PCM16Stereo, false);
+    /// wave.set_sample_rate(44100.);
+    ///
+    /// channel_0.play_wave(&mut wave)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "ndspChnWaveBufAdd")]
+    pub fn play_wave<Buffer: LinearAllocation + AsRef<[u8]>>(
+        &mut self,
+        wave: &mut Wave<Buffer>,
+    ) -> std::result::Result<(), Error> {
+        let rate = wave
+            .sample_rate()
+            .ok_or(Error::MissingSampleRate(self.id))?;
+
+        self.check_wave(wave)?;
+
+        self.set_format(wave.format());
+        self.set_sample_rate(rate);
+
+        self.queue_wave_unchecked(wave)
+    }
+
+    /// Add a wave buffer to the channel's queue, without checking whether its format matches the channel's format.
+    ///
+    /// This behaves like [`Channel::queue_wave()`], and is only useful when the wave's data is meant to be
+    /// interpreted with the channel's format regardless of the format specified by the wave.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the wave is already busy playing, if it has no samples to play,
+    /// or if the channel's queue is full.
+    #[doc(alias = "ndspChnWaveBufAdd")]
+    pub fn queue_wave_unchecked<Buffer: LinearAllocation + AsRef<[u8]>>(
+        &mut self,
+        wave: &mut Wave<Buffer>,
+    ) -> std::result::Result<(), Error> {
+        self.check_wave(wave)?;
+
+        debug_assert!(
+            wave.get_buffer().is_empty()
+                || crate::linear::is_linear_ptr(wave.get_buffer().as_ptr()),
+            "wave buffer at {:p} queued on channel {} is not on the LINEAR memory",
+            wave.get_buffer().as_ptr(),
+            self.id
+        );
+
+        // Waves sharing the same coefficients (e.g. chunks of the same stream) don't need to set them again,
+        // which would also affect the waves still queued.
+        if let Some(coefficients) = wave.adpcm_coefficients() {
+            if self.state.adpcm_coefficients.as_ref() != Some(coefficients) {
+                self.set_adpcm_coefficients(coefficients);
+            }
+        }
+
+        wave.set_rejected(false);
+        wave.set_channel(self.id);
+
+        // The buffer may have been refilled since the wave was created (e.g. after `Wave::reset()`),
+        // so the CPU cache must be flushed for the DSP to read the new data.
+        unsafe {
+            let buf = wave.get_buffer();
+            let _r = ctru_sys::DSP_FlushDataCache(buf.as_ptr().cast(), buf.len() as u32);
+
+            ctru_sys::ndspChnWaveBufAdd(self.id.into(), &mut wave.raw_data);
+        }
+
+        // The sequence ID is assigned by `libctru` when the wave is queued.
+        self.state.queued.push_back(wave.raw_data.sequence_id);
+
+        Ok(())
+    }
+
+    /// Check whether `wave` can be added to the channel's queue, without changing any of the channel's settings.
+    fn check_wave<Buffer: LinearAllocation + AsRef<[u8]>>(
+        &mut self,
+        wave: &mut Wave<Buffer>,
     ) -> std::result::Result<(), Error> {
         match wave.status() {
             Status::Playing | Status::Queued => return Err(Error::WaveBusy(self.id)),
             _ => (),
         }
 
-        wave.set_channel(self.id);
+        // `ndspChnWaveBufAdd` silently ignores empty buffers, which would otherwise never leave their current status.
+        if wave.raw_data.nsamples == 0 {
+            wave.set_rejected(true);
+
+            return Err(Error::WaveRejected(self.id));
+        }
 
-        unsafe { ctru_sys::ndspChnWaveBufAdd(self.id.into(), &mut wave.raw_data) };
+        let pending: VecDeque<u16> = self.state.pending(self.id).copied().collect();
+        self.state.queued = pending;
+
+        if self.state.queued.len() >= MAX_QUEUED_WAVES {
+            return Err(Error::QueueFull(self.id));
+        }
 
         Ok(())
     }
@@ -649,16 +1189,287 @@ impl AudioFormat {
     ///
     /// - 8 bit mono formats return 1 (byte)
     /// - 16 bit stereo (dual-channel) formats return 4 (bytes)
+    ///
+    /// # Notes
+    ///
+    /// ADPCM samples take half a byte (plus the frame headers), so [`AudioFormat::Adpcm`] rounds up to 1 byte.
+    /// Use [`AudioFormat::byte_len()`] and [`AudioFormat::sample_count()`] for exact conversions with any format.
     pub const fn size(self) -> usize {
         match self {
-            Self::PCM8Mono => 1,
+            Self::PCM8Mono | Self::Adpcm => 1,
             Self::PCM16Mono | Self::PCM8Stereo => 2,
             Self::PCM16Stereo => 4,
         }
     }
+
+    /// Returns the amount of bytes needed to store `samples` samples.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// use ctru::services::ndsp::AudioFormat;
+    ///
+    /// assert_eq!(AudioFormat::PCM16Stereo.byte_len(100), 400);
+    ///
+    /// // Two full ADPCM frames, and a third one holding 2 samples (header included).
+    /// assert_eq!(AudioFormat::Adpcm.byte_len(30), 18);
+    /// ```
+    pub const fn byte_len(self, samples: usize) -> usize {
+        match self {
+            Self::Adpcm => {
+                let partial = samples % ADPCM_SAMPLES_PER_FRAME;
+                let partial_bytes = if partial == 0 {
+                    0
+                } else {
+                    1 + partial.div_ceil(2)
+                };
+
+                samples / ADPCM_SAMPLES_PER_FRAME * ADPCM_FRAME_SIZE + partial_bytes
+            }
+            _ => samples * self.size(),
+        }
+    }
+
+    /// Returns the amount of whole samples stored in `bytes` bytes.
+    pub const fn sample_count(self, bytes: usize) -> usize {
+        match self {
+            Self::Adpcm => {
+                let partial = bytes % ADPCM_FRAME_SIZE;
+                let partial_samples = if partial == 0 { 0 } else { (partial - 1) * 2 };
+
+                bytes / ADPCM_FRAME_SIZE * ADPCM_SAMPLES_PER_FRAME + partial_samples
+            }
+            _ => bytes / self.size(),
+        }
+    }
+
+    /// Convert a raw `NDSP_FORMAT` value, ignoring the flags unrelated to the sample layout.
+    fn from_raw(value: u16) -> Option<Self> {
+        // Bits 0-1 hold the channel count, and bits 2-3 the encoding.
+        [
+            Self::PCM8Mono,
+            Self::PCM16Mono,
+            Self::PCM8Stereo,
+            Self::PCM16Stereo,
+            Self::Adpcm,
+        ]
+        .into_iter()
+        .find(|&format| u16::from(format) == value & 0xF)
+    }
 }
 
 impl AudioMix {
+    /// Stereo mix: the channel's left and right samples are played at full volume on the front left and front right outputs.
+    ///
+    /// This is the same as [`AudioMix::default()`].
+    pub const STEREO: Self = Self {
+        raw: [1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    };
+
+    /// Mix for centered mono sounds: the channel is played on both front outputs at `1/√2` (about 70%) volume,
+    /// so that its perceived loudness matches a sound panned fully to one side.
+    pub const MONO_CENTERED: Self = Self {
+        raw: [
+            std::f32::consts::FRAC_1_SQRT_2,
+            std::f32::consts::FRAC_1_SQRT_2,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+        ],
+    };
+
+    /// Surround mix: the channel's left and right samples are played at full volume on both the front and the back outputs.
+    ///
+    /// The back outputs are only audible when the [`OutputMode`] is set to [`OutputMode::Surround`].
+    pub const SURROUND: Self = Self {
+        raw: [1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    };
+
+    /// Creates a surround mix with the given front and back volumes, and no auxiliary sends.
+    ///
+    /// The back outputs are only audible when the [`OutputMode`] is set to [`OutputMode::Surround`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// use ctru::services::ndsp::AudioMix;
+    ///
+    /// // A sound coming from the back right.
+    /// let mix = AudioMix::surround(0.0, 0.4, 0.2, 1.0);
+    ///
+    /// assert_eq!(mix.front(), (0.0, 0.4));
+    /// assert_eq!(mix.back(), (0.2, 1.0));
+    /// ```
+    pub const fn surround(
+        front_left: f32,
+        front_right: f32,
+        back_left: f32,
+        back_right: f32,
+    ) -> Self {
+        Self {
+            raw: [
+                front_left,
+                front_right,
+                back_left,
+                back_right,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+            ],
+        }
+    }
+
+    /// Returns this mix with the given front and back volumes (left and right channel) for the specified auxiliary output device.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// use ctru::services::ndsp::{AudioMix, AuxDevice};
+    ///
+    /// let mix = AudioMix::surround(1.0, 1.0, 0.5, 0.5)
+    ///     .with_aux(AuxDevice::Zero, (0.3, 0.3), (0.0, 0.0))
+    ///     .with_aux(AuxDevice::One, (0.0, 0.0), (0.1, 0.1));
+    ///
+    /// assert_eq!(mix.aux_front(AuxDevice::Zero), (0.3, 0.3));
+    /// assert_eq!(mix.aux_back(AuxDevice::One), (0.1, 0.1));
+    /// ```
+    pub fn with_aux(mut self, id: AuxDevice, front: (f32, f32), back: (f32, f32)) -> Self {
+        self.set_aux_front(front.0, front.1, id);
+        self.set_aux_back(back.0, back.1, id);
+
+        self
+    }
+
+    /// Scale all volumes of the mix so that none of them is larger than 1, returning the applied scale factor.
+    ///
+    /// The relative balance between the outputs is kept. Mixes whose volumes are already within 0 and 1 are left untouched,
+    /// and a scale factor of 1 is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// use ctru::services::ndsp::AudioMix;
+    ///
+    /// let mut mix = AudioMix::surround(2.0, 2.0, 1.0, 1.0);
+    ///
+    /// assert_eq!(mix.normalize(), 0.5);
+    /// assert_eq!(mix.front(), (1.0, 1.0));
+    /// assert_eq!(mix.back(), (0.5, 0.5));
+    /// ```
+    pub fn normalize(&mut self) -> f32 {
+        // `f32::max()` ignores NaN values.
+        let max = self.raw.iter().copied().fold(0.0, f32::max);
+
+        if max <= 1.0 {
+            return 1.0;
+        }
+
+        let scale = max.recip();
+        for value in self.raw.iter_mut() {
+            *value *= scale;
+        }
+
+        scale
+    }
+
+    /// Returns this mix with an additional send to the given auxiliary output device.
+    ///
+    /// The front and back volumes of the mix are copied (multiplied by `volume`) to the front and back volumes of the auxiliary device,
+    /// so that the effects applied to it (e.g. a reverb) are fed the same signal as the main output.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// use ctru::services::ndsp::{AudioMix, AuxDevice};
+    ///
+    /// // Full volume on the main output, and a 30% send to the first aux device.
+    /// let mix = AudioMix::STEREO.with_aux_send(AuxDevice::Zero, 0.3);
+    ///
+    /// assert_eq!(mix.aux_front(AuxDevice::Zero), (0.3, 0.3));
+    /// assert_eq!(mix.aux_back(AuxDevice::Zero), (0.0, 0.0));
+    /// ```
+    pub fn with_aux_send(mut self, id: AuxDevice, volume: f32) -> Self {
+        let (front_left, front_right) = self.front();
+        let (back_left, back_right) = self.back();
+
+        self.set_aux_front(front_left * volume, front_right * volume, id);
+        self.set_aux_back(back_left * volume, back_right * volume, id);
+
+        self
+    }
+
+    /// Creates a new [`AudioMix`] from the given volumes, checking that all of them are valid.
+    ///
+    /// # Notes
+    ///
+    /// The volumes are laid out in the [raw representation](AudioMix::as_raw) as follows:
+    ///
+    /// | Index | Volume |
+    /// |-------|--------|
+    /// | 0-1   | `front` (left, right) |
+    /// | 2-3   | `back` (left, right) |
+    /// | 4-7   | `aux[0]`: front left, front right, back left, back right of [`AuxDevice::Zero`] |
+    /// | 8-11  | `aux[1]`: front left, front right, back left, back right of [`AuxDevice::One`] |
+    ///
+    /// Use [`AudioMix::from()`] to build a mix from raw values without any checks.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any of the volumes is NaN or negative.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// use ctru::services::ndsp::{AudioMix, MixError};
+    ///
+    /// let mix = AudioMix::try_new((1.0, 1.0), (0.5, 0.5), [[0.0; 4]; 2]).unwrap();
+    /// assert_eq!(mix.back(), (0.5, 0.5));
+    ///
+    /// let invalid = AudioMix::try_new((1.0, f32::NAN), (0.0, 0.0), [[0.0; 4]; 2]);
+    /// assert_eq!(invalid, Err(MixError::NotANumber(1)));
+    /// ```
+    pub fn try_new(
+        front: (f32, f32),
+        back: (f32, f32),
+        aux: [[f32; 4]; 2],
+    ) -> Result<Self, MixError> {
+        let [aux_0, aux_1] = aux;
+
+        let raw = [
+            front.0, front.1, back.0, back.1, aux_0[0], aux_0[1], aux_0[2], aux_0[3], aux_1[0],
+            aux_1[1], aux_1[2], aux_1[3],
+        ];
+
+        for (index, value) in raw.iter().enumerate() {
+            if value.is_nan() {
+                return Err(MixError::NotANumber(index));
+            }
+            if *value < 0.0 {
+                return Err(MixError::Negative(index));
+            }
+        }
+
+        Ok(Self { raw })
+    }
+
     /// Creates a new [`AudioMix`] with all volumes set to 0.
     pub fn zeroed() -> Self {
         Self { raw: [0.; 12] }
@@ -750,10 +1561,7 @@ impl AudioMix {
 impl Default for AudioMix {
     /// Returns an [`AudioMix`] object with "front left" and "front right" volumes set to 100%, and all other volumes set to 0%.
     fn default() -> Self {
-        let mut mix = AudioMix::zeroed();
-        mix.set_front(1.0, 1.0);
-
-        mix
+        Self::STEREO
     }
 }
 
@@ -770,12 +1578,35 @@ impl fmt::Display for Error {
             Self::ChannelAlreadyInUse(id) => write!(f, "audio Channel with ID {id} is already being used. Drop the other instance if you want to use it here"),
             Self::WaveBusy(id) => write!(f, "the selected Wave is busy playing on channel {id}"),
             Self::SampleCountOutOfBounds(samples_requested, max_samples) => write!(f, "the sample count requested is too big (requested = {samples_requested}, maximum = {max_samples})"),
+            Self::FormatMismatch { id, channel, wave } => write!(f, "the selected Wave has format {wave:?}, but channel {id} is set to play {channel:?}"),
+            Self::QueueFull(id) => write!(f, "the queue of channel {id} is full, wait for a queued Wave to finish playing"),
+            Self::WaveRejected(id) => write!(f, "the selected Wave was rejected by channel {id} since it has no samples to play"),
+            Self::MissingSampleRate(id) => write!(f, "the selected Wave has no sample rate to play it with on channel {id}"),
         }
     }
 }
 
 impl error::Error for Error {}
 
+impl fmt::Display for MixError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotANumber(index) => write!(f, "the volume at index {index} of the mix is NaN"),
+            Self::Negative(index) => {
+                write!(f, "the volume at index {index} of the mix is negative")
+            }
+        }
+    }
+}
+
+impl error::Error for MixError {}
+
+impl Drop for Channel<'_> {
+    fn drop(&mut self) {
+        FRAME_SYNC.callbacks.lock().unwrap()[self.id as usize] = None;
+    }
+}
+
 impl Drop for Ndsp {
     #[doc(alias = "ndspExit")]
     fn drop(&mut self) {
@@ -788,3 +1619,48 @@ impl Drop for Ndsp {
 from_impl!(InterpolationType, ctru_sys::ndspInterpType);
 from_impl!(OutputMode, ctru_sys::ndspOutputMode);
 from_impl!(AudioFormat, u16);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adpcm_byte_len() {
+        // A 1 second clip at 32728 Hz: 2337 full frames and a partial one with 10 samples.
+        let samples = 32728;
+        let bytes = AudioFormat::Adpcm.byte_len(samples);
+
+        assert_eq!(bytes, 2337 * 8 + 1 + 5);
+        assert_eq!(AudioFormat::Adpcm.sample_count(bytes), samples);
+
+        // A partial frame holding only its header has no samples.
+        assert_eq!(AudioFormat::Adpcm.sample_count(9), 14);
+        assert_eq!(AudioFormat::Adpcm.byte_len(0), 0);
+
+        assert_eq!(AudioFormat::PCM16Stereo.byte_len(samples), samples * 4);
+        assert_eq!(AudioFormat::PCM16Stereo.sample_count(samples * 4), samples);
+    }
+
+    #[test]
+    fn mix_normalize() {
+        let mut mix = AudioMix::surround(1.0, 0.5, 0.0, 0.25).with_aux(
+            AuxDevice::One,
+            (0.1, 0.1),
+            (0.0, 1.0),
+        );
+        let before = mix;
+
+        assert_eq!(mix.normalize(), 1.0);
+        assert_eq!(mix, before);
+
+        let mut mix = AudioMix::surround(4.0, 2.0, 0.0, 0.0).with_aux(
+            AuxDevice::Zero,
+            (1.0, 1.0),
+            (0.0, 0.0),
+        );
+
+        assert_eq!(mix.normalize(), 0.25);
+        assert_eq!(mix.front(), (1.0, 0.5));
+        assert_eq!(mix.aux_front(AuxDevice::Zero), (0.25, 0.25));
+    }
+}
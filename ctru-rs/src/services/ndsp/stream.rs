@@ -0,0 +1,192 @@
+//! Audio streaming.
+//!
+//! This module contains [`ChannelStream`], which plays long (or endless) audio on a [`Channel`] by decoding it in small chunks,
+//! instead of keeping the whole track in memory.
+
+use super::wave::Wave;
+use super::{AudioFormat, Channel, Error};
+use crate::linear::LinearAllocator;
+
+type StreamBuffer = Box<[u8], LinearAllocator>;
+
+/// Double-buffered audio stream playing on a [`Channel`].
+///
+/// The stream owns two buffers on the [LINEAR memory](crate::linear), which are queued alternately on the channel:
+/// while one of them is playing, the other one is refilled by the stream's callback.
+///
+/// The callback is called with a buffer to fill with audio data (in the stream's [`AudioFormat`]),
+/// and returns the number of bytes written.
+///
+/// # Underruns
+///
+/// If the callback returns 0 (e.g. because the decoder isn't fast enough, or the track ended), no buffer is queued:
+/// the channel simply stops playing once the queued data is over, instead of repeating old data.
+/// The callback is called again on each [`ChannelStream::poll()`], and playback resumes as soon as it provides new data.
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru::services::ndsp::stream::ChannelStream;
+/// use ctru::services::ndsp::{AudioFormat, Ndsp};
+/// let ndsp = Ndsp::new()?;
+/// let mut channel = ndsp.channel(0)?;
+/// channel.set_sample_rate(32000.0);
+///
+/// // A 440 Hz square wave.
+/// let mut phase = 0u32;
+/// let mut stream = ChannelStream::new(channel, AudioFormat::PCM16Mono, 0x1000, |buf| {
+///     for sample in buf.chunks_exact_mut(2) {
+///         let value: i16 = if phase % 32000 < 16000 { 0x2000 } else { -0x2000 };
+///         sample.copy_from_slice(&value.to_le_bytes());
+///         phase += 440;
+///     }
+///
+///     buf.len()
+/// });
+///
+/// loop {
+///     stream.poll()?;
+/// #   break;
+/// }
+///
+/// stream.stop();
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub struct ChannelStream<'ndsp, F: FnMut(&mut [u8]) -> usize> {
+    // The waves must be dropped after `Drop::drop` clears the channel's queue, and before the channel itself.
+    waves: [Wave<StreamBuffer>; 2],
+    channel: Channel<'ndsp>,
+    fill: F,
+    /// Index of the wave to be queued next.
+    next: usize,
+    starved: bool,
+    stopped: bool,
+}
+
+impl<'ndsp, F: FnMut(&mut [u8]) -> usize> ChannelStream<'ndsp, F> {
+    /// Create a new stream playing on `channel`, with two buffers of `buffer_size` bytes each.
+    ///
+    /// The channel's format is set to `format`, and its queue is cleared. Nothing is played until the first [`ChannelStream::poll()`].
+    ///
+    /// # Notes
+    ///
+    /// `buffer_size` is rounded down to a whole number of samples. Smaller buffers reduce the latency of the stream,
+    /// but must be refilled more often: each buffer should hold at least a frame's worth of audio (about 16 ms).
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `buffer_size` is smaller than a single sample of `format`.
+    pub fn new(
+        mut channel: Channel<'ndsp>,
+        format: AudioFormat,
+        buffer_size: usize,
+        fill: F,
+    ) -> Self {
+        let buffer_size = format.byte_len(format.sample_count(buffer_size));
+        assert!(
+            buffer_size > 0,
+            "stream buffers must hold at least one sample"
+        );
+
+        channel.clear_queue();
+        channel.set_format(format);
+
+        let new_wave = || {
+            let mut buffer = Vec::with_capacity_in(buffer_size, LinearAllocator);
+            buffer.resize(buffer_size, 0);
+
+            Wave::new(buffer.into_boxed_slice(), format, false)
+        };
+
+        Self {
+            waves: [new_wave(), new_wave()],
+            channel,
+            fill,
+            next: 0,
+            starved: false,
+            stopped: false,
+        }
+    }
+
+    /// Refill and queue the buffers which have finished playing.
+    ///
+    /// This should be called at least once per frame. If the stream was [stopped](ChannelStream::stop), this does nothing.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a buffer couldn't be queued on the channel.
+    pub fn poll(&mut self) -> Result<(), Error> {
+        if self.stopped {
+            return Ok(());
+        }
+
+        // Buffers are queued strictly alternately, so at most both of them are refilled.
+        for _ in 0..self.waves.len() {
+            let wave = &mut self.waves[self.next];
+
+            // The buffer is still waiting to be played (or playing).
+            if wave.reset().is_err() {
+                break;
+            }
+
+            let buffer = wave.get_buffer_mut()?;
+            let written = (self.fill)(buffer).min(buffer.len());
+
+            // Trailing bytes not making up a whole sample can't be played either.
+            let samples = wave.format().sample_count(written);
+
+            self.starved = samples == 0;
+            if self.starved {
+                break;
+            }
+
+            wave.set_sample_count(samples)?;
+
+            self.channel.queue_wave(wave)?;
+
+            self.next = (self.next + 1) % self.waves.len();
+        }
+
+        Ok(())
+    }
+
+    /// Stop the stream, clearing the channel's queue.
+    ///
+    /// The callback won't be called anymore, and [`ChannelStream::poll()`] won't queue any more data.
+    #[doc(alias = "ndspChnWaveBufClear")]
+    pub fn stop(&mut self) {
+        self.stopped = true;
+        self.channel.clear_queue();
+    }
+
+    /// Returns `true` if the stream was [stopped](ChannelStream::stop).
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Returns `true` if the callback didn't provide a single whole sample on the last [`ChannelStream::poll()`].
+    ///
+    /// The channel stops playing once the data queued before the underrun is over.
+    pub fn is_starved(&self) -> bool {
+        self.starved
+    }
+
+    /// Returns the channel the stream is playing on, e.g. to change its volume or mix.
+    ///
+    /// Changing the channel's format makes [`ChannelStream::poll()`] fail with [`Error::FormatMismatch`].
+    pub fn channel_mut(&mut self) -> &mut Channel<'ndsp> {
+        &mut self.channel
+    }
+}
+
+impl<F: FnMut(&mut [u8]) -> usize> Drop for ChannelStream<'_, F> {
+    fn drop(&mut self) {
+        // Make sure the DSP stops reading the buffers before they are freed.
+        self.channel.clear_queue();
+    }
+}
@@ -0,0 +1,542 @@
+//! WAV file encoding and decoding.
+//!
+//! This module contains a minimal encoder for RIFF/WAVE files, useful to save recorded or generated audio samples to the SD card,
+//! and a decoder loading PCM WAV files (e.g. from the RomFS) into playable [`Wave`]s.
+
+use super::wave::Wave;
+use super::AudioFormat;
+use crate::linear::LinearAllocator;
+
+use std::error;
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// Size of the RIFF/WAVE header written by [`WavWriter`], in bytes.
+const HEADER_SIZE: u32 = 44;
+
+/// Format tag of uncompressed integer PCM data.
+const WAVE_FORMAT_PCM: u16 = 1;
+
+/// Format tag of the extensible format chunk, which stores the actual format tag in its sub-format GUID.
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Size of the extensible format chunk, the largest one describing PCM data, in bytes.
+///
+/// Any further bytes of a format chunk are skipped without being read into memory.
+const MAX_FORMAT_SIZE: usize = 40;
+
+/// Errors returned by [`Wave::from_wav_reader()`].
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum WavError {
+    /// Reading the file failed, or the file ended before the end of a chunk.
+    Io(io::Error),
+    /// The file doesn't start with a RIFF/WAVE header.
+    NotWav,
+    /// The format chunk is missing, or comes after the data chunk.
+    MissingFormat,
+    /// The format chunk is shorter than the 16 bytes describing a PCM format.
+    InvalidFormat,
+    /// The samples are compressed or stored as floating point, with the contained format tag.
+    ///
+    /// Only uncompressed integer PCM data can be played.
+    UnsupportedEncoding(u16),
+    /// The samples are PCM data, but their layout isn't supported by the DSP.
+    ///
+    /// Only 8 and 16 bit samples with 1 or 2 channels are supported.
+    UnsupportedLayout {
+        /// Amount of audio channels.
+        channels: u16,
+        /// Size of each sample, in bits.
+        bits_per_sample: u16,
+    },
+    /// The file has no data chunk.
+    MissingData,
+    /// The data chunk is too large to fit in the LINEAR memory.
+    TooLarge,
+}
+
+impl Wave<Box<[u8], LinearAllocator>> {
+    /// Decode a PCM WAV file into a new wave on the [LINEAR memory](crate::linear).
+    ///
+    /// The wave's [`AudioFormat`] and [sample rate](Wave::sample_rate) are taken from the format chunk of the file.
+    /// The data chunk is read whole (chunks after it are ignored), and 8 bit samples are converted
+    /// from the unsigned representation used by WAV files to the signed one expected by the DSP.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if reading fails, if the file isn't a valid WAV file,
+    /// if its samples aren't 8 or 16 bit integer PCM data with 1 or 2 channels,
+    /// or if there isn't enough LINEAR memory left to hold its data chunk.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use std::fs::File;
+    /// use ctru::services::ndsp::wave::Wave;
+    /// use ctru::services::ndsp::Ndsp;
+    /// use ctru::services::romfs::RomFS;
+    ///
+    /// let _romfs = RomFS::new()?;
+    /// let ndsp = Ndsp::new()?;
+    ///
+    /// let mut wave = Wave::from_wav_reader(File::open("romfs:/sound.wav")?)?;
+    ///
+    /// let mut channel = ndsp.channel(0)?;
+    /// channel.play_wave(&mut wave)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_wav_reader<R: Read>(mut reader: R) -> Result<Self, WavError> {
+        let mut riff = [0; 12];
+        reader.read_exact(&mut riff).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => WavError::NotWav,
+            _ => WavError::Io(e),
+        })?;
+
+        if &riff[0..4] != b"RIFF" || &riff[8..12] != b"WAVE" {
+            return Err(WavError::NotWav);
+        }
+
+        let mut format = None;
+
+        loop {
+            let mut header = [0; 8];
+            reader.read_exact(&mut header).map_err(|e| match e.kind() {
+                io::ErrorKind::UnexpectedEof => WavError::MissingData,
+                _ => WavError::Io(e),
+            })?;
+
+            let size = u32::from_le_bytes(header[4..8].try_into().unwrap());
+            // Chunks are padded to an even size.
+            let padded_size = u64::from(size) + u64::from(size % 2);
+
+            match &header[0..4] {
+                b"fmt " => {
+                    let len = (size as usize).min(MAX_FORMAT_SIZE);
+
+                    let mut chunk = vec![0; len];
+                    reader.read_exact(&mut chunk)?;
+                    io::copy(
+                        &mut (&mut reader).take(padded_size - len as u64),
+                        &mut io::sink(),
+                    )?;
+
+                    format = Some(parse_format(&chunk)?);
+                }
+                b"data" => {
+                    let (audio_format, sample_rate) = format.ok_or(WavError::MissingFormat)?;
+
+                    // Trailing bytes not making up a whole sample are dropped.
+                    let len = audio_format.byte_len(audio_format.sample_count(size as usize));
+
+                    // The size comes straight from the file, so a failed allocation must not abort the application.
+                    let mut buffer = Vec::new_in(LinearAllocator);
+                    buffer
+                        .try_reserve_exact(len)
+                        .map_err(|_| WavError::TooLarge)?;
+                    buffer.resize(len, 0);
+                    reader.read_exact(&mut buffer)?;
+
+                    if matches!(
+                        audio_format,
+                        AudioFormat::PCM8Mono | AudioFormat::PCM8Stereo
+                    ) {
+                        for sample in buffer.iter_mut() {
+                            *sample ^= 0x80;
+                        }
+                    }
+
+                    let mut wave = Wave::new(buffer.into_boxed_slice(), audio_format, false);
+                    wave.set_sample_rate(sample_rate as f32);
+
+                    return Ok(wave);
+                }
+                _ => {
+                    io::copy(&mut (&mut reader).take(padded_size), &mut io::sink())?;
+                }
+            }
+        }
+    }
+}
+
+/// Parse the contents of a format chunk, returning the audio format and sample rate it describes.
+fn parse_format(chunk: &[u8]) -> Result<(AudioFormat, u32), WavError> {
+    if chunk.len() < 16 {
+        return Err(WavError::InvalidFormat);
+    }
+
+    let read_u16 = |offset: usize| u16::from_le_bytes([chunk[offset], chunk[offset + 1]]);
+
+    let mut tag = read_u16(0);
+    let channels = read_u16(2);
+    let sample_rate = u32::from_le_bytes(chunk[4..8].try_into().unwrap());
+    let bits_per_sample = read_u16(14);
+
+    // The actual format tag is stored in the first bytes of the sub-format GUID.
+    if tag == WAVE_FORMAT_EXTENSIBLE {
+        if chunk.len() < 26 {
+            return Err(WavError::InvalidFormat);
+        }
+
+        tag = read_u16(24);
+    }
+
+    if tag != WAVE_FORMAT_PCM {
+        return Err(WavError::UnsupportedEncoding(tag));
+    }
+
+    let audio_format = match (channels, bits_per_sample) {
+        (1, 8) => AudioFormat::PCM8Mono,
+        (2, 8) => AudioFormat::PCM8Stereo,
+        (1, 16) => AudioFormat::PCM16Mono,
+        (2, 16) => AudioFormat::PCM16Stereo,
+        _ => {
+            return Err(WavError::UnsupportedLayout {
+                channels,
+                bits_per_sample,
+            })
+        }
+    };
+
+    Ok((audio_format, sample_rate))
+}
+
+/// Encoder writing PCM audio samples to a RIFF/WAVE file.
+///
+/// The header is written as soon as the encoder is created, while the size fields
+/// are patched in by [`WavWriter::finalize()`] once all samples have been written.
+///
+/// # Notes
+///
+/// Samples are always provided as `i16`. For 8 bit [`AudioFormat`]s, only the upper byte of each sample is kept
+/// (converted to the unsigned representation used by WAV files).
+/// For stereo formats, samples are expected to be interleaved (left, right, left, right, ...).
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use std::fs::File;
+/// use ctru::services::ndsp::{AudioFormat, WavWriter};
+///
+/// let file = File::create("sdmc:/recording.wav")?;
+/// let mut writer = WavWriter::new(file, AudioFormat::PCM16Mono, 22050)?;
+///
+/// writer.write_samples(&[0, 1000, 2000, 1000, 0, -1000, -2000, -1000])?;
+///
+/// writer.finalize()?;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub struct WavWriter<W: Write + Seek> {
+    inner: W,
+    format: AudioFormat,
+    data_size: u32,
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    /// Create a new encoder writing to `file`, and write the WAV header for the given format and sample rate.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if writing the header fails, if `format` is [`AudioFormat::Adpcm`]
+    /// (which can't be stored in WAV files), or if `rate` is too high for the byte rate to fit in the header.
+    pub fn new(mut file: W, format: AudioFormat, rate: u32) -> io::Result<Self> {
+        let channels: u16 = match format {
+            AudioFormat::PCM8Mono | AudioFormat::PCM16Mono => 1,
+            AudioFormat::PCM8Stereo | AudioFormat::PCM16Stereo => 2,
+            AudioFormat::Adpcm => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "DSP-ADPCM data can't be stored in WAV files",
+                ))
+            }
+        };
+        let block_align = format.size() as u16;
+        let bits_per_sample = block_align / channels * 8;
+        let byte_rate = rate.checked_mul(u32::from(block_align)).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "WAV sample rate too high")
+        })?;
+
+        let mut header = Vec::with_capacity(HEADER_SIZE as usize);
+        header.extend_from_slice(b"RIFF");
+        // Patched by `finalize()`.
+        header.extend_from_slice(&(HEADER_SIZE - 8).to_le_bytes());
+        header.extend_from_slice(b"WAVE");
+
+        header.extend_from_slice(b"fmt ");
+        header.extend_from_slice(&16u32.to_le_bytes());
+        // PCM encoding.
+        header.extend_from_slice(&1u16.to_le_bytes());
+        header.extend_from_slice(&channels.to_le_bytes());
+        header.extend_from_slice(&rate.to_le_bytes());
+        header.extend_from_slice(&byte_rate.to_le_bytes());
+        header.extend_from_slice(&block_align.to_le_bytes());
+        header.extend_from_slice(&bits_per_sample.to_le_bytes());
+
+        header.extend_from_slice(b"data");
+        // Patched by `finalize()`.
+        header.extend_from_slice(&0u32.to_le_bytes());
+
+        file.write_all(&header)?;
+
+        Ok(Self {
+            inner: file,
+            format,
+            data_size: 0,
+        })
+    }
+
+    /// Returns the audio format of the samples being written.
+    pub fn format(&self) -> AudioFormat {
+        self.format
+    }
+
+    /// Append `samples` to the data section of the file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if writing to the file fails, if `samples` doesn't hold whole frames
+    /// (i.e. an odd number of samples for a stereo format), or if the data section would grow larger than
+    /// what a WAV file can represent (4 GiB).
+    pub fn write_samples(&mut self, samples: &[i16]) -> io::Result<()> {
+        let channels = match self.format {
+            AudioFormat::PCM8Stereo | AudioFormat::PCM16Stereo => 2,
+            _ => 1,
+        };
+
+        if samples.len() % channels != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "stereo samples must come in left/right pairs",
+            ));
+        }
+
+        let bytes: Vec<u8> = match self.format {
+            AudioFormat::PCM8Mono | AudioFormat::PCM8Stereo => samples
+                .iter()
+                .map(|&s| ((s >> 8) as u8).wrapping_add(128))
+                .collect(),
+            AudioFormat::PCM16Mono | AudioFormat::PCM16Stereo => {
+                samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+            }
+            AudioFormat::Adpcm => unreachable!("rejected by `WavWriter::new()`"),
+        };
+
+        let new_size = u32::try_from(bytes.len())
+            .ok()
+            .and_then(|len| self.data_size.checked_add(len))
+            .filter(|size| size.checked_add(HEADER_SIZE - 8).is_some())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "WAV data too large"))?;
+
+        self.inner.write_all(&bytes)?;
+        self.data_size = new_size;
+
+        Ok(())
+    }
+
+    /// Patch the RIFF and data size fields in the header, flush the file and return it.
+    ///
+    /// # Notes
+    ///
+    /// If the encoder is dropped without calling this function, the resulting file will have
+    /// an empty data section according to its header.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if seeking or writing to the file fails.
+    pub fn finalize(mut self) -> io::Result<W> {
+        let end = self.inner.stream_position()?;
+
+        self.inner.seek(SeekFrom::Start(4))?;
+        self.inner
+            .write_all(&(HEADER_SIZE - 8 + self.data_size).to_le_bytes())?;
+
+        self.inner
+            .seek(SeekFrom::Start(u64::from(HEADER_SIZE) - 4))?;
+        self.inner.write_all(&self.data_size.to_le_bytes())?;
+
+        self.inner.seek(SeekFrom::Start(end))?;
+        self.inner.flush()?;
+
+        Ok(self.inner)
+    }
+}
+
+impl From<io::Error> for WavError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl fmt::Display for WavError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read the WAV file: {e}"),
+            Self::NotWav => write!(f, "the file is not a RIFF/WAVE file"),
+            Self::MissingFormat => write!(f, "the WAV file has no format chunk before its data"),
+            Self::InvalidFormat => write!(f, "the format chunk of the WAV file is too short"),
+            Self::UnsupportedEncoding(tag) => write!(f, "the WAV file uses encoding {tag:#06x}, but only uncompressed integer PCM is supported"),
+            Self::UnsupportedLayout { channels, bits_per_sample } => write!(f, "the WAV file has {channels} channels of {bits_per_sample} bit samples, but only 1 or 2 channels of 8 or 16 bit samples are supported"),
+            Self::MissingData => write!(f, "the WAV file has no data chunk"),
+            Self::TooLarge => write!(f, "the data chunk of the WAV file doesn't fit in the LINEAR memory"),
+        }
+    }
+}
+
+impl error::Error for WavError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    #[test]
+    fn wav_pcm16_stereo() {
+        let mut writer =
+            WavWriter::new(Cursor::new(Vec::new()), AudioFormat::PCM16Stereo, 32000).unwrap();
+        writer.write_samples(&[1, -1, 0x1234, 0x7FFF]).unwrap();
+
+        let data = writer.finalize().unwrap().into_inner();
+
+        assert_eq!(data.len(), 44 + 8);
+        assert_eq!(&data[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(data[4..8].try_into().unwrap()), 36 + 8);
+        assert_eq!(&data[8..16], b"WAVEfmt ");
+        // Channels
+        assert_eq!(u16::from_le_bytes(data[22..24].try_into().unwrap()), 2);
+        // Sample rate
+        assert_eq!(u32::from_le_bytes(data[24..28].try_into().unwrap()), 32000);
+        // Byte rate
+        assert_eq!(u32::from_le_bytes(data[28..32].try_into().unwrap()), 128000);
+        // Block align and bits per sample
+        assert_eq!(u16::from_le_bytes(data[32..34].try_into().unwrap()), 4);
+        assert_eq!(u16::from_le_bytes(data[34..36].try_into().unwrap()), 16);
+        assert_eq!(&data[36..40], b"data");
+        assert_eq!(u32::from_le_bytes(data[40..44].try_into().unwrap()), 8);
+        assert_eq!(&data[44..], &[1, 0, 0xFF, 0xFF, 0x34, 0x12, 0xFF, 0x7F]);
+    }
+
+    #[test]
+    fn wav_pcm8_mono() {
+        let mut writer =
+            WavWriter::new(Cursor::new(Vec::new()), AudioFormat::PCM8Mono, 8000).unwrap();
+        writer.write_samples(&[i16::MIN, 0]).unwrap();
+        writer.write_samples(&[i16::MAX]).unwrap();
+
+        let data = writer.finalize().unwrap().into_inner();
+
+        assert_eq!(u32::from_le_bytes(data[4..8].try_into().unwrap()), 36 + 3);
+        assert_eq!(u16::from_le_bytes(data[22..24].try_into().unwrap()), 1);
+        assert_eq!(u16::from_le_bytes(data[34..36].try_into().unwrap()), 8);
+        assert_eq!(u32::from_le_bytes(data[40..44].try_into().unwrap()), 3);
+        assert_eq!(&data[44..], &[0, 128, 255]);
+    }
+
+    #[test]
+    fn wav_writer_rejects_invalid_input() {
+        assert_eq!(
+            WavWriter::new(Cursor::new(Vec::new()), AudioFormat::PCM16Stereo, u32::MAX)
+                .err()
+                .map(|e| e.kind()),
+            Some(io::ErrorKind::InvalidInput)
+        );
+
+        let mut writer =
+            WavWriter::new(Cursor::new(Vec::new()), AudioFormat::PCM8Stereo, 8000).unwrap();
+
+        assert_eq!(
+            writer.write_samples(&[0, 0, 0]).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+
+        // Nothing was written.
+        assert_eq!(writer.finalize().unwrap().into_inner().len(), 44);
+    }
+
+    #[test]
+    fn wav_decode_roundtrip() {
+        let mut writer =
+            WavWriter::new(Cursor::new(Vec::new()), AudioFormat::PCM16Stereo, 22050).unwrap();
+        writer
+            .write_samples(&[1, -1, 0x1234, 0x7FFF, 0, 0])
+            .unwrap();
+
+        let mut data = writer.finalize().unwrap().into_inner();
+        // An odd-sized chunk before the data, which must be skipped along with its padding byte.
+        let list: &[u8] = b"LIST\x03\0\0\0abc\0";
+        data.splice(36..36, list.iter().copied());
+
+        let wave = Wave::from_wav_reader(Cursor::new(data)).unwrap();
+
+        assert_eq!(wave.format(), AudioFormat::PCM16Stereo);
+        assert_eq!(wave.sample_count(), 3);
+        assert_eq!(wave.sample_rate(), Some(22050.0));
+        assert_eq!(
+            wave.get_buffer(),
+            &[1, 0, 0xFF, 0xFF, 0x34, 0x12, 0xFF, 0x7F, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn wav_decode_pcm8_signed() {
+        let mut writer =
+            WavWriter::new(Cursor::new(Vec::new()), AudioFormat::PCM8Mono, 8000).unwrap();
+        writer.write_samples(&[i16::MIN, 0, i16::MAX]).unwrap();
+
+        let data = writer.finalize().unwrap().into_inner();
+        let wave = Wave::from_wav_reader(Cursor::new(data)).unwrap();
+
+        assert_eq!(wave.format(), AudioFormat::PCM8Mono);
+        assert_eq!(wave.sample_count(), 3);
+        assert_eq!(wave.get_buffer(), &[0x80, 0, 0x7F]);
+    }
+
+    #[test]
+    fn wav_decode_rejects_float() {
+        let mut writer =
+            WavWriter::new(Cursor::new(Vec::new()), AudioFormat::PCM16Mono, 8000).unwrap();
+        writer.write_samples(&[0, 0]).unwrap();
+
+        let mut data = writer.finalize().unwrap().into_inner();
+        // IEEE float format tag.
+        data[20..22].copy_from_slice(&3u16.to_le_bytes());
+
+        assert!(matches!(
+            Wave::from_wav_reader(Cursor::new(data)),
+            Err(WavError::UnsupportedEncoding(3))
+        ));
+    }
+
+    #[test]
+    fn wav_decode_rejects_oversized_data() {
+        let writer =
+            WavWriter::new(Cursor::new(Vec::new()), AudioFormat::PCM16Stereo, 8000).unwrap();
+
+        let mut data = writer.finalize().unwrap().into_inner();
+        // Data chunk size, way beyond the available LINEAR memory.
+        data[40..44].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        assert!(matches!(
+            Wave::from_wav_reader(Cursor::new(data)),
+            Err(WavError::TooLarge)
+        ));
+    }
+}
@@ -15,11 +15,37 @@ pub struct Wave<Buffer: LinearAllocation + AsRef<[u8]>> {
     // Holding the data with the raw format is necessary since `libctru` will access it.
     pub(crate) raw_data: ctru_sys::ndspWaveBuf,
     played_on_channel: Option<u8>,
+    rejected: bool,
+    // Boxed so that its address (stored in `raw_data`) stays valid when the wave is moved.
+    adpcm_loop_context: Option<Box<ctru_sys::ndspAdpcmData>>,
+    adpcm_coefficients: Option<[i16; 16]>,
+    sample_rate: Option<f32>,
+}
+
+/// State of the DSP's ADPCM decoder at a given point of an ADPCM stream.
+///
+/// ADPCM samples are encoded as differences from the previous ones, so decoding can't simply restart
+/// from an arbitrary sample: the decoder needs the predictor/scale value and the last two decoded samples.
+/// The context at the loop start point is usually provided by the encoder (e.g. in the header of `.bcstm` or `.dspadpcm` files).
+///
+/// See [`Wave::set_adpcm_loop_context()`] to learn how to use this.
+#[doc(alias = "ndspAdpcmData")]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AdpcmContext {
+    /// Predictor and scale of the current ADPCM frame (the frame header byte).
+    pub index: u16,
+    /// Last decoded sample.
+    pub history0: i16,
+    /// Second-to-last decoded sample.
+    pub history1: i16,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u8)]
 /// Playback status of a [`Wave`].
+///
+/// All variants except [`Status::Error`] map directly to the `NDSP_WBUF_*` values
+/// `libctru` stores in the wave buffer's status field.
 pub enum Status {
     /// Wave has never been used.
     Free = ctru_sys::NDSP_WBUF_FREE,
@@ -29,6 +55,12 @@ pub enum Status {
     Playing = ctru_sys::NDSP_WBUF_PLAYING,
     /// Wave has finished playing.
     Done = ctru_sys::NDSP_WBUF_DONE,
+    /// Wave was rejected when queued, and will never be played.
+    ///
+    /// `libctru` silently ignores wave buffers without any samples to play (leaving their status untouched),
+    /// so [`Channel::queue_wave()`](super::Channel::queue_wave) marks them with this status instead.
+    /// This status is also reported if the raw status value isn't one of the known `NDSP_WBUF_*` values.
+    Error,
 }
 
 impl<Buffer> Wave<Buffer>
@@ -55,7 +87,13 @@ where
     /// ```
     pub fn new(buffer: Buffer, audio_format: AudioFormat, looping: bool) -> Self {
         let buf = buffer.as_ref();
-        let sample_count = buf.len() / audio_format.size();
+        let sample_count = audio_format.sample_count(buf.len());
+
+        debug_assert!(
+            buf.is_empty() || crate::linear::is_linear_ptr(buf.as_ptr()),
+            "wave buffer at {:p} is not on the LINEAR memory, the DSP won't be able to read it",
+            buf.as_ptr()
+        );
 
         // Signal to the DSP processor the buffer's RAM sector.
         // This step may seem delicate, but testing reports failure most of the time, while still having no repercussions on the resulting audio.
@@ -84,9 +122,53 @@ where
             audio_format,
             raw_data,
             played_on_channel: None,
+            rejected: false,
+            adpcm_loop_context: None,
+            adpcm_coefficients: None,
+            sample_rate: None,
         }
     }
 
+    /// Build a new playable wave object from DSP-ADPCM data on [LINEAR memory](`crate::linear`).
+    ///
+    /// `coefficients` are the decoder coefficients of the encoded data, and `context` the state of the decoder at the first sample
+    /// (see [`Wave::set_adpcm_loop_context()`]). Both are provided by the encoder, e.g. in the header of `.dspadpcm` files.
+    ///
+    /// The coefficients are set on the channel when the wave is queued with [`Channel::queue_wave()`](super::Channel::queue_wave).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # fn main() {
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// #
+    /// use ctru::linear::LinearAllocator;
+    /// use ctru::services::ndsp::wave::{AdpcmContext, Wave};
+    ///
+    /// // Provide your own encoded data (e.g. read from a `.dspadpcm` file).
+    /// let audio_data: Box<[_], _> = Box::new_in([0u8; 800], LinearAllocator);
+    /// let coefficients = [0i16; 16];
+    ///
+    /// let wave = Wave::new_adpcm(audio_data, coefficients, AdpcmContext::default(), false);
+    /// assert_eq!(wave.sample_count(), 1400);
+    /// # }
+    /// ```
+    pub fn new_adpcm(
+        buffer: Buffer,
+        coefficients: [i16; 16],
+        context: AdpcmContext,
+        looping: bool,
+    ) -> Self {
+        let mut wave = Self::new(buffer, AudioFormat::Adpcm, looping);
+
+        wave.adpcm_coefficients = Some(coefficients);
+        wave.set_adpcm_loop_context(context)
+            .expect("a new wave can't be busy");
+
+        wave
+    }
+
     /// Returns a slice to the audio data (on the LINEAR memory).
     pub fn get_buffer(&self) -> &[u8] {
         self.buffer.as_ref()
@@ -132,7 +214,14 @@ where
     /// # }
     /// ```
     pub fn status(&self) -> Status {
-        self.raw_data.status.try_into().unwrap()
+        if self.rejected {
+            return Status::Error;
+        }
+
+        // The status is updated by the NDSP thread, even while the wave is borrowed immutably.
+        let status = unsafe { std::ptr::read_volatile(&self.raw_data.status) };
+
+        status.try_into().unwrap_or(Status::Error)
     }
 
     /// Returns the amount of samples *read* by the NDSP process.
@@ -149,6 +238,80 @@ where
         self.audio_format
     }
 
+    /// Returns the sample rate (in Hz) the audio data should be played at, if known.
+    ///
+    /// The sample rate is set by [`Wave::set_sample_rate()`], or taken from the decoded file
+    /// for waves built with [`Wave::from_wav_reader()`](Wave::from_wav_reader).
+    pub fn sample_rate(&self) -> Option<f32> {
+        self.sample_rate
+    }
+
+    /// Set the sample rate (in Hz) the audio data should be played at.
+    ///
+    /// [`Channel::play_wave()`](super::Channel::play_wave) configures the channel with this sample rate.
+    pub fn set_sample_rate(&mut self, rate: f32) {
+        self.sample_rate = Some(rate);
+    }
+
+    /// Reset the playback state of this wave, so that it can be refilled and queued again as if it was new.
+    ///
+    /// This clears the wave's [`Status`] (including [`Status::Error`] for rejected waves) and its association with
+    /// the channel it was played on, without touching its buffer. Reusing the same buffer for each chunk of a streamed
+    /// audio track avoids allocating new LINEAR memory for every chunk.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the [`Wave`] is currently queued or playing.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #![feature(allocator_api)]
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// # use ctru::linear::LinearAllocator;
+    /// use ctru::services::ndsp::wave::{Status, Wave};
+    /// use ctru::services::ndsp::{AudioFormat, Ndsp};
+    /// let ndsp = Ndsp::new()?;
+    /// let mut channel_0 = ndsp.channel(0)?;
+    /// channel_0.set_format(AudioFormat::PCM16Stereo);
+    ///
+    /// # let audio_data: Box<[_], _> = Box::new_in([0u8; 4096], LinearAllocator);
+    /// let mut wave = Wave::new(audio_data, AudioFormat::PCM16Stereo, false);
+    /// channel_0.queue_wave(&mut wave)?;
+    ///
+    /// # let more_data_available = || false;
+    /// while more_data_available() {
+    ///     if wave.status() == Status::Done {
+    ///         wave.reset()?;
+    ///
+    ///         // Decode the next chunk of audio into the same buffer.
+    ///         wave.get_buffer_mut()?.fill(0);
+    ///
+    ///         channel_0.queue_wave(&mut wave)?;
+    ///     }
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reset(&mut self) -> Result<(), Error> {
+        match self.status() {
+            Status::Playing | Status::Queued => {
+                return Err(Error::WaveBusy(self.played_on_channel.unwrap()));
+            }
+            _ => (),
+        }
+
+        self.raw_data.status = Status::Free as u8;
+        self.raw_data.offset = 0;
+        self.played_on_channel = None;
+        self.rejected = false;
+
+        Ok(())
+    }
+
     // Set the internal flag for the id of the channel playing this wave.
     //
     // Internal Use Only.
@@ -156,6 +319,61 @@ where
         self.played_on_channel = Some(id)
     }
 
+    // Set the internal flag signaling whether the wave was rejected when queued.
+    //
+    // Internal Use Only.
+    pub(crate) fn set_rejected(&mut self, rejected: bool) {
+        self.rejected = rejected;
+    }
+
+    /// Returns the ADPCM decoder coefficients of a wave built with [`Wave::new_adpcm()`].
+    pub fn adpcm_coefficients(&self) -> Option<&[i16; 16]> {
+        self.adpcm_coefficients.as_ref()
+    }
+
+    /// Returns the ADPCM decoder context set with [`Wave::set_adpcm_loop_context()`], if any.
+    pub fn adpcm_loop_context(&self) -> Option<AdpcmContext> {
+        self.adpcm_loop_context.as_deref().map(|data| AdpcmContext {
+            index: data.index,
+            history0: data.history0,
+            history1: data.history1,
+        })
+    }
+
+    /// Set the ADPCM decoder context used when the playback of this wave starts, including each time a looping wave restarts.
+    ///
+    /// Without this context, the decoder keeps the state it had at the end of the wave when looping back to its start,
+    /// which produces an audible glitch at the seam of looping ADPCM music.
+    ///
+    /// # Notes
+    ///
+    /// The context only affects waves holding ADPCM data, and should match the decoder state at the first sample of the wave.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the [`Wave`] is currently queued.
+    #[doc(alias = "adpcm_data")]
+    pub fn set_adpcm_loop_context(&mut self, context: AdpcmContext) -> Result<(), Error> {
+        match self.status() {
+            Status::Playing | Status::Queued => {
+                return Err(Error::WaveBusy(self.played_on_channel.unwrap()));
+            }
+            _ => (),
+        }
+
+        let data = self
+            .adpcm_loop_context
+            .insert(Box::new(ctru_sys::ndspAdpcmData {
+                index: context.index,
+                history0: context.history0,
+                history1: context.history1,
+            }));
+
+        self.raw_data.adpcm_data = data.as_mut();
+
+        Ok(())
+    }
+
     /// Set the amount of samples to be read.
     ///
     /// # Note
@@ -176,7 +394,7 @@ where
             _ => (),
         }
 
-        let max_count = self.buffer.as_ref().len() / self.audio_format.size();
+        let max_count = self.audio_format.sample_count(self.buffer.as_ref().len());
 
         if sample_count > max_count {
             return Err(Error::SampleCountOutOfBounds(sample_count, max_count));
@@ -188,6 +406,85 @@ where
     }
 }
 
+/// Audio data borrowed from a larger buffer on the LINEAR memory.
+///
+/// This is the buffer type of [`Wave`]s built with [`Wave::from_slice()`].
+pub struct LinearSlice<'a> {
+    data: &'a mut [i16],
+}
+
+// SAFETY: `LinearSlice` can only be built by `Wave::from_slice()`, whose safety contract requires the data to be on the LINEAR memory.
+unsafe impl LinearAllocation for LinearSlice<'_> {}
+
+impl AsRef<[u8]> for LinearSlice<'_> {
+    fn as_ref(&self) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts(
+                self.data.as_ptr().cast(),
+                std::mem::size_of_val::<[i16]>(self.data),
+            )
+        }
+    }
+}
+
+impl AsMut<[u8]> for LinearSlice<'_> {
+    fn as_mut(&mut self) -> &mut [u8] {
+        unsafe {
+            std::slice::from_raw_parts_mut(
+                self.data.as_mut_ptr().cast(),
+                std::mem::size_of_val::<[i16]>(self.data),
+            )
+        }
+    }
+}
+
+impl<'a> Wave<LinearSlice<'a>> {
+    /// Build a new playable wave object borrowing `data`, without copying or allocating.
+    ///
+    /// This is useful for streaming audio, where the samples are decoded into different sections of a larger buffer
+    /// (e.g. a ring buffer), each played by a short-lived [`Wave`].
+    ///
+    /// The returned [`Wave`] is not looping. Have a look at [`Wave::new()`] to build a [`Wave`] owning its buffer.
+    ///
+    /// # Notes
+    ///
+    /// Just like with owned buffers, dropping the [`Wave`] while it is still queued or playing will clear the queue of the channel it was queued on,
+    /// so the DSP stops reading `data` before the borrow ends.
+    ///
+    /// # Safety
+    ///
+    /// - `data` must reside on the [LINEAR memory](crate::linear), e.g. be borrowed from a `Vec<i16, LinearAllocator>`.
+    ///   With [`Wave::new()`] this is guaranteed by the [`LinearAllocation`] bound, but a plain slice carries no such information.
+    /// - The returned [`Wave`] must be dropped (and not leaked, e.g. via [`std::mem::forget`]) while it's queued or playing.
+    ///   Otherwise the DSP would keep reading `data` after the borrow ends, even though the memory may be reused or freed.
+    ///   An owned buffer would simply be leaked along with the [`Wave`], but a borrowed one cannot.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #![feature(allocator_api)]
+    /// # fn main() {
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// #
+    /// use ctru::linear::LinearAllocator;
+    /// use ctru::services::ndsp::{AudioFormat, wave::Wave};
+    ///
+    /// // Ring buffer allocated in the LINEAR memory.
+    /// let mut ring_buffer = Vec::with_capacity_in(4096, LinearAllocator);
+    /// ring_buffer.resize(4096, 0i16);
+    ///
+    /// let (first_half, second_half) = ring_buffer.split_at_mut(2048);
+    ///
+    /// // SAFETY: the ring buffer is on the LINEAR memory, and the waves are dropped normally.
+    /// let first_wave = unsafe { Wave::from_slice(first_half, AudioFormat::PCM16Mono) };
+    /// let second_wave = unsafe { Wave::from_slice(second_half, AudioFormat::PCM16Mono) };
+    /// # }
+    /// ```
+    pub unsafe fn from_slice(data: &'a mut [i16], audio_format: AudioFormat) -> Self {
+        Self::new(LinearSlice { data }, audio_format, false)
+    }
+}
+
 impl TryFrom<u8> for Status {
     type Error = &'static str;
 
@@ -210,7 +507,7 @@ where
         // This was the only way I found I could check for improper drops of `Wave`.
         // A panic was considered, but it would cause issues with drop order against `Ndsp`.
         match self.status() {
-            Status::Free | Status::Done => (),
+            Status::Free | Status::Done | Status::Error => (),
             // If the status flag is "unfinished"
             _ => {
                 // The unwrap is safe, since it must have a value in the case the status is "unfinished".
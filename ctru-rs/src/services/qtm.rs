@@ -0,0 +1,185 @@
+//! QTM service.
+//!
+//! The QTM service provides access to the head tracking performed by the New 3DS, which uses the inner camera
+//! to follow the position of the user's eyes and stabilize the stereoscopic 3D effect.
+//!
+//! # Notes
+//!
+//! This service is only available on the New 3DS family of consoles (New 3DS, New 3DS XL, New 2DS XL).
+#![doc(alias = "head tracking")]
+#![doc(alias = "camera")]
+
+use std::sync::Mutex;
+
+use crate::error::ResultCode;
+use crate::services::ServiceReference;
+use crate::Error;
+
+pub(crate) static QTM_ACTIVE: Mutex<()> = Mutex::new(());
+
+/// Position of the user's head as seen by the inner camera.
+///
+/// Have a look at [`Qtm::head_tracking_info()`] to retrieve it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HeadCoordinates {
+    points: [(f32, f32); 4],
+    fully_detected: bool,
+}
+
+impl HeadCoordinates {
+    /// Returns the tracked points of the user's face, as (x, y) coordinates relative to the camera's field of view.
+    ///
+    /// Both coordinates range from -1.0 to 1.0, with (0.0, 0.0) being the center of the camera's view.
+    pub fn points(&self) -> [(f32, f32); 4] {
+        self.points
+    }
+
+    /// Returns `true` if all tracked points were detected, and not just estimated.
+    ///
+    /// See [`Qtm::is_head_fully_detected()`] for more info.
+    pub fn is_fully_detected(&self) -> bool {
+        self.fully_detected
+    }
+
+    /// Convert the tracked point at `index` to the corresponding pixel position (x, y) on the top screen.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `index` is not between 0 and 3.
+    #[doc(alias = "qtmConvertCoordToScreen")]
+    pub fn to_screen(&self, index: usize) -> (u32, u32) {
+        let (x, y) = self.points[index];
+        let mut coord = ctru_sys::QTM_HeadTrackingInfoCoord { x, y };
+
+        let mut screen_x = 0;
+        let mut screen_y = 0;
+
+        // The top screen's size is passed explicitly, rather than relying on the defaults `libctru` uses for null pointers.
+        let mut screen_width = 400.0;
+        let mut screen_height = 240.0;
+
+        unsafe {
+            ctru_sys::qtmConvertCoordToScreen(
+                &mut coord,
+                &mut screen_width,
+                &mut screen_height,
+                &mut screen_x,
+                &mut screen_y,
+            );
+        }
+
+        (screen_x, screen_y)
+    }
+}
+
+/// Handle to the QTM service.
+pub struct Qtm {
+    _service_handler: ServiceReference,
+}
+
+impl Qtm {
+    /// Initialize a new service handle.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::ServiceUnavailable`] if the console isn't a New 3DS,
+    /// or another error if the service was unable to be initialized or if a [`Qtm`] handle already exists.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::qtm::Qtm;
+    ///
+    /// match Qtm::new() {
+    ///     Ok(qtm) => println!("Head tracking is available"),
+    ///     Err(ctru::Error::ServiceUnavailable { .. }) => println!("This isn't a New 3DS"),
+    ///     Err(e) => return Err(e.into()),
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "qtmInit")]
+    pub fn new() -> crate::Result<Self> {
+        if !crate::os::is_new_3ds()? {
+            return Err(Error::ServiceUnavailable { name: "qtm:u" });
+        }
+
+        let _service_handler = ServiceReference::new(
+            &QTM_ACTIVE,
+            "qtm:u",
+            || {
+                ResultCode(unsafe { ctru_sys::qtmInit() })?;
+
+                Ok(())
+            },
+            || unsafe {
+                ctru_sys::qtmExit();
+            },
+        )?;
+
+        Ok(Self { _service_handler })
+    }
+
+    /// Returns the position of the user's head, or `None` if no head is currently being tracked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::qtm::Qtm;
+    /// let qtm = Qtm::new()?;
+    ///
+    /// if let Some(head) = qtm.head_tracking_info()? {
+    ///     println!("Head found at {:?}", head.points());
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "QTM_GetHeadTrackingInfo")]
+    pub fn head_tracking_info(&self) -> crate::Result<Option<HeadCoordinates>> {
+        let mut info = self.raw_info()?;
+
+        // The first flag is cleared while no face is visible to the camera.
+        if info.flags[0] == 0 {
+            return Ok(None);
+        }
+
+        let fully_detected = unsafe { ctru_sys::qtmCheckHeadFullyDetected(&mut info) };
+
+        Ok(Some(HeadCoordinates {
+            points: info.coords0.map(|c| (c.x, c.y)),
+            fully_detected,
+        }))
+    }
+
+    /// Returns `true` if the user's head is currently fully detected by the camera.
+    ///
+    /// # Notes
+    ///
+    /// When the user's head is only partially visible (or the lighting conditions are poor),
+    /// the system may still report estimated coordinates, which are less reliable.
+    #[doc(alias = "qtmCheckHeadFullyDetected")]
+    pub fn is_head_fully_detected(&self) -> crate::Result<bool> {
+        let mut info = self.raw_info()?;
+
+        Ok(unsafe { ctru_sys::qtmCheckHeadFullyDetected(&mut info) })
+    }
+
+    fn raw_info(&self) -> crate::Result<ctru_sys::QTM_HeadTrackingInfo> {
+        let mut info = ctru_sys::QTM_HeadTrackingInfo::default();
+
+        // The first argument is ignored by the service.
+        ResultCode(unsafe { ctru_sys::QTM_GetHeadTrackingInfo(0, &mut info) })?;
+
+        Ok(info)
+    }
+}
@@ -7,7 +7,15 @@ pub(crate) struct ServiceReference {
 }
 
 impl ServiceReference {
-    pub fn new<S, E>(counter: &'static Mutex<()>, start: S, close: E) -> crate::Result<Self>
+    /// Lock `counter` and start the service.
+    ///
+    /// `name` is only used to report a more descriptive [`Error::ServiceUnavailable`] if the service couldn't be found.
+    pub fn new<S, E>(
+        counter: &'static Mutex<()>,
+        name: &'static str,
+        start: S,
+        close: E,
+    ) -> crate::Result<Self>
     where
         S: FnOnce() -> crate::Result<()>,
         E: Fn() + Send + Sync + 'static,
@@ -22,13 +30,22 @@ impl ServiceReference {
                     // It's up to our `close()` implementations to avoid panicking/doing weird stuff again.
                     close();
 
+                    // The service is about to be restarted from a clean state, so it's no longer considered poisoned.
+                    // See `services::is_poisoned()`.
+                    counter.clear_poison();
+
                     guard.into_inner()
                 }
                 TryLockError::WouldBlock => return Err(Error::ServiceAlreadyActive),
             },
         };
 
-        start()?;
+        match start() {
+            Err(Error::Os(code)) if is_service_unavailable(code) => {
+                return Err(Error::ServiceUnavailable { name })
+            }
+            res => res?,
+        }
 
         Ok(Self {
             _guard,
@@ -37,6 +54,17 @@ impl ServiceReference {
     }
 }
 
+/// Returns `true` if `code` means that the requested service (or one of its system components) doesn't exist.
+fn is_service_unavailable(code: ctru_sys::Result) -> bool {
+    // Returned by `srvGetServiceHandle` when the service isn't registered.
+    const SRV_NOT_REGISTERED: ctru_sys::Result = 0xD8E06406_u32 as ctru_sys::Result;
+
+    // Also covers `svcConnectToPort` failing and missing components such as the DSP firmware.
+    code == SRV_NOT_REGISTERED
+        || (ctru_sys::R_SUMMARY(code) == ctru_sys::RS_NOTFOUND
+            && ctru_sys::R_DESCRIPTION(code) == ctru_sys::RD_NOT_FOUND)
+}
+
 impl Drop for ServiceReference {
     fn drop(&mut self) {
         (self.close)();
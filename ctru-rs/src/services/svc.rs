@@ -31,12 +31,7 @@ pub trait HandleExt {
 
 impl HandleExt for Handle {
     fn wait_for_event(self, timeout: Duration) -> crate::Result<()> {
-        let timeout = i64::try_from(timeout.as_nanos()).map_err(|e| {
-            crate::Error::Other(format!(
-                "Failed to convert timeout to 64-bit nanoseconds: {}",
-                e
-            ))
-        })?;
+        let timeout = timeout_as_nanos(timeout)?;
         unsafe {
             ResultCode(ctru_sys::svcWaitSynchronization(self, timeout))?;
         }
@@ -77,6 +72,16 @@ impl HandleExt for Handle {
     }
 }
 
+/// Convert a timeout to the signed 64-bit nanoseconds count expected by the kernel.
+pub(crate) fn timeout_as_nanos(timeout: Duration) -> crate::Result<i64> {
+    i64::try_from(timeout.as_nanos()).map_err(|e| {
+        crate::Error::Other(format!(
+            "Failed to convert timeout to 64-bit nanoseconds: {}",
+            e
+        ))
+    })
+}
+
 /// Creates a command header to be used for IPC. This is a const fn version of [`ctru_sys::IPC_MakeHeader`].
 pub const fn make_ipc_header(command_id: u16, normal_params: u8, translate_params: u8) -> u32 {
     ((command_id as u32) << 16)
@@ -5,6 +5,7 @@
 #![doc(alias = "network")]
 #![doc(alias = "dlplay")]
 
+use std::collections::HashSet;
 use std::error::Error as StdError;
 use std::ffi::CString;
 use std::fmt::{Debug, Display};
@@ -12,6 +13,7 @@ use std::mem::MaybeUninit;
 use std::ops::FromResidual;
 use std::ptr::null;
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 use crate::error::ResultCode;
 use crate::services::ServiceReference;
@@ -50,6 +52,15 @@ pub enum Error {
     TooMuchAppData,
     /// The provided node ID does not reference a specific node.
     NotANode,
+    /// The operation did not complete before the timeout expired.
+    Timeout,
+    /// The provided packet was larger than [`Uds::MAX_PACKET_SIZE`].
+    PacketTooLarge {
+        /// Size of the provided packet.
+        provided: usize,
+        /// Maximum size of a packet.
+        max: usize,
+    },
     /// ctru-rs error
     Lib(crate::Error),
 }
@@ -88,6 +99,9 @@ impl Display for Error {
                 Self::NoNetwork => "not hosting a network".into(),
                 Self::TooMuchAppData => "provided too much app data (max 200 bytes)".into(),
                 Self::NotANode => "provided node ID was non-specific".into(),
+                Self::Timeout => "operation timed out".into(),
+                Self::PacketTooLarge { provided, max } =>
+                    format!("provided packet was too large ({provided} bytes, max {max} bytes)"),
                 Self::Lib(e) => format!("ctru-rs error: {e}"),
             }
         )
@@ -197,7 +211,15 @@ impl NodeInfo {
 
     /// Username associated with this network node.
     pub fn username(&self) -> String {
-        String::from_utf16_lossy(unsafe { &self.0.__bindgen_anon_1.__bindgen_anon_1.username })
+        let username = unsafe { &self.0.__bindgen_anon_1.__bindgen_anon_1.username };
+
+        // The username is NUL-terminated, unless it takes up the whole buffer.
+        let len = username
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(username.len());
+
+        String::from_utf16_lossy(&username[..len])
     }
 
     /// Flag associated with this network node.
@@ -270,6 +292,16 @@ impl NetworkScanInfo {
         &mut self.0.network
     }
 
+    /// Communication ID of the network, as passed to [`Uds::scan()`].
+    pub fn comm_id(&self) -> [u8; 4] {
+        self.0.network.wlancommID.to_be_bytes()
+    }
+
+    /// Username of the network's host (its first node), or `None` if the host's information is missing.
+    pub fn host_username(&self) -> Option<String> {
+        self.nodes()[0].map(|host| host.username())
+    }
+
     /// All nodes on the network (first node is the server,
     /// max 16, `None` means no node connected).
     pub fn nodes(&self) -> [Option<NodeInfo>; 16] {
@@ -391,15 +423,157 @@ pub enum ServiceStatus {
     Server,
 }
 
+/// Handle to an additional bind context, receiving data on a specific data channel.
+///
+/// Obtained with [`Uds::bind_channel()`]. Packets received on the channel can be pulled with [`Uds::pull_packet_from()`].
+///
+/// The bind context is owned by the [`Uds`] handle that created it, and stays valid until it is unbound with
+/// [`Uds::unbind_channel()`] or the service disconnects from the network.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct BindHandle {
+    id: u32,
+    channel: u8,
+}
+
+impl BindHandle {
+    /// Returns the data channel the context is bound to.
+    pub fn channel(&self) -> u8 {
+        self.channel
+    }
+}
+
+/// Application data of a network, as a fixed-size buffer of at most [`Uds::MAX_APPDATA_SIZE`] bytes.
+///
+/// App data is set by the host of a network, and can be read by any console scanning for (or connected to) the network.
+/// It's usually used to share information about a game's lobby, such as the selected map or game mode.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// use bytemuck::{Pod, Zeroable};
+/// use ctru::services::uds::AppData;
+///
+/// #[derive(Copy, Clone, Debug, PartialEq, Pod, Zeroable)]
+/// #[repr(C)]
+/// struct Lobby {
+///     map: u8,
+///     mode: u8,
+///     players: u16,
+/// }
+///
+/// let lobby = Lobby { map: 3, mode: 1, players: 2 };
+/// let appdata = AppData::from_pod(&lobby);
+///
+/// assert_eq!(appdata.len(), 4);
+/// assert_eq!(appdata.read::<Lobby>(), Some(lobby));
+/// ```
+#[derive(Copy, Clone)]
+pub struct AppData {
+    data: [u8; Uds::MAX_APPDATA_SIZE],
+    len: usize,
+}
+
+impl AppData {
+    /// Create empty app data.
+    pub const fn new() -> Self {
+        Self {
+            data: [0; Uds::MAX_APPDATA_SIZE],
+            len: 0,
+        }
+    }
+
+    /// Create app data holding a copy of `bytes`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `bytes` is longer than [`Uds::MAX_APPDATA_SIZE`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() > Uds::MAX_APPDATA_SIZE {
+            return Err(Error::TooMuchAppData);
+        }
+
+        let mut appdata = Self::new();
+        appdata.data[..bytes.len()].copy_from_slice(bytes);
+        appdata.len = bytes.len();
+
+        Ok(appdata)
+    }
+
+    /// Create app data holding the byte representation of `value`.
+    ///
+    /// Types larger than [`Uds::MAX_APPDATA_SIZE`] are rejected at compile time.
+    pub fn from_pod<T: bytemuck::Pod>(value: &T) -> Self {
+        const { assert_appdata_size::<T>() };
+
+        // The size was already checked at compile time.
+        Self::from_bytes(bytemuck::bytes_of(value)).unwrap()
+    }
+
+    /// Reinterpret the app data as a value of type `T`.
+    ///
+    /// Returns `None` if the size of the app data doesn't match the size of `T`.
+    pub fn read<T: bytemuck::Pod>(&self) -> Option<T> {
+        bytemuck::try_pod_read_unaligned(self.as_bytes()).ok()
+    }
+
+    /// Returns the app data as a byte slice.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+
+    /// Returns the length of the app data, in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the app data is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Default for AppData {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for AppData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("AppData").field(&self.as_bytes()).finish()
+    }
+}
+
+impl PartialEq for AppData {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for AppData {}
+
+const fn assert_appdata_size<T>() {
+    assert!(
+        std::mem::size_of::<T>() <= Uds::MAX_APPDATA_SIZE,
+        "type is too large to be used as UDS app data"
+    );
+}
+
 /// Handle to the UDS service.
 pub struct Uds {
     _service_handler: ServiceReference,
     context: Option<ctru_sys::udsBindContext>,
+    /// Additional contexts bound with [`Uds::bind_channel()`], along with the ID of their [`BindHandle`].
+    extra_contexts: Vec<(u32, ctru_sys::udsBindContext)>,
+    next_bind_id: u32,
     network: Option<ctru_sys::udsNetworkStruct>,
     scan_buf: Box<[u8; Self::SCAN_BUF_SIZE]>,
+    /// Wi-Fi channel used by [`Uds::create_network()`], or `None` to let the system choose.
+    wifi_channel: Option<u8>,
 }
 
-static UDS_ACTIVE: Mutex<()> = Mutex::new(());
+pub(crate) static UDS_ACTIVE: Mutex<()> = Mutex::new(());
 
 impl Uds {
     /// Size of one frame.
@@ -408,6 +582,9 @@ impl Uds {
     /// Size of receive buffer; max frame size * 8.
     const RECV_BUF_SIZE: u32 = ctru_sys::UDS_DEFAULT_RECVBUFSIZE;
 
+    /// Wi-Fi channels on which local wireless networks can be hosted.
+    pub const WIFI_CHANNELS: [u8; 3] = [1, 6, 11];
+
     /// Shared memory size; must be slightly larger
     /// than `RECV_BUF_SIZE`.
     const SHAREDMEM_SIZE: usize = 0x3000;
@@ -420,9 +597,15 @@ impl Uds {
     /// to a network (16). Can be further limited.
     const MAX_NODES: u8 = ctru_sys::UDS_MAXNODES as u8;
 
-    /// The maximum amount of app data any server can provide.
+    /// The maximum amount of app data any server can provide (200 bytes).
     /// Limited by the size of a struct in libctru.
-    const MAX_APPDATA_SIZE: usize = Self::size_of_call(|s: ctru_sys::udsNetworkStruct| s.appdata);
+    pub const MAX_APPDATA_SIZE: usize =
+        Self::size_of_call(|s: ctru_sys::udsNetworkStruct| s.appdata);
+
+    /// The maximum size of a packet sent with [`Uds::send_packet()`] (1478 bytes).
+    /// Packets are sent in a single data frame, and are not split by the service.
+    #[doc(alias = "UDS_DATAFRAME_MAXSIZE")]
+    pub const MAX_PACKET_SIZE: usize = ctru_sys::UDS_DATAFRAME_MAXSIZE as usize;
 
     const fn size_of_call<T, U>(_: fn(T) -> U) -> usize {
         std::mem::size_of::<U>()
@@ -438,6 +621,34 @@ impl Uds {
         }
     }
 
+    /// Returns whether the service is currently connected to a network as a spectator.
+    ///
+    /// Spectators can receive packets with [`Uds::pull_packet()`], but cannot send any:
+    /// [`Uds::send_packet()`] will always return [`Error::Spectator`] while this is `true`.
+    ///
+    /// Returns `false` if the service isn't connected to a network at all.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::uds::{ConnectionType, Uds};
+    /// let mut uds = Uds::new(None)?;
+    ///
+    /// let networks = uds.scan(b"HBW\x10", None, None)?;
+    /// uds.connect_network(&networks[0], b"udsdemo passphrase c186093cd2652741\0", ConnectionType::Spectator, 1)?;
+    /// assert!(uds.is_spectator());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn is_spectator(&self) -> bool {
+        self.context.is_some_and(|ctx| ctx.spectator)
+    }
+
     /// Initialise a new service handle.
     /// No `new_with_buffer_size` function is provided, as there isn't really a
     /// reason to use any size other than the default.
@@ -474,6 +685,7 @@ impl Uds {
         let cstr = username.map(CString::new).transpose()?;
         let handler = ServiceReference::new(
             &UDS_ACTIVE,
+            "nwm::UDS",
             || {
                 let ptr = cstr.map(|c| c.as_ptr()).unwrap_or(null());
 
@@ -489,8 +701,11 @@ impl Uds {
         Ok(Self {
             _service_handler: handler,
             context: None,
+            extra_contexts: Vec::new(),
+            next_bind_id: 0,
             network: None,
             scan_buf: Box::new([0; Self::SCAN_BUF_SIZE]),
+            wifi_channel: None,
         })
     }
 
@@ -556,6 +771,98 @@ impl Uds {
         Ok(networks)
     }
 
+    /// Scan the UDS service for networks like [`Uds::scan()`], returning an iterator which retrieves the app data of each network
+    /// (via [`Uds::network_appdata()`]) as it is advanced.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the scan fails. Errors retrieving the app data of a network
+    /// are returned by the iterator, along with the other networks.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::uds::Uds;
+    /// let mut uds = Uds::new(None)?;
+    ///
+    /// // Find the first network hosted by "foo" whose app data could be retrieved.
+    /// let network = uds
+    ///     .scan_iter(b"HBW\x10", None, None)?
+    ///     .filter_map(Result::ok)
+    ///     .find(|(network, _appdata)| network.host_username().as_deref() == Some("foo"));
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "udsScanBeacons")]
+    #[doc(alias = "udsGetNetworkStructApplicationData")]
+    pub fn scan_iter(
+        &mut self,
+        comm_id: &[u8; 4],
+        additional_id: Option<u8>,
+        whitelist_macaddr: Option<MacAddr6>,
+    ) -> crate::Result<impl Iterator<Item = crate::Result<(NetworkScanInfo, Vec<u8>)>> + '_> {
+        let networks = self.scan(comm_id, additional_id, whitelist_macaddr)?;
+        let uds = &*self;
+
+        Ok(networks.into_iter().map(move |network| {
+            let appdata = uds.network_appdata(&network, None)?;
+
+            Ok((network, appdata))
+        }))
+    }
+
+    /// Repeatedly scan the UDS service for beacons broadcasting with the given ID, until at least one network is found
+    /// or `duration` has elapsed.
+    ///
+    /// Networks are deduplicated by their host's MAC address.
+    /// Have a look at [`Uds::scan()`] for a single scan with more filtering options.
+    ///
+    /// # Notes
+    ///
+    /// This function blocks the current thread for up to `duration` (plus the time taken by a single scan).
+    /// An empty list is returned if no network was found in time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use std::time::Duration;
+    /// use ctru::services::uds::Uds;
+    /// let mut uds = Uds::new(None)?;
+    ///
+    /// let networks = uds.scan_for(b"HBW\x10", Duration::from_secs(5))?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "udsScanBeacons")]
+    pub fn scan_for(
+        &mut self,
+        comm_id: &[u8; 4],
+        duration: Duration,
+    ) -> crate::Result<Vec<NetworkScanInfo>> {
+        let start = Instant::now();
+
+        loop {
+            let mut networks = self.scan(comm_id, None, None)?;
+
+            let mut seen_hosts = HashSet::new();
+            networks.retain(|n| seen_hosts.insert(n.network_ref().host_macaddress));
+
+            if !networks.is_empty() || start.elapsed() >= duration {
+                return Ok(networks);
+            }
+        }
+    }
+
     /// Retrieve app data for a network which the service is not connected to.
     ///
     /// # Example
@@ -661,6 +968,69 @@ impl Uds {
         Ok(appdata_buffer)
     }
 
+    /// Retrieve app data for a network which the service is not connected to, reinterpreted as a value of type `T`.
+    ///
+    /// Returns `None` if the size of the app data doesn't match the size of `T` (see [`AppData::read()`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use bytemuck::{Pod, Zeroable};
+    /// use ctru::services::uds::Uds;
+    ///
+    /// #[derive(Copy, Clone, Pod, Zeroable)]
+    /// #[repr(C)]
+    /// struct Lobby {
+    ///     map: u8,
+    ///     mode: u8,
+    ///     players: u16,
+    /// }
+    ///
+    /// let mut uds = Uds::new(None)?;
+    ///
+    /// let networks = uds.scan(b"HBW\x10", None, None)?;
+    ///
+    /// if let Some(lobby) = uds.network_appdata_as::<Lobby>(&networks[0])? {
+    ///     println!("{} players in the lobby", lobby.players);
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "udsGetNetworkStructApplicationData")]
+    pub fn network_appdata_as<T: bytemuck::Pod>(
+        &self,
+        network: &NetworkScanInfo,
+    ) -> crate::Result<Option<T>> {
+        let appdata = self.network_appdata(network, None)?;
+
+        // The app data returned by the service always fits in `AppData`.
+        Ok(AppData::from_bytes(&appdata)
+            .ok()
+            .and_then(|appdata| appdata.read()))
+    }
+
+    /// Retrieve app data for the currently connected network, reinterpreted as a value of type `T`.
+    ///
+    /// Returns `None` if the size of the app data doesn't match the size of `T` (see [`AppData::read()`]).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the service is not connected to a network.
+    #[doc(alias = "udsGetApplicationData")]
+    pub fn appdata_as<T: bytemuck::Pod>(&self) -> Result<Option<T>, Error> {
+        let appdata = self.appdata(None)?;
+
+        // The app data returned by the service always fits in `AppData`.
+        Ok(AppData::from_bytes(&appdata)
+            .ok()
+            .and_then(|appdata| appdata.read()))
+    }
+
     /// Connect to a network.
     ///
     /// # Example
@@ -739,6 +1109,8 @@ impl Uds {
             return Err(Error::NotConnected);
         }
 
+        self.unbind_all_channels()?;
+
         if self.context.is_some() {
             self.unbind_context()?;
         }
@@ -753,6 +1125,8 @@ impl Uds {
     /// Normally, there's no reason to call this function,
     /// since [`Uds::disconnect_network()`] and [`Uds::destroy_network()`] both automatically unbind their contexts.
     ///
+    /// Contexts bound with [`Uds::bind_channel()`] are not affected; see [`Uds::unbind_channel()`].
+    ///
     /// # Errors
     ///
     /// This function will return an error if no context is currently bound (i.e. the service is neither connected to nor hosting a network).
@@ -902,15 +1276,79 @@ impl Uds {
         Ok(status.into())
     }
 
+    /// Wait until at least `count` nodes (including the host) are connected to the network, returning the resulting [`ConnectionStatus`].
+    ///
+    /// This function blocks the current thread for up to `timeout`, polling the connection status event
+    /// and re-checking the connection status about once per frame (every 16 ms) until enough nodes are connected.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the service is currently neither connected to nor hosting a network,
+    /// or [`Error::Timeout`] if not enough nodes connected in time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use std::time::Duration;
+    /// use ctru::services::uds::Uds;
+    /// let mut uds = Uds::new(None)?;
+    ///
+    /// uds.create_network(b"HBW\x10", None, None, b"udsdemo passphrase c186093cd2652741\0", 1)?;
+    ///
+    /// // Wait for 3 other players to join.
+    /// match uds.wait_for_clients(4, Duration::from_secs(60)) {
+    ///     Ok(status) => println!("Starting the game with {} players", status.total_nodes()),
+    ///     Err(ctru::services::uds::Error::Timeout) => println!("Not enough players joined"),
+    ///     Err(e) => return Err(e.into()),
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn wait_for_clients(
+        &self,
+        count: u8,
+        timeout: Duration,
+    ) -> Result<ConnectionStatus, Error> {
+        // `udsWaitConnectionStatusEvent` can't wait with a timeout, and `libctru` doesn't expose the underlying
+        // event handle to wait on it with `svcWaitSynchronization`, so the event is polled instead.
+        const POLL_INTERVAL: Duration = Duration::from_millis(16);
+
+        let start = Instant::now();
+
+        loop {
+            let status = self.connection_status()?;
+
+            if status.total_nodes() >= count {
+                return Ok(status);
+            }
+
+            let elapsed = start.elapsed();
+            if elapsed >= timeout {
+                return Err(Error::Timeout);
+            }
+
+            if !self.wait_status_event(false, false)? {
+                std::thread::sleep(POLL_INTERVAL.min(timeout - elapsed));
+            }
+        }
+    }
+
     /// Send a packet to the network.
     ///
-    /// TODO: max size?
+    /// Packets can be at most [`Uds::MAX_PACKET_SIZE`] bytes long.
     ///
     /// # Errors
     ///
+    /// This function will return [`Error::PacketTooLarge`] if the packet is larger than [`Uds::MAX_PACKET_SIZE`].
     /// This function will return an error if the service is currently neither connected to nor hosting a network.
     /// See [`Uds::connect_network()`] to connect to a network or [`Uds::create_network()`] to create one.
-    /// It will also return an error if the service is currently connected to a network as a spectator, as spectators cannot send data, only receive it.
+    /// It will also return [`Error::Spectator`] if the service is currently connected to a network as a spectator, as spectators cannot send data, only receive it.
+    /// Use [`Uds::is_spectator()`] to check for this beforehand.
     ///
     /// # Example
     ///
@@ -937,11 +1375,13 @@ impl Uds {
         channel: u8,
         flags: SendFlags,
     ) -> Result<(), Error> {
+        check_packet_size(packet)?;
+
         if self.service_status() == ServiceStatus::Disconnected {
             return Err(Error::NotConnected);
         }
 
-        if self.context.unwrap().spectator {
+        if self.is_spectator() {
             return Err(Error::Spectator);
         }
 
@@ -999,6 +1439,90 @@ impl Uds {
             return Err(Error::NotConnected);
         }
 
+        Self::pull_packet_with(&self.context.unwrap())
+    }
+
+    /// Wait for a packet to be received, and pull it from the network.
+    ///
+    /// This blocks the current thread until a packet is available, or until `timeout` has elapsed (waiting indefinitely if `None`).
+    /// Returns `None` if no packet was received in time.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the service is currently neither connected to nor hosting a network.
+    /// See [`Uds::connect_network()`] to connect to a network or [`Uds::create_network()`] to create one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use std::time::Duration;
+    /// use ctru::services::uds::{ConnectionType, NodeID, SendFlags, Uds};
+    /// let mut uds = Uds::new(None)?;
+    ///
+    /// let networks = uds.scan(b"HBW\x10", None, None)?;
+    /// uds.connect_network(&networks[0], b"udsdemo passphrase c186093cd2652741\0", ConnectionType::Client, 1)?;
+    ///
+    /// // The host is always the first node.
+    /// uds.send_packet(b"ping", NodeID::Node(1), 1, SendFlags::Default)?;
+    ///
+    /// match uds.recv_packet(Some(Duration::from_secs(1)))? {
+    ///     Some((packet, _sender)) => println!("Received {} bytes", packet.len()),
+    ///     None => println!("No answer"),
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "udsWaitDataAvailable")]
+    #[doc(alias = "udsPullPacket")]
+    pub fn recv_packet(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<Option<(Vec<u8>, NodeID)>, Error> {
+        if self.service_status() == ServiceStatus::Disconnected {
+            return Err(Error::NotConnected);
+        }
+
+        let context = self.context.as_ref().unwrap();
+
+        // A deadline too far in the future to be represented is the same as no deadline.
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+
+        loop {
+            // The event is cleared before pulling, so that packets received after the pull signal it again.
+            let _ = unsafe { ctru_sys::svcClearEvent(context.event) };
+
+            if let Some(packet) = Self::pull_packet_with(context)? {
+                return Ok(Some(packet));
+            }
+
+            match deadline {
+                None => unsafe {
+                    ctru_sys::udsWaitDataAvailable(context as *const _, false, true);
+                },
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Ok(None);
+                    }
+
+                    match crate::os::wait_any(&[context.event], remaining) {
+                        Ok(_) => (),
+                        Err(e) if e.is_timeout() => return Ok(None),
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+            }
+        }
+    }
+
+    fn pull_packet_with(
+        context: &ctru_sys::udsBindContext,
+    ) -> Result<Option<(Vec<u8>, NodeID)>, Error> {
         let mut frame = MaybeUninit::<[u8; Self::RECV_FRAME_SIZE]>::zeroed();
 
         let mut actual_size = MaybeUninit::uninit();
@@ -1006,7 +1530,7 @@ impl Uds {
 
         ResultCode(unsafe {
             ctru_sys::udsPullPacket(
-                &self.context.unwrap() as *const _,
+                context as *const _,
                 frame.as_mut_ptr().cast(),
                 Self::RECV_FRAME_SIZE,
                 actual_size.as_mut_ptr(),
@@ -1031,6 +1555,73 @@ impl Uds {
         })
     }
 
+    /// Suggest the Wi-Fi channel (one of [`Uds::WIFI_CHANNELS`]) with the least networks broadcasting with the given ID.
+    ///
+    /// # Notes
+    ///
+    /// The heuristic is simple: a single scan is performed, the networks found are counted per Wi-Fi channel,
+    /// and the channel with the fewest networks is returned (preferring the lowest channel in case of a tie).
+    /// Scans only report networks with a matching `comm_id`, so the congestion caused by other applications and
+    /// by regular Wi-Fi access points is not accounted for.
+    ///
+    /// Use [`Uds::set_wifi_channel()`] to host the next network on the suggested channel.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::uds::Uds;
+    /// let mut uds = Uds::new(None)?;
+    ///
+    /// let channel = uds.recommended_channel(b"HBW\x10")?;
+    /// uds.set_wifi_channel(Some(channel))?;
+    ///
+    /// uds.create_network(b"HBW\x10", None, None, b"udsdemo passphrase c186093cd2652741\0", 1)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn recommended_channel(&mut self, comm_id: &[u8; 4]) -> crate::Result<u8> {
+        let networks = self.scan(comm_id, None, None)?;
+
+        let least_used = Self::WIFI_CHANNELS
+            .into_iter()
+            .min_by_key(|&channel| {
+                networks
+                    .iter()
+                    .filter(|n| n.network_ref().channel == channel)
+                    .count()
+            })
+            .expect("there is at least one Wi-Fi channel");
+
+        Ok(least_used)
+    }
+
+    /// Set the Wi-Fi channel on which networks created with [`Uds::create_network()`] are hosted.
+    ///
+    /// With `None` (the default), the system chooses the channel.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the channel is not one of [`Uds::WIFI_CHANNELS`].
+    pub fn set_wifi_channel(&mut self, channel: Option<u8>) -> crate::Result<()> {
+        if let Some(channel) = channel {
+            if !Self::WIFI_CHANNELS.contains(&channel) {
+                return Err(crate::Error::Other(format!(
+                    "local wireless networks can't be hosted on Wi-Fi channel {channel} (only {:?} are allowed)",
+                    Self::WIFI_CHANNELS
+                )));
+            }
+        }
+
+        self.wifi_channel = channel;
+
+        Ok(())
+    }
+
     /// Create a new network.
     ///
     /// # Errors
@@ -1071,7 +1662,10 @@ impl Uds {
             )
         };
 
-        let network = unsafe { network.assume_init() };
+        let mut network = unsafe { network.assume_init() };
+
+        // A channel of 0 lets the system choose.
+        network.channel = self.wifi_channel.unwrap_or(0);
 
         let mut context = MaybeUninit::uninit();
 
@@ -1124,6 +1718,8 @@ impl Uds {
             return Err(Error::NoNetwork);
         }
 
+        self.unbind_all_channels()?;
+
         // should always be true
         if self.context.is_some() {
             self.unbind_context()?;
@@ -1175,6 +1771,45 @@ impl Uds {
         Ok(())
     }
 
+    /// Set the app data for the currently hosted network to the byte representation of `data`.
+    ///
+    /// Types larger than [`Uds::MAX_APPDATA_SIZE`] are rejected at compile time (see [`AppData::from_pod()`]).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if no network has been created.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use bytemuck::{Pod, Zeroable};
+    /// use ctru::services::uds::Uds;
+    ///
+    /// #[derive(Copy, Clone, Pod, Zeroable)]
+    /// #[repr(C)]
+    /// struct Lobby {
+    ///     map: u8,
+    ///     mode: u8,
+    ///     players: u16,
+    /// }
+    ///
+    /// let mut uds = Uds::new(None)?;
+    ///
+    /// uds.create_network(b"HBW\x10", None, None, b"udsdemo passphrase c186093cd2652741\0", 1)?;
+    /// uds.set_appdata_typed(&Lobby { map: 3, mode: 1, players: 1 })?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "udsSetApplicationData")]
+    pub fn set_appdata_typed<T: bytemuck::Pod>(&self, data: &T) -> Result<(), Error> {
+        self.set_appdata(AppData::from_pod(data).as_bytes())
+    }
+
     /// Wait for a bind event to occur.
     ///
     /// If `next` is `true`, discard the current event (if any) and wait for the next one.
@@ -1218,6 +1853,137 @@ impl Uds {
         })
     }
 
+    /// Bind an additional context to receive data on the given data channel.
+    ///
+    /// Each bind context has its own receive buffer, so packets sent on different data channels
+    /// (e.g. game state on channel 1 and voice chat on channel 2) can be handled independently.
+    /// Use [`Uds::pull_packet_from()`] and [`Uds::wait_data_available_on()`] with the returned [`BindHandle`].
+    ///
+    /// All contexts bound with this function are unbound automatically by [`Uds::disconnect_network()`] and [`Uds::destroy_network()`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the service is currently neither connected to nor hosting a network.
+    /// See [`Uds::connect_network()`] to connect to a network or [`Uds::create_network()`] to create one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::uds::{ConnectionType, Uds};
+    /// let mut uds = Uds::new(None)?;
+    ///
+    /// let networks = uds.scan(b"HBW\x10", None, None)?;
+    /// uds.connect_network(&networks[0], b"udsdemo passphrase c186093cd2652741\0", ConnectionType::Client, 1)?;
+    ///
+    /// // Game state is received on the connection's channel, voice data on channel 2.
+    /// let voice = uds.bind_channel(2)?;
+    ///
+    /// let game_packet = uds.pull_packet()?;
+    /// let voice_packet = uds.pull_packet_from(&voice)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "udsBind")]
+    pub fn bind_channel(&mut self, channel: u8) -> Result<BindHandle, Error> {
+        if self.service_status() == ServiceStatus::Disconnected {
+            return Err(Error::NotConnected);
+        }
+
+        let mut context = MaybeUninit::uninit();
+
+        ResultCode(unsafe {
+            ctru_sys::udsBind(
+                context.as_mut_ptr(),
+                NodeID::Broadcast.into(),
+                self.is_spectator(),
+                channel,
+                Self::RECV_BUF_SIZE,
+            )
+        })?;
+
+        let context = unsafe { context.assume_init() };
+
+        let id = self.next_bind_id;
+        self.next_bind_id = self.next_bind_id.wrapping_add(1);
+
+        self.extra_contexts.push((id, context));
+
+        Ok(BindHandle { id, channel })
+    }
+
+    /// Unbind a context bound with [`Uds::bind_channel()`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::NoContext`] if the context has already been unbound (e.g. after disconnecting from the network).
+    #[doc(alias = "udsUnbind")]
+    pub fn unbind_channel(&mut self, handle: BindHandle) -> Result<(), Error> {
+        let index = self
+            .extra_contexts
+            .iter()
+            .position(|(id, _)| *id == handle.id)
+            .ok_or(Error::NoContext)?;
+
+        let (_, mut context) = self.extra_contexts.remove(index);
+
+        ResultCode(unsafe { ctru_sys::udsUnbind(&mut context as *mut _) })?;
+
+        Ok(())
+    }
+
+    /// Pull a packet received by a context bound with [`Uds::bind_channel()`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::NoContext`] if the context has been unbound (e.g. after disconnecting from the network).
+    #[doc(alias = "udsPullPacket")]
+    pub fn pull_packet_from(
+        &self,
+        handle: &BindHandle,
+    ) -> Result<Option<(Vec<u8>, NodeID)>, Error> {
+        Self::pull_packet_with(self.bound_context(handle)?)
+    }
+
+    /// Wait for data to be available on a context bound with [`Uds::bind_channel()`].
+    ///
+    /// `next` and `wait` behave as in [`Uds::wait_data_available()`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::NoContext`] if the context has been unbound (e.g. after disconnecting from the network).
+    #[doc(alias = "udsWaitDataAvailable")]
+    pub fn wait_data_available_on(
+        &self,
+        handle: &BindHandle,
+        next: bool,
+        wait: bool,
+    ) -> Result<bool, Error> {
+        let context = self.bound_context(handle)?;
+
+        Ok(unsafe { ctru_sys::udsWaitDataAvailable(context as *const _, next, wait) })
+    }
+
+    fn bound_context(&self, handle: &BindHandle) -> Result<&ctru_sys::udsBindContext, Error> {
+        self.extra_contexts
+            .iter()
+            .find(|(id, _)| *id == handle.id)
+            .map(|(_, context)| context)
+            .ok_or(Error::NoContext)
+    }
+
+    fn unbind_all_channels(&mut self) -> Result<(), Error> {
+        while let Some((_, mut context)) = self.extra_contexts.pop() {
+            ResultCode(unsafe { ctru_sys::udsUnbind(&mut context as *mut _) })?;
+        }
+
+        Ok(())
+    }
+
     /// Eject a client from the network.
     ///
     /// # Errors
@@ -1367,6 +2133,67 @@ impl Uds {
 
         Ok(info.into())
     }
+
+    /// Returns the [`NodeInfo`] of all the other nodes connected to the network, ordered by node ID.
+    ///
+    /// The node of this console (the host, when hosting the network) is not included.
+    /// Nodes whose information can't be retrieved (e.g. because they disconnected in the meantime) are skipped.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the service is currently neither connected to nor hosting a network.
+    /// See [`Uds::connect_network()`] to connect to a network or [`Uds::create_network()`] to create one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::uds::Uds;
+    /// let mut uds = Uds::new(None)?;
+    ///
+    /// uds.create_network(b"HBW\x10", None, None, b"udsdemo passphrase c186093cd2652741\0", 4)?;
+    ///
+    /// for (id, info) in uds.nodes()? {
+    ///     println!("{id:?}: {}", info.username());
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "udsGetNodeInformation")]
+    pub fn nodes(&self) -> Result<Vec<(NodeID, NodeInfo)>, Error> {
+        if self.service_status() == ServiceStatus::Disconnected {
+            return Err(Error::NotConnected);
+        }
+
+        let status = self.connection_status()?;
+        let bitmask = status.node_bitmask();
+
+        // Bit `n` of the bitmask is set if the node with ID `n + 1` is connected.
+        let nodes = (0..Self::MAX_NODES)
+            .filter(|bit| bitmask & (1 << bit) != 0)
+            .map(|bit| NodeID::Node(bit + 1))
+            .filter(|&id| id != status.cur_node_id())
+            .filter_map(|id| self.node_info(id).ok().map(|info| (id, info)))
+            .collect();
+
+        Ok(nodes)
+    }
+}
+
+/// Check that `packet` fits in a single UDS data frame.
+fn check_packet_size(packet: &[u8]) -> Result<(), Error> {
+    if packet.len() > Uds::MAX_PACKET_SIZE {
+        return Err(Error::PacketTooLarge {
+            provided: packet.len(),
+            max: Uds::MAX_PACKET_SIZE,
+        });
+    }
+
+    Ok(())
 }
 
 impl Drop for Uds {
@@ -1380,3 +2207,19 @@ impl Drop for Uds {
         // ctru_sys::udsExit() is called by the ServiceHandle
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn send_packet_too_large() {
+        let packet = vec![0; Uds::MAX_PACKET_SIZE + 1];
+
+        assert!(matches!(
+            check_packet_size(&packet),
+            Err(Error::PacketTooLarge { provided, max: Uds::MAX_PACKET_SIZE }) if provided == packet.len()
+        ));
+        assert!(check_packet_size(&packet[..Uds::MAX_PACKET_SIZE]).is_ok());
+    }
+}
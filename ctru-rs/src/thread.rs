@@ -0,0 +1,348 @@
+//! Thread utilities.
+//!
+//! This module contains helpers built on top of [`std::thread`] and the Horizon-specific
+//! [`BuilderExt`](std::os::horizon::thread::BuilderExt) extension, which allows choosing the processor a thread runs on.
+//!
+//! # Notes
+//!
+//! The Old3DS exposes 2 cores to applications: the application core (core #0) and the system core (core #1).
+//! New3DS systems have an additional application-usable core (core #2).
+//! Threads can be spawned on the system core only after lending it some CPU time with [`Apt::set_app_cpu_time_limit()`](crate::services::apt::Apt::set_app_cpu_time_limit).
+//!
+//! Threads are named like in [`std::thread`]: since `libctru` threads don't carry names, the name set with
+//! [`Builder::name()`](thread::Builder::name) is kept by the standard library, and returned by [`Thread::name()`](thread::Thread::name).
+//! The workers of a [`Pool`] are named `pool-worker-<index>`.
+//!
+//! Scoped threads, which can borrow data from the spawning thread's stack, are available through [`std::thread::scope()`]
+//! (and [`spawn_scoped_with_stack_size()`] to keep track of their stack).
+//!
+//! ```
+//! # let _runner = test_runner::GdbRunner::default();
+//! # use std::error::Error;
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! #
+//! use std::thread;
+//!
+//! let handle = thread::Builder::new()
+//!     .name(String::from("loader"))
+//!     .spawn(|| thread::current().name().map(String::from))?;
+//!
+//! assert_eq!(handle.join().unwrap().as_deref(), Some("loader"));
+//! #
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! This module is only available with the `std-threads` feature.
+#![doc(alias = "threading")]
+
+use std::cell::Cell;
+use std::os::horizon::thread::BuilderExt;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle, Scope, ScopedJoinHandle};
+
+/// ID of the application core.
+const APP_CORE: i32 = 0;
+/// ID of the system core.
+const SYS_CORE: i32 = 1;
+/// ID of the additional application core available on New3DS systems.
+const NEW3DS_APP_CORE: i32 = 2;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+thread_local! {
+    /// Approximate top address and size of the current thread's stack, if known.
+    static STACK_BOUNDS: Cell<Option<(usize, usize)>> = const { Cell::new(None) };
+}
+
+/// Strategy used by a [`Pool`] to assign its workers to the console's cores.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CoreAssignment {
+    /// All workers run on the application core (core #0).
+    AppCore,
+    /// All workers run on the system core (core #1).
+    ///
+    /// [`Apt::set_app_cpu_time_limit()`](crate::services::apt::Apt::set_app_cpu_time_limit) must be called before creating the [`Pool`].
+    SysCore,
+    /// Workers are spread over all the application-usable cores (core #0, and core #2 on New3DS systems), one worker per core.
+    ///
+    /// The number of workers is capped to the number of available cores, so an Old3DS will use a single worker.
+    Spread,
+}
+
+impl CoreAssignment {
+    /// Returns the processor IDs available for this assignment strategy on the current console.
+    fn cores(self) -> Vec<i32> {
+        match self {
+            Self::AppCore => vec![APP_CORE],
+            Self::SysCore => vec![SYS_CORE],
+            Self::Spread => {
+                // On failure, fall back to the capabilities of an Old3DS.
+                if crate::os::is_new_3ds().unwrap_or(false) {
+                    vec![APP_CORE, NEW3DS_APP_CORE]
+                } else {
+                    vec![APP_CORE]
+                }
+            }
+        }
+    }
+}
+
+/// Fixed-size pool of worker threads pinned to the console's cores.
+///
+/// Jobs submitted with [`Pool::spawn()`] are run by the first idle worker, in submission order.
+/// Dropping the pool waits for all submitted jobs to finish and joins the workers.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use std::sync::mpsc;
+/// use ctru::thread::{CoreAssignment, Pool};
+///
+/// let pool = Pool::new(2, CoreAssignment::Spread)?;
+/// let (tx, rx) = mpsc::channel();
+///
+/// for i in 0..8u32 {
+///     let tx = tx.clone();
+///     pool.spawn(move || tx.send(i * i).unwrap());
+/// }
+/// drop(tx);
+///
+/// let sum: u32 = rx.iter().sum();
+/// assert_eq!(sum, 140);
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub struct Pool {
+    // Wrapped in an `Option` to be able to close the channel before joining the workers.
+    sender: Option<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl Pool {
+    /// Spawn a new pool with (at most) `workers` threads, assigned to the console's cores following the chosen [`CoreAssignment`].
+    ///
+    /// With [`CoreAssignment::Spread`], the number of workers is capped to the number of cores available on the console.
+    /// At least one worker is always spawned.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a worker thread couldn't be spawned
+    /// (for example when trying to spawn threads on the system core without lending it CPU time first).
+    /// Any worker spawned before the error is joined before returning.
+    pub fn new(workers: usize, affinity: CoreAssignment) -> std::io::Result<Self> {
+        let cores = affinity.cores();
+
+        let workers = match affinity {
+            CoreAssignment::Spread => workers.clamp(1, cores.len()),
+            CoreAssignment::AppCore | CoreAssignment::SysCore => workers.max(1),
+        };
+
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        // Spawning a worker may fail, in which case the partially built pool is dropped (and its workers joined).
+        let mut pool = Self {
+            sender: Some(sender),
+            workers: Vec::with_capacity(workers),
+        };
+
+        for (index, &core) in cores.iter().cycle().take(workers).enumerate() {
+            let receiver = Arc::clone(&receiver);
+
+            let handle = thread::Builder::new()
+                .name(format!("pool-worker-{index}"))
+                .processor_id(core)
+                .spawn(move || worker_loop(&receiver))?;
+
+            pool.workers.push(handle);
+        }
+
+        Ok(pool)
+    }
+
+    /// Submit a job to be run by one of the pool's workers.
+    ///
+    /// # Notes
+    ///
+    /// A panic inside the job terminates the worker running it. The other workers keep processing the remaining jobs.
+    pub fn spawn<F>(&self, job: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        // The receiving end is only dropped once all workers exit, which can happen only if they all panicked.
+        let _ = self
+            .sender
+            .as_ref()
+            .expect("pool sender is only taken on drop")
+            .send(Box::new(job));
+    }
+
+    /// Returns the number of worker threads in the pool.
+    pub fn workers(&self) -> usize {
+        self.workers.len()
+    }
+}
+
+impl Drop for Pool {
+    fn drop(&mut self) {
+        // Closing the channel makes the workers exit once the queued jobs are done.
+        drop(self.sender.take());
+
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn worker_loop(receiver: &Mutex<Receiver<Job>>) {
+    loop {
+        // The lock is released before running the job, so other workers can pick up jobs in the meantime.
+        let job = match receiver.lock() {
+            Ok(receiver) => receiver.recv(),
+            Err(_) => return,
+        };
+
+        match job {
+            Ok(job) => job(),
+            Err(_) => return,
+        }
+    }
+}
+
+/// Spawn a thread with the given stack size (in bytes), keeping track of its stack so that [`remaining_stack()`] can be used within it.
+///
+/// The stack size set on `builder` (if any) is overridden with `stack_size`.
+///
+/// # Errors
+///
+/// This function will return an error if the thread couldn't be spawned, as for [`Builder::spawn()`](thread::Builder::spawn).
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use std::thread::Builder;
+/// use ctru::thread;
+///
+/// let handle = thread::spawn_with_stack_size(Builder::new(), 0x8000, || {
+///     thread::remaining_stack().is_some()
+/// })?;
+///
+/// assert!(handle.join().unwrap());
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn spawn_with_stack_size<F, T>(
+    builder: thread::Builder,
+    stack_size: usize,
+    f: F,
+) -> std::io::Result<JoinHandle<T>>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    builder.stack_size(stack_size).spawn(move || {
+        // The first frame of the thread sits right below the top of its stack.
+        STACK_BOUNDS.with(|bounds| bounds.set(Some((stack_pointer(), stack_size))));
+
+        f()
+    })
+}
+
+/// Spawn a scoped thread with the given stack size (in bytes), keeping track of its stack so that [`remaining_stack()`] can be used within it.
+///
+/// This is the scoped counterpart of [`spawn_with_stack_size()`]: the thread runs within `scope` (see [`std::thread::scope()`]),
+/// so it can borrow data from the stack of the spawning thread. The stack size set on `builder` (if any) is overridden with `stack_size`,
+/// while the priority and processor set via [`BuilderExt`] are kept.
+///
+/// # Errors
+///
+/// This function will return an error if the thread couldn't be spawned, as for [`Builder::spawn_scoped()`](thread::Builder::spawn_scoped).
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use std::os::horizon::thread::BuilderExt;
+/// use std::thread::Builder;
+/// use ctru::thread;
+///
+/// let mut samples = [1u32, 2, 3, 4];
+///
+/// std::thread::scope(|s| -> std::io::Result<()> {
+///     let builder = Builder::new().priority(std::os::horizon::thread::current_priority());
+///
+///     // The thread borrows `samples`, which outlives the scope.
+///     thread::spawn_scoped_with_stack_size(builder, s, 0x4000, || {
+///         samples.iter_mut().for_each(|sample| *sample *= 2);
+///     })?;
+///
+///     Ok(())
+/// })?;
+///
+/// // All scoped threads are joined at the end of the scope.
+/// assert_eq!(samples, [2, 4, 6, 8]);
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn spawn_scoped_with_stack_size<'scope, 'env, F, T>(
+    builder: thread::Builder,
+    scope: &'scope Scope<'scope, 'env>,
+    stack_size: usize,
+    f: F,
+) -> std::io::Result<ScopedJoinHandle<'scope, T>>
+where
+    F: FnOnce() -> T + Send + 'scope,
+    T: Send + 'scope,
+{
+    builder.stack_size(stack_size).spawn_scoped(scope, move || {
+        STACK_BOUNDS.with(|bounds| bounds.set(Some((stack_pointer(), stack_size))));
+
+        f()
+    })
+}
+
+/// Returns an estimate of the stack space (in bytes) left to the current thread, or `None` if it is unknown.
+///
+/// The stack bounds are only known for threads spawned with [`spawn_with_stack_size()`] or [`spawn_scoped_with_stack_size()`].
+/// Recursive code can use this function to stop before the stack overflows, which on the 3DS happens silently
+/// (there are no guard pages below thread stacks, so an overflow corrupts whatever memory lies beneath).
+///
+/// # Notes
+///
+/// This is only an estimate, to be used with a generous safety margin:
+///
+/// - The top of the stack is recorded when the thread starts running its closure, so the space used by the thread's
+///   start-up code (and by the thread-local storage `libctru` places at the top of the stack) is not accounted for.
+/// - The current position is taken from a local variable of this function, which may differ slightly from the real stack pointer
+///   of the caller.
+/// - The main thread's stack bounds are not tracked, so this function always returns `None` on it.
+pub fn remaining_stack() -> Option<usize> {
+    let (top, size) = STACK_BOUNDS.with(Cell::get)?;
+    let used = top.saturating_sub(stack_pointer());
+
+    Some(size.saturating_sub(used))
+}
+
+/// Returns an approximation of the current stack pointer.
+#[inline(never)]
+fn stack_pointer() -> usize {
+    let marker = 0u8;
+
+    std::hint::black_box(&marker) as *const u8 as usize
+}
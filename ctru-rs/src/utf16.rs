@@ -0,0 +1,204 @@
+//! UTF-16 string buffers.
+//!
+//! Many `libctru` functions (especially the ones used by [applets](crate::applets)) expect nul-terminated UTF-16 strings
+//! stored in fixed-size arrays. The types in this module allow writing Rust strings into such buffers without allocating.
+
+use std::fmt;
+
+/// [`fmt::Write`] implementation encoding text as UTF-16 into a borrowed, nul-terminated buffer.
+///
+/// The last code unit of the buffer is always reserved for the nul terminator.
+/// Text that doesn't fit in the buffer is truncated at a character boundary.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// use std::fmt::Write;
+/// use ctru::utf16::Utf16Writer;
+///
+/// let mut buffer = [0u16; 8];
+/// let mut writer = Utf16Writer::new(&mut buffer);
+///
+/// write!(writer, "{} + {}", 1, 2).unwrap();
+///
+/// // The text doesn't fit, so it gets truncated.
+/// assert!(write!(writer, " = {}", 3).is_err());
+/// assert!(writer.is_truncated());
+/// assert_eq!(writer.as_slice(), "1 + 2 =".encode_utf16().collect::<Vec<_>>());
+/// ```
+#[derive(Debug)]
+pub struct Utf16Writer<'a> {
+    buffer: &'a mut [u16],
+    len: usize,
+    truncated: bool,
+}
+
+impl<'a> Utf16Writer<'a> {
+    /// Create a new writer over `buffer`, clearing its previous contents.
+    pub fn new(buffer: &'a mut [u16]) -> Self {
+        if let Some(first) = buffer.first_mut() {
+            *first = 0;
+        }
+
+        Self {
+            buffer,
+            len: 0,
+            truncated: false,
+        }
+    }
+
+    /// Returns the maximum number of code units the buffer can hold, excluding the nul terminator.
+    pub fn capacity(&self) -> usize {
+        self.buffer.len().saturating_sub(1)
+    }
+
+    /// Returns the number of code units written so far, excluding the nul terminator.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if nothing has been written yet.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if some text was truncated to fit in the buffer.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Returns the code units written so far, excluding the nul terminator.
+    pub fn as_slice(&self) -> &[u16] {
+        &self.buffer[..self.len]
+    }
+}
+
+impl fmt::Write for Utf16Writer<'_> {
+    /// Append `s` to the buffer.
+    ///
+    /// Returns an error if the text had to be truncated.
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let capacity = self.capacity();
+
+        for c in s.chars() {
+            let mut units = [0; 2];
+            let units = c.encode_utf16(&mut units);
+
+            if self.len + units.len() > capacity {
+                self.truncated = true;
+                break;
+            }
+
+            self.buffer[self.len..self.len + units.len()].copy_from_slice(units);
+            self.len += units.len();
+        }
+
+        if let Some(terminator) = self.buffer.get_mut(self.len) {
+            *terminator = 0;
+        }
+
+        if self.truncated {
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Owned, fixed-capacity and nul-terminated UTF-16 string buffer.
+///
+/// The buffer holds up to `N - 1` code units, since the last code unit is reserved for the nul terminator.
+/// Like [`Utf16Writer`], text that doesn't fit in the buffer is truncated at a character boundary.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// use std::fmt::Write;
+/// use ctru::utf16::Utf16Buffer;
+///
+/// let mut text = Utf16Buffer::<64>::new();
+/// write!(text, "Score: {}", 100).unwrap();
+///
+/// // The slice includes the nul terminator, as expected by `libctru`.
+/// assert_eq!(text.as_slice().last(), Some(&0));
+/// assert_eq!(text.len(), 10);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Utf16Buffer<const N: usize> {
+    data: [u16; N],
+}
+
+impl<const N: usize> Utf16Buffer<N> {
+    /// Create a new empty buffer.
+    ///
+    /// # Notes
+    ///
+    /// Fails to compile if `N` is 0, since there would be no space for the nul terminator.
+    pub const fn new() -> Self {
+        const { assert!(N > 0, "a Utf16Buffer needs space for the nul terminator") };
+
+        Self { data: [0; N] }
+    }
+
+    /// Create a new buffer holding `text`, truncated at a character boundary if it doesn't fit.
+    pub fn from_str_truncated(text: &str) -> Self {
+        let mut buffer = Self::new();
+
+        let _ = fmt::Write::write_str(&mut buffer.writer(), text);
+
+        buffer
+    }
+
+    /// Returns a [`Utf16Writer`] view over the buffer, clearing its previous contents.
+    pub fn writer(&mut self) -> Utf16Writer<'_> {
+        Utf16Writer::new(&mut self.data)
+    }
+
+    /// Returns the number of code units in the buffer, excluding the nul terminator.
+    ///
+    /// The text ends at the first nul code unit, so anything written after an embedded nul (e.g. `"a\0b"`) isn't counted.
+    pub fn len(&self) -> usize {
+        // The buffer is always nul-terminated, since writers never overwrite the last code unit.
+        self.data.iter().position(|&c| c == 0).unwrap_or(N - 1)
+    }
+
+    /// Returns `true` if the buffer holds no text.
+    pub fn is_empty(&self) -> bool {
+        self.data[0] == 0
+    }
+
+    /// Returns a pointer to the nul-terminated string, to be passed to `libctru` functions.
+    pub fn as_ptr(&self) -> *const u16 {
+        self.data.as_ptr()
+    }
+
+    /// Returns the contents of the buffer, including the nul terminator.
+    pub fn as_slice(&self) -> &[u16] {
+        &self.data[..=self.len()]
+    }
+}
+
+impl<const N: usize> Default for Utf16Buffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Write for Utf16Buffer<N> {
+    /// Append `s` to the buffer.
+    ///
+    /// Returns an error if the text had to be truncated.
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let len = self.len();
+
+        let mut writer = Utf16Writer {
+            buffer: &mut self.data,
+            len,
+            truncated: false,
+        };
+
+        writer.write_str(s)
+    }
+}
@@ -0,0 +1,79 @@
+//! Colored test output.
+//!
+//! The pretty formatter of the `test` crate only colors its output if it finds a terminfo entry
+//! for the terminal, which the 3DS doesn't have. A minimal entry is bundled in the RomFS of the test runner
+//! (see the `romfs` directory), and the `TERM`/`TERMINFO` environment variables are set to point to it.
+
+use std::ffi::CStr;
+use std::path::Path;
+
+use test::ColorConfig;
+
+use crate::config_var;
+
+/// Environment variable used to configure the test output colors: `always` (the default), `never` or `auto`.
+pub(crate) const COLOR_ENV: &str = "CTRU_TEST_COLOR";
+
+/// Name of the terminfo entry bundled in the RomFS.
+const TERM: &str = "ctru-console";
+
+/// Name of the mount point of the application's RomFS, separate from the one used by `ctru::services::romfs::RomFS`
+/// so that tests can still mount it themselves.
+const ROMFS_MOUNT: &str = "test-runner";
+
+/// Terminfo database found in the RomFS. Dropping it unmounts the RomFS.
+pub(crate) struct Terminfo(());
+
+/// Returns the color configuration set via [`COLOR_ENV`].
+pub(crate) fn color_config() -> ColorConfig {
+    match config_var(COLOR_ENV, option_env!("CTRU_TEST_COLOR")).as_deref() {
+        None | Some("always") => ColorConfig::AlwaysColor,
+        Some("never") => ColorConfig::NeverColor,
+        Some("auto") => ColorConfig::AutoColor,
+        Some(other) => {
+            eprintln!("invalid value {other:?} for {COLOR_ENV}, using colors");
+            ColorConfig::AlwaysColor
+        }
+    }
+}
+
+impl Terminfo {
+    /// Point the `test` crate to the terminfo entry bundled in the RomFS, if it is available.
+    ///
+    /// Nothing is done if `TERM` is already set, or if the application has no RomFS.
+    /// The RomFS must stay mounted until the tests start running, since that's when the entry is read.
+    pub(crate) fn setup() -> Option<Self> {
+        if std::env::var_os("TERM").is_some() {
+            return None;
+        }
+
+        let result = unsafe { ctru_sys::romfsMountSelf(mount_name().as_ptr()) };
+        if ctru_sys::R_FAILED(result) {
+            return None;
+        }
+
+        let terminfo = Self(());
+
+        let dir = format!("{ROMFS_MOUNT}:/terminfo");
+
+        // Entries are stored in subdirectories named after their first letter.
+        if !Path::new(&dir).join(&TERM[..1]).join(TERM).exists() {
+            return None;
+        }
+
+        std::env::set_var("TERM", TERM);
+        std::env::set_var("TERMINFO", dir);
+
+        Some(terminfo)
+    }
+}
+
+impl Drop for Terminfo {
+    fn drop(&mut self) {
+        let _ = unsafe { ctru_sys::romfsUnmount(mount_name().as_ptr()) };
+    }
+}
+
+fn mount_name() -> &'static CStr {
+    CStr::from_bytes_with_nul(b"test-runner\0").unwrap()
+}
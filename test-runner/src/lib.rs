@@ -7,13 +7,16 @@
 #![feature(test)]
 #![feature(custom_test_frameworks)]
 #![feature(exitcode_exit_method)]
+#![feature(horizon_thread_ext)]
 #![test_runner(run_gdb)]
 
 extern crate test;
 
+mod color;
 mod console;
 mod gdb;
 mod socket;
+mod watchdog;
 
 use std::process::{ExitCode, Termination};
 
@@ -21,6 +24,7 @@ pub use console::ConsoleRunner;
 pub use gdb::GdbRunner;
 pub use socket::SocketRunner;
 use test::{ColorConfig, OutputFormat, TestDescAndFn, TestFn, TestOpts};
+use watchdog::Watchdog;
 
 /// Run tests using the [`GdbRunner`].
 /// This function can be used with the `#[test_runner]` attribute.
@@ -46,23 +50,30 @@ fn run<Runner: TestRunner>(tests: &[&TestDescAndFn]) {
     let mut runner = Runner::new();
     let ctx = runner.setup();
 
+    let color = color::color_config();
+    let terminfo = match color {
+        ColorConfig::NeverColor => None,
+        _ => color::Terminfo::setup(),
+    };
+
     let opts = TestOpts {
         force_run_in_process: true,
         run_tests: true,
-        // TODO: color doesn't work because of TERM/TERMINFO.
-        // With RomFS we might be able to fake this out nicely...
-        color: ColorConfig::AlwaysColor,
+        color,
         format: OutputFormat::Pretty,
         test_threads: Some(1),
-        // Hopefully this interface is more stable vs specifying individual options,
-        // and parsing the empty list of args should always work, I think.
-        // TODO Ideally we could pass actual std::env::args() here too
-        ..test::test::parse_opts(&[]).unwrap().unwrap()
+        ..parse_opts(&test_args())
     };
 
-    let tests = tests.iter().map(|t| make_owned_test(t)).collect();
+    let watchdog = Watchdog::spawn();
+
+    let tests = tests
+        .iter()
+        .map(|t| make_owned_test(t, watchdog.as_ref()))
+        .collect();
     let result = test::run_tests_console(&opts, tests);
 
+    drop(terminfo);
     drop(ctx);
 
     let reportable_result = match result {
@@ -74,15 +85,84 @@ fn run<Runner: TestRunner>(tests: &[&TestDescAndFn]) {
     let _ = runner.cleanup(reportable_result);
 }
 
+/// Environment variable used to pass arguments (such as test name filters) to the tests, e.g. `CTRU_TEST_ARGS="fs --skip romfs"`.
+///
+/// It is only used if the test executable was started without arguments.
+const ARGS_ENV: &str = "CTRU_TEST_ARGS";
+
+/// Returns the arguments of the test executable (starting with the program name), or the ones set via [`ARGS_ENV`].
+fn test_args() -> Vec<String> {
+    let mut args: Vec<String> = std::env::args().collect();
+
+    if args.len() <= 1 {
+        let program = args.pop().unwrap_or_else(|| String::from("test"));
+
+        args = std::iter::once(program)
+            .chain(
+                config_var(ARGS_ENV, option_env!("CTRU_TEST_ARGS"))
+                    .iter()
+                    .flat_map(|args| args.split_whitespace())
+                    .map(String::from),
+            )
+            .collect();
+    }
+
+    args
+}
+
+/// Parse the command line options of the `test` crate (such as name filters, `--exact` and `--skip`),
+/// falling back to the default options if `args` are invalid.
+fn parse_opts(args: &[String]) -> TestOpts {
+    match test::test::parse_opts(args) {
+        Some(Ok(opts)) => opts,
+        Some(Err(e)) => {
+            eprintln!(
+                "invalid test arguments {:?}: {e}",
+                args.get(1..).unwrap_or_default()
+            );
+            default_opts()
+        }
+        // The usage was printed because of `--help`.
+        None => default_opts(),
+    }
+}
+
+fn default_opts() -> TestOpts {
+    // Hopefully this interface is more stable vs specifying individual options,
+    // and parsing the empty list of args should always work, I think.
+    test::test::parse_opts(&[]).unwrap().unwrap()
+}
+
+/// Returns the value of the environment variable `name`, falling back to its value when the crate was built (`build_time`).
+///
+/// Configuration is also read at build time since 3DS applications usually have no environment of their own,
+/// e.g. `CTRU_TEST_TIMEOUT=10 cargo 3ds test`.
+fn config_var(name: &str, build_time: Option<&'static str>) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .or_else(|| build_time.map(String::from))
+}
+
 /// Adapted from [`test::make_owned_test`].
 /// Clones static values for putting into a dynamic vector, which `test_main()`
 /// needs to hand out ownership of tests to parallel test runners.
 ///
+/// If a [`Watchdog`] is given, tests are wrapped to be tracked by it (benchmarks aren't).
+///
 /// This will panic when fed any dynamic tests, because they cannot be cloned.
-fn make_owned_test(test: &TestDescAndFn) -> TestDescAndFn {
-    let testfn = match test.testfn {
-        TestFn::StaticTestFn(f) => TestFn::StaticTestFn(f),
-        TestFn::StaticBenchFn(f) => TestFn::StaticBenchFn(f),
+fn make_owned_test(test: &TestDescAndFn, watchdog: Option<&Watchdog>) -> TestDescAndFn {
+    let testfn = match (&test.testfn, watchdog) {
+        (&TestFn::StaticTestFn(f), None) => TestFn::StaticTestFn(f),
+        (&TestFn::StaticTestFn(f), Some(watchdog)) => {
+            let watchdog = watchdog.clone();
+            let name = test.desc.name.to_string();
+
+            TestFn::DynTestFn(Box::new(move || {
+                let _guard = watchdog.start(&name);
+                f()
+            }))
+        }
+        (&TestFn::StaticBenchFn(f), _) => TestFn::StaticBenchFn(f),
         _ => panic!("non-static tests passed to test::test_main_static"),
     };
 
@@ -141,6 +221,25 @@ mod link_fix {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    #[test]
+    fn name_filters() {
+        let args = ["test", "it_works", "--exact", "--skip", "it_fails"].map(String::from);
+        let opts = parse_opts(&args);
+
+        assert_eq!(opts.filters, ["it_works"]);
+        assert_eq!(opts.skip, ["it_fails"]);
+        assert!(opts.filter_exact);
+
+        // Invalid arguments are ignored.
+        let args = ["test", "--no-such-option"].map(String::from);
+        let opts = parse_opts(&args);
+
+        assert!(opts.filters.is_empty());
+        assert!(!opts.filter_exact);
+    }
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
@@ -1,28 +1,131 @@
+use std::io::Write;
+use std::net::{Shutdown, SocketAddr, TcpStream};
+use std::os::fd::AsRawFd;
+
 use ctru::prelude::*;
 
-use super::TestRunner;
+use super::{config_var, TestRunner};
+
+/// Environment variable used to configure the address of the host receiving the test output, e.g. `192.168.1.10:4000`.
+///
+/// If it isn't set, the output is sent to `3dslink`.
+pub(crate) const SOCKET_ENV: &str = "CTRU_TEST_SOCKET";
+
+/// Line written once all tests ran, just before the connection is closed.
+const END_MARKER: &str = "test-runner: done";
 
-/// Show test output via a network socket to `3dslink`. This runner is only useful
-/// on real hardware, since `3dslink` doesn't work with emulators.
+/// Show test output via a network socket. This runner is only useful
+/// on real hardware, since the emulators have no network access.
 ///
-/// See [`Soc::redirect_to_3dslink`] for more details.
+/// By default, the output is sent to `3dslink`, see [`Soc::redirect_to_3dslink`] for more details.
+/// If the `CTRU_TEST_SOCKET` environment variable is set (when building the tests), or if the runner is
+/// created with [`SocketRunner::with_addr()`], the output is sent to a TCP listener at the given address instead,
+/// such as `nc -l 4000` on the host.
+///
+/// # Protocol
+///
+/// Both `stdout` and `stderr` are written as plain text to the connection. Once all tests ran,
+/// the runner writes a last `test-runner: done` line and closes the connection, so the host knows the output is over
+/// (either by looking for the line, or by waiting for the end of the stream). The connection is still closed
+/// if the runner itself panics, but the last line is only written if the tests completed.
 ///
 /// [`Soc::redirect_to_3dslink`]: ctru::services::soc::Soc::redirect_to_3dslink
 pub struct SocketRunner {
+    // The stream must be closed before the network service.
+    stream: Option<TcpStream>,
     soc: Soc,
+    completed: bool,
 }
 
-impl TestRunner for SocketRunner {
-    type Context<'this> = &'this Soc;
+impl SocketRunner {
+    /// Create a runner sending the test output to the TCP listener at `addr`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the network service can't be initialized, or if the connection fails.
+    pub fn with_addr(addr: SocketAddr) -> Self {
+        let soc = Soc::new().expect("failed to initialize network service");
 
-    fn new() -> Self {
+        let stream =
+            TcpStream::connect(addr).unwrap_or_else(|e| panic!("failed to connect to {addr}: {e}"));
+
+        // Same as `3dslink`'s redirection, but to the given address.
+        for fd in [libc::STDOUT_FILENO, libc::STDERR_FILENO] {
+            if unsafe { libc::dup2(stream.as_raw_fd(), fd) } < 0 {
+                panic!(
+                    "failed to redirect to socket: {}",
+                    std::io::Error::last_os_error()
+                );
+            }
+        }
+
+        Self {
+            stream: Some(stream),
+            soc,
+            completed: false,
+        }
+    }
+
+    fn with_3dslink() -> Self {
         let mut soc = Soc::new().expect("failed to initialize network service");
         soc.redirect_to_3dslink(true, true)
             .expect("failed to redirect to socket");
-        Self { soc }
+
+        Self {
+            stream: None,
+            soc,
+            completed: false,
+        }
+    }
+}
+
+impl TestRunner for SocketRunner {
+    type Context<'this> = &'this Soc;
+
+    fn new() -> Self {
+        let Some(value) = config_var(SOCKET_ENV, option_env!("CTRU_TEST_SOCKET")) else {
+            return Self::with_3dslink();
+        };
+
+        match value.trim().parse() {
+            Ok(addr) => Self::with_addr(addr),
+            Err(e) => {
+                let runner = Self::with_3dslink();
+                eprintln!("invalid value {value:?} for {SOCKET_ENV} ({e}), using 3dslink");
+                runner
+            }
+        }
     }
 
     fn setup(&mut self) -> Self::Context<'_> {
         &self.soc
     }
+
+    fn cleanup<T: std::process::Termination>(mut self, test_result: T) -> T {
+        self.completed = true;
+
+        test_result
+    }
+}
+
+impl Drop for SocketRunner {
+    fn drop(&mut self) {
+        let mut stdout = std::io::stdout();
+
+        let _ = std::io::stderr().flush();
+        if self.completed {
+            let _ = writeln!(stdout, "{END_MARKER}");
+        }
+        let _ = stdout.flush();
+
+        // The standard streams hold duplicates of the socket, which must be closed too for the host to see the end of the stream.
+        unsafe {
+            libc::close(libc::STDOUT_FILENO);
+            libc::close(libc::STDERR_FILENO);
+        }
+
+        if let Some(stream) = self.stream.take() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
 }
@@ -0,0 +1,133 @@
+//! Per-test time budget.
+//!
+//! A hung test would otherwise block the whole run (and CI) indefinitely, since tests
+//! run in-process and can't be killed. The watchdog thread keeps track of the running test
+//! and exits the process with a failure code if it takes longer than the time budget.
+
+use std::io::Write;
+use std::os::horizon::thread::BuilderExt;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config_var;
+
+/// Environment variable used to configure the time budget of each test, in seconds.
+///
+/// A value of `0` disables the watchdog.
+pub(crate) const TIMEOUT_ENV: &str = "CTRU_TEST_TIMEOUT";
+
+/// Time budget used when [`TIMEOUT_ENV`] isn't set.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Exit code used by the stdlib test runner when tests fail.
+const FAILURE_EXIT_CODE: i32 = 101;
+
+/// Handle to the watchdog thread.
+#[derive(Clone)]
+pub(crate) struct Watchdog {
+    state: Arc<(Mutex<Option<RunningTest>>, Condvar)>,
+}
+
+struct RunningTest {
+    name: String,
+    started: Instant,
+}
+
+/// Marks the end of a test when dropped, even if the test panicked.
+pub(crate) struct TestGuard<'a> {
+    watchdog: &'a Watchdog,
+}
+
+impl Watchdog {
+    /// Spawn the watchdog thread, using the time budget configured via [`TIMEOUT_ENV`].
+    ///
+    /// Returns `None` if the watchdog is disabled.
+    pub(crate) fn spawn() -> Option<Self> {
+        let budget = budget()?;
+
+        let watchdog = Self {
+            state: Arc::new((Mutex::new(None), Condvar::new())),
+        };
+
+        let state = Arc::clone(&watchdog.state);
+
+        // The watchdog must be able to preempt tests stuck in a busy loop,
+        // so it runs with a higher priority than the thread running them.
+        std::thread::Builder::new()
+            .name(String::from("test watchdog"))
+            .priority(std::os::horizon::thread::current_priority() - 1)
+            .spawn(move || watch(&state, budget))
+            .expect("failed to spawn the test watchdog");
+
+        Some(watchdog)
+    }
+
+    /// Start tracking the test named `name`, until the returned guard is dropped.
+    pub(crate) fn start(&self, name: &str) -> TestGuard<'_> {
+        let (running, cvar) = &*self.state;
+
+        *running.lock().unwrap() = Some(RunningTest {
+            name: name.to_owned(),
+            started: Instant::now(),
+        });
+        cvar.notify_one();
+
+        TestGuard { watchdog: self }
+    }
+}
+
+impl Drop for TestGuard<'_> {
+    fn drop(&mut self) {
+        let (running, cvar) = &*self.watchdog.state;
+
+        *running.lock().unwrap() = None;
+        cvar.notify_one();
+    }
+}
+
+fn watch(state: &(Mutex<Option<RunningTest>>, Condvar), budget: Duration) {
+    let (running, cvar) = state;
+    let mut running = running.lock().unwrap();
+
+    loop {
+        let remaining = match &*running {
+            None => None,
+            Some(test) => match budget.checked_sub(test.started.elapsed()) {
+                Some(remaining) if !remaining.is_zero() => Some(remaining),
+                _ => {
+                    eprintln!(
+                        "\ntest {} has been running for more than {budget:?}, aborting",
+                        test.name
+                    );
+                    let _ = std::io::stderr().flush();
+                    let _ = std::io::stdout().flush();
+
+                    std::process::exit(FAILURE_EXIT_CODE);
+                }
+            },
+        };
+
+        running = match remaining {
+            None => cvar.wait(running).unwrap(),
+            Some(remaining) => cvar.wait_timeout(running, remaining).unwrap().0,
+        };
+    }
+}
+
+/// Returns the time budget of each test, or `None` if the watchdog is disabled.
+fn budget() -> Option<Duration> {
+    let value = config_var(TIMEOUT_ENV, option_env!("CTRU_TEST_TIMEOUT"));
+
+    let Some(value) = value else {
+        return Some(DEFAULT_TIMEOUT);
+    };
+
+    match value.trim().parse::<u64>() {
+        Ok(0) => None,
+        Ok(secs) => Some(Duration::from_secs(secs)),
+        Err(_) => {
+            eprintln!("invalid value {value:?} for {TIMEOUT_ENV}, using the default test timeout of {DEFAULT_TIMEOUT:?}");
+            Some(DEFAULT_TIMEOUT)
+        }
+    }
+}